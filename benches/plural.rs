@@ -0,0 +1,29 @@
+//! Compare the evaluation of the plural formulas by walking their tree and by running their compiled program.
+//!
+//! Run with `cargo bench --bench plural`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use locale_config::LanguageRange;
+use poreader::plural::PluralForms;
+
+const LANGUAGES: [&str; 3] = ["fr", "ru", "ar"];
+
+fn bench_get_value(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_value");
+
+    for lang in LANGUAGES {
+        let forms = PluralForms::for_language(&LanguageRange::new(lang).unwrap()).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("tree", lang), &forms, |b, forms| {
+            b.iter(|| (0..10_000).filter_map(|n| forms.get_value_by_tree(black_box(n))).sum::<usize>())
+        });
+        group.bench_with_input(BenchmarkId::new("compiled", lang), &forms, |b, forms| {
+            b.iter(|| (0..10_000).filter_map(|n| forms.get_value(black_box(n))).sum::<usize>())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_value);
+criterion_main!(benches);
@@ -0,0 +1,857 @@
+//! Catalogue of translation units held in memory.
+
+use crate::{
+    comment::Comment,
+    error::{Error, ErrorKind},
+    header::language_chain,
+    note::Note,
+    plural::PluralForms,
+    unit::Unit,
+    CatalogueReader, PoParser, State,
+};
+
+use locale_config::LanguageRange;
+use std::{collections::HashMap, mem::replace, slice::Iter, vec::IntoIter as VecIntoIter};
+
+/// Separator between the context and the message in the keys, like in MO files.
+const CONTEXT_SEPARATOR: char = '\x04';
+
+fn make_key(context: Option<&str>, id: &str) -> String {
+    match context {
+        Some(context) => format!("{}{}{}", context, CONTEXT_SEPARATOR, id),
+        None => id.to_string(),
+    }
+}
+
+/// Catalogue of translation units held in memory.
+///
+/// The units are kept in their original order, and they are indexed by their context and their message, and by their
+/// context and their plural message. A unit without context is different from a unit with an empty context.
+///
+/// A catalogue is read from any catalogue reader, and it can be written back with a catalogue writer as it implements
+/// [`IntoIterator`] with an iterator which is a [`CatalogueReader`].
+///
+/// Example:
+/// ```rust
+/// use poreader::{Catalog, PoParser};
+///
+/// let source = "msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"Ouvrir\"\n";
+/// let parser = PoParser::new();
+/// let catalog = Catalog::from_reader(parser.parse(source.as_bytes()).unwrap()).unwrap();
+///
+/// assert_eq!(
+///     catalog
+///         .get(Some("menu"), "Open")
+///         .map(|u| u.message().get_text()),
+///     Some("Ouvrir")
+/// );
+/// assert!(catalog.get(None, "Open").is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    units: Vec<Unit>,
+    index: HashMap<String, usize>,
+    plural_index: HashMap<String, usize>,
+    header_notes: Vec<Note>,
+    header_comments: Vec<Comment>,
+    header_properties: HashMap<String, String>,
+    header_source: Option<String>,
+    target_language: LanguageRange<'static>,
+    allow_fuzzy: bool,
+
+    /// Plural forms used for the translations, kept in sync with the header and the target language.
+    forms: Option<PluralForms>,
+}
+
+impl Catalog {
+    /// Create an empty catalogue, without header.
+    pub fn new() -> Catalog {
+        Catalog {
+            units: vec![],
+            index: HashMap::new(),
+            plural_index: HashMap::new(),
+            header_notes: vec![],
+            header_comments: vec![],
+            header_properties: HashMap::new(),
+            header_source: None,
+            target_language: LanguageRange::invariant(),
+            allow_fuzzy: false,
+            forms: None,
+        }
+    }
+
+    /// Read all units of a catalogue reader, with the header.
+    ///
+    /// Two units with the same context and the same message are an error.
+    pub fn from_reader<C: CatalogueReader>(reader: C) -> Result<Catalog, Error> {
+        let mut res = Catalog {
+            header_notes: reader.header_notes().clone(),
+            header_comments: reader.header_comments().clone(),
+            header_properties: reader.header_properties().clone(),
+            header_source: reader.header_source().map(String::from),
+            target_language: reader.target_language().clone(),
+            ..Catalog::new()
+        };
+
+        res.update_forms();
+
+        for unit in reader {
+            let unit = unit?;
+
+            if res.contains(unit.context(), unit.message().get_id()) {
+                let err = Error::new(
+                    ErrorKind::DuplicateEntry,
+                    format!("Duplicate message definition: {}", unit.message().get_id()),
+                );
+
+                return Err(match unit.field_span("msgid") {
+                    Some(span) => err.with_span(*span),
+                    None => err,
+                });
+            }
+
+            res.insert(unit);
+        }
+
+        Ok(res)
+    }
+
+    /// Use the fuzzy units for the translations, like the option `--use-fuzzy` of `msgfmt`.
+    pub fn set_allow_fuzzy(&mut self, allow: bool) {
+        self.allow_fuzzy = allow;
+    }
+
+    /// Set the target language, and the header `Language` with it.
+    ///
+    /// If the header has no `Plural-Forms`, or the standard plural forms of the previous target language, the standard
+    /// plural forms of the new language are set in the header (or removed if the language has none), which is handy
+    /// for new catalogues. Other plural forms are kept as they were written by the translators.
+    pub fn set_target_language(&mut self, language: LanguageRange<'static>) {
+        let name = language_chain(&language).into_iter().next().unwrap_or_default();
+        let previous = PluralForms::for_language(&self.target_language);
+        let is_standard = match self.header_properties.get("Plural-Forms").filter(|d| !d.is_empty()) {
+            Some(definition) => previous.is_some_and(|forms| forms.get_definition() == definition),
+            None => true,
+        };
+
+        self.set_header_property("Language", &name);
+        if is_standard {
+            match PluralForms::for_language(&language) {
+                Some(forms) => self.set_header_property("Plural-Forms", forms.get_definition()),
+                None => {
+                    if self.header_properties.remove("Plural-Forms").is_some() {
+                        self.header_source = None;
+                    }
+                }
+            }
+        }
+
+        self.target_language = language;
+        self.update_forms();
+    }
+
+    /// Get the plural forms of the header, or the standard plural forms of the target language without `Plural-Forms`.
+    pub(crate) fn plural_forms(&self) -> Result<Option<PluralForms>, Error> {
+        match self
+            .header_properties
+            .get("Plural-Forms")
+            .filter(|forms| !forms.is_empty())
+        {
+            Some(definition) => PluralForms::parse(definition, &PoParser::new()).map(Some),
+            None => Ok(PluralForms::for_language(&self.target_language)),
+        }
+    }
+
+    fn update_forms(&mut self) {
+        self.forms = self.plural_forms().ok().flatten();
+    }
+
+    /// Create an empty catalogue with the header of this one.
+    pub(crate) fn without_units(&self) -> Catalog {
+        Catalog {
+            header_notes: self.header_notes.clone(),
+            header_comments: self.header_comments.clone(),
+            header_properties: self.header_properties.clone(),
+            header_source: self.header_source.clone(),
+            target_language: self.target_language.clone(),
+            allow_fuzzy: self.allow_fuzzy,
+            forms: self.forms.clone(),
+            ..Catalog::new()
+        }
+    }
+
+    /// Set a property of the header, the original text of the header is then dropped.
+    pub(crate) fn set_header_property(&mut self, key: &str, value: &str) {
+        if self.header_properties.get(key).map(String::as_str) != Some(value) {
+            self.header_properties.insert(key.to_string(), value.to_string());
+            self.header_source = None;
+            if key == "Plural-Forms" {
+                self.update_forms();
+            }
+        }
+    }
+
+    /// Get the position of the unit with a context and a message.
+    pub(crate) fn position(&self, context: Option<&str>, id: &str) -> Option<usize> {
+        self.index.get(&make_key(context, id)).copied()
+    }
+
+    /// Get the unit with a context and a message.
+    pub fn get(&self, context: Option<&str>, id: &str) -> Option<&Unit> {
+        self.index.get(&make_key(context, id)).map(|pos| &self.units[*pos])
+    }
+
+    /// Get the unit with a context and a plural message.
+    pub fn get_by_plural(&self, context: Option<&str>, plural_id: &str) -> Option<&Unit> {
+        self.plural_index
+            .get(&make_key(context, plural_id))
+            .map(|pos| &self.units[*pos])
+    }
+
+    /// Returns whether the catalogue has a unit with a context and a message.
+    pub fn contains(&self, context: Option<&str>, id: &str) -> bool {
+        self.index.contains_key(&make_key(context, id))
+    }
+
+    /// Insert a unit, the unit with the same context and the same message is replaced at its place and returned.
+    /// Otherwise, the unit is added at the end.
+    pub fn insert(&mut self, unit: Unit) -> Option<Unit> {
+        let key = make_key(unit.context(), unit.message().get_id());
+        let plural_key = unit.message().get_plural_id().map(|p| make_key(unit.context(), p));
+
+        match self.index.get(&key) {
+            Some(pos) => {
+                let pos = *pos;
+                let old = replace(&mut self.units[pos], unit);
+
+                self.remove_plural_key(&old, pos);
+                if let Some(plural_key) = plural_key {
+                    self.plural_index.insert(plural_key, pos);
+                }
+
+                Some(old)
+            }
+            None => {
+                let pos = self.units.len();
+
+                self.index.insert(key, pos);
+                if let Some(plural_key) = plural_key {
+                    self.plural_index.insert(plural_key, pos);
+                }
+
+                self.units.push(unit);
+
+                None
+            }
+        }
+    }
+
+    /// Remove the unit with a context and a message, and return it.
+    pub fn remove(&mut self, context: Option<&str>, id: &str) -> Option<Unit> {
+        let pos = self.index.remove(&make_key(context, id))?;
+        let unit = self.units.remove(pos);
+
+        self.remove_plural_key(&unit, pos);
+        for value in self.index.values_mut().chain(self.plural_index.values_mut()) {
+            if *value > pos {
+                *value -= 1;
+            }
+        }
+
+        Some(unit)
+    }
+
+    /// Translate a message, the message itself is returned if it has no translation.
+    pub fn gettext<'a>(&'a self, id: &'a str) -> &'a str {
+        self.find_text(None, id).unwrap_or(id)
+    }
+
+    /// Translate a message in a context, the message itself is returned if it has no translation.
+    pub fn pgettext<'a>(&'a self, context: &str, id: &'a str) -> &'a str {
+        self.find_text(Some(context), id).unwrap_or(id)
+    }
+
+    /// Translate a message with a plural form for the number `count`.
+    ///
+    /// Without translation, the message `id` is returned if `count` is 1, the message `plural_id` otherwise.
+    pub fn ngettext<'a>(&'a self, id: &'a str, plural_id: &'a str, count: usize) -> &'a str {
+        self.find_plural_text(None, id, count)
+            .unwrap_or(if count == 1 { id } else { plural_id })
+    }
+
+    /// Translate a message in a context with a plural form for the number `count`.
+    ///
+    /// Without translation, the message `id` is returned if `count` is 1, the message `plural_id` otherwise.
+    pub fn npgettext<'a>(&'a self, context: &str, id: &'a str, plural_id: &'a str, count: usize) -> &'a str {
+        self.find_plural_text(Some(context), id, count)
+            .unwrap_or(if count == 1 { id } else { plural_id })
+    }
+
+    /// Get the unit which can be used for translating a message.
+    fn translation(&self, context: Option<&str>, id: &str) -> Option<&Unit> {
+        self.get(context, id).filter(|unit| {
+            !unit.is_obsolete() && (unit.is_translated() || (self.allow_fuzzy && unit.state() == State::NeedsWork))
+        })
+    }
+
+    /// Get the translation of a message, if there is one.
+    pub(crate) fn find_text(&self, context: Option<&str>, id: &str) -> Option<&str> {
+        self.translation(context, id)
+            .map(|unit| unit.message().get_text())
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Get the translation of a message with a plural form for the number `count`, if there is one.
+    ///
+    /// The form is given by the plural forms of the catalogue, or by the ones of the message if the catalogue has none.
+    pub(crate) fn find_plural_text(&self, context: Option<&str>, id: &str, count: usize) -> Option<&str> {
+        let plural = self.translation(context, id).and_then(|unit| unit.message().plural())?;
+        let index = self.forms.as_ref().or(plural.get_forms())?.get_value(count)?;
+
+        plural
+            .values()
+            .get(index)
+            .map(String::as_str)
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Check the plural forms of the header, and the number of translations of the plural messages.
+    ///
+    /// The formula of the header `Plural-Forms` must give all the forms below `nplurals` and no other one (see
+    /// [`PluralForms::check`]). Each plural message which is not obsolete must have `nplurals` translations, the
+    /// forms of the target language being used without `Plural-Forms` in the header (2 for an unknown language),
+    /// else an error of kind [`ErrorKind::PluralCountMismatch`] is given with the span of the message.
+    pub fn check_plurals(&self) -> Vec<Error> {
+        let mut res = vec![];
+        let count = match self.plural_forms() {
+            Ok(Some(forms)) => {
+                res.extend(forms.check().err());
+                forms.get_count()
+            }
+            Ok(None) => 2,
+            Err(err) => return vec![err],
+        };
+
+        for unit in self.units.iter().filter(|unit| !unit.is_obsolete()) {
+            if let Some(plural) = unit.message().plural().filter(|p| p.values().len() != count) {
+                let message = format!(
+                    "the message ‘{}’ has {} translations, but ‘nplurals’ is {}",
+                    plural.singular(),
+                    plural.values().len(),
+                    count
+                );
+                let err = Error::new(ErrorKind::PluralCountMismatch, message);
+
+                res.push(match unit.field_span("msgid_plural").or(unit.span()) {
+                    Some(span) => err.with_span(*span),
+                    None => err,
+                });
+            }
+        }
+
+        res
+    }
+
+    fn remove_plural_key(&mut self, unit: &Unit, pos: usize) {
+        if let Some(plural_id) = unit.message().get_plural_id() {
+            let plural_key = make_key(unit.context(), plural_id);
+
+            if self.plural_index.get(&plural_key) == Some(&pos) {
+                self.plural_index.remove(&plural_key);
+            }
+        }
+    }
+
+    /// Iterate on the units, in their original order.
+    pub fn iter(&self) -> Iter<'_, Unit> {
+        self.units.iter()
+    }
+
+    /// Number of units.
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Returns whether the catalogue has no unit.
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// The target language of the translation
+    pub fn target_language(&self) -> &LanguageRange<'static> {
+        &self.target_language
+    }
+
+    /// Notes in the header entry
+    pub fn header_notes(&self) -> &Vec<Note> {
+        &self.header_notes
+    }
+
+    /// Comments in the header entry
+    pub fn header_comments(&self) -> &Vec<Comment> {
+        &self.header_comments
+    }
+
+    /// Header properties as a map
+    pub fn header_properties(&self) -> &HashMap<String, String> {
+        &self.header_properties
+    }
+
+    /// Original text of the header entry, only kept if the catalogue was read in lossless mode
+    pub fn header_source(&self) -> Option<&str> {
+        self.header_source.as_deref()
+    }
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'c> IntoIterator for &'c Catalog {
+    type IntoIter = Iter<'c, Unit>;
+    type Item = &'c Unit;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Catalog {
+    type IntoIter = IntoIter;
+    type Item = Result<Unit, Error>;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            units: self.units.into_iter(),
+            header_notes: self.header_notes,
+            header_comments: self.header_comments,
+            header_properties: self.header_properties,
+            header_source: self.header_source,
+            target_language: self.target_language,
+        }
+    }
+}
+
+/// Iterator on the units of a catalogue, which is also a catalogue reader for writing the catalogue back.
+pub struct IntoIter {
+    units: VecIntoIter<Unit>,
+    header_notes: Vec<Note>,
+    header_comments: Vec<Comment>,
+    header_properties: HashMap<String, String>,
+    header_source: Option<String>,
+    target_language: LanguageRange<'static>,
+}
+
+impl Iterator for IntoIter {
+    type Item = Result<Unit, Error>;
+
+    fn next(&mut self) -> Option<Result<Unit, Error>> {
+        self.units.next().map(Ok)
+    }
+}
+
+impl CatalogueReader for IntoIter {
+    fn target_language(&self) -> &LanguageRange<'static> {
+        &self.target_language
+    }
+
+    fn header_notes(&self) -> &Vec<Note> {
+        &self.header_notes
+    }
+
+    fn header_comments(&self) -> &Vec<Comment> {
+        &self.header_comments
+    }
+
+    fn header_properties(&self) -> &HashMap<String, String> {
+        &self.header_properties
+    }
+
+    fn header_source(&self) -> Option<&str> {
+        self.header_source.as_deref()
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{plural::Plural, Message, PoParser, PoWriter};
+
+    const SOURCE: &str = r#"# Header comment
+msgid ""
+msgstr ""
+"Language: fr\n"
+"Plural-Forms: nplurals=2; plural=n > 1;\n"
+
+msgid "Open"
+msgstr "Ouvrir"
+
+msgctxt "menu"
+msgid "Open"
+msgstr "Ouvrir…"
+
+msgctxt ""
+msgid "Open"
+msgstr "Ouvre"
+
+msgid "One file"
+msgid_plural "%d files"
+msgstr[0] "Un fichier"
+msgstr[1] "%d fichiers"
+
+msgctxt "menu"
+msgid "Close"
+msgstr "Fermer"
+"#;
+
+    const TRANSLATIONS: &str = r#"#, fuzzy
+msgid "Save"
+msgstr "Enregistrer"
+
+msgctxt "menu"
+msgid "Quit"
+msgstr ""
+
+#, fuzzy
+msgctxt "menu"
+msgid "One window"
+msgid_plural "%d windows"
+msgstr[0] "Une fenêtre"
+msgstr[1] "%d fenêtres"
+
+#~ msgid "Old"
+#~ msgstr "Vieux"
+"#;
+
+    fn make_catalog() -> Catalog {
+        parse_catalog(SOURCE)
+    }
+
+    fn parse_catalog(source: &str) -> Catalog {
+        let parser = PoParser::new();
+
+        match Catalog::from_reader(parser.parse(source.as_bytes()).unwrap()) {
+            Ok(catalog) => catalog,
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    fn ids(catalog: &Catalog) -> Vec<(Option<&str>, &str)> {
+        catalog.iter().map(|u| (u.context(), u.message().get_id())).collect()
+    }
+
+    fn text<'c>(catalog: &'c Catalog, context: Option<&str>, id: &str) -> Option<&'c str> {
+        catalog.get(context, id).map(|u| u.message().get_text())
+    }
+
+    #[test]
+    fn test_func_new() {
+        let catalog = Catalog::default();
+
+        assert!(catalog.is_empty());
+        assert_eq!(catalog.len(), 0);
+        assert_eq!(catalog.target_language(), &LanguageRange::invariant());
+        assert!(catalog.header_properties().is_empty());
+        assert_eq!(catalog.header_source(), None);
+    }
+
+    #[test]
+    fn test_func_from_reader() {
+        let catalog = make_catalog();
+
+        assert_eq!(catalog.len(), 5);
+        assert_eq!(catalog.target_language().as_ref(), "fr");
+        assert_eq!(catalog.header_comments().len() + catalog.header_notes().len(), 1);
+        assert_eq!(
+            catalog.header_properties().get("Language").map(String::as_str),
+            Some("fr")
+        );
+        assert_eq!(
+            ids(&catalog),
+            vec![
+                (None, "Open"),
+                (Some("menu"), "Open"),
+                (Some(""), "Open"),
+                (None, "One file"),
+                (Some("menu"), "Close"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_func_from_reader_with_duplicate() {
+        let parser = PoParser::new();
+        let source = format!("{}\nmsgctxt \"menu\"\nmsgid \"Close\"\nmsgstr \"Quitter\"\n", SOURCE);
+
+        match Catalog::from_reader(parser.parse(source.as_bytes()).unwrap()) {
+            Err(err) => {
+                assert_eq!(err.kind(), ErrorKind::DuplicateEntry);
+                assert_eq!(err.line(), 28);
+            }
+            Ok(catalog) => panic!("Unexpected catalog: {:?}", ids(&catalog)),
+        }
+    }
+
+    #[test]
+    fn test_func_get() {
+        let catalog = make_catalog();
+
+        assert_eq!(text(&catalog, None, "Open"), Some("Ouvrir"));
+        assert_eq!(text(&catalog, Some("menu"), "Open"), Some("Ouvrir…"));
+        assert_eq!(text(&catalog, Some(""), "Open"), Some("Ouvre"));
+        assert_eq!(text(&catalog, Some("menu"), "Close"), Some("Fermer"));
+        assert_eq!(text(&catalog, None, "Close"), None);
+        assert!(catalog.contains(None, "One file"));
+        assert!(!catalog.contains(None, "%d files"));
+    }
+
+    #[test]
+    fn test_func_get_by_plural() {
+        let catalog = make_catalog();
+
+        assert_eq!(
+            catalog.get_by_plural(None, "%d files").map(|u| u.message().get_id()),
+            Some("One file")
+        );
+        assert!(catalog.get_by_plural(Some("menu"), "%d files").is_none());
+        assert!(catalog.get_by_plural(None, "One file").is_none());
+    }
+
+    #[test]
+    fn test_func_insert() {
+        let mut catalog = make_catalog();
+        let mut other = make_catalog();
+        let unit = other.remove(Some("menu"), "Close").unwrap();
+        let plural = other.remove(None, "One file").unwrap();
+
+        assert_eq!(
+            catalog.insert(unit.clone()).map(|u| u.message().get_id().to_string()),
+            Some(String::from("Close"))
+        );
+        assert_eq!(catalog.len(), 5);
+
+        catalog.remove(Some("menu"), "Close");
+        catalog.remove(None, "One file");
+        assert!(catalog.get_by_plural(None, "%d files").is_none());
+        assert!(catalog.insert(plural).is_none());
+        assert!(catalog.insert(unit).is_none());
+        assert_eq!(
+            ids(&catalog),
+            vec![
+                (None, "Open"),
+                (Some("menu"), "Open"),
+                (Some(""), "Open"),
+                (None, "One file"),
+                (Some("menu"), "Close"),
+            ]
+        );
+        assert_eq!(
+            catalog.get_by_plural(None, "%d files").map(|u| u.message().get_id()),
+            Some("One file")
+        );
+    }
+
+    #[test]
+    fn test_func_remove() {
+        let mut catalog = make_catalog();
+
+        assert_eq!(
+            catalog
+                .remove(Some("menu"), "Open")
+                .map(|u| u.message().get_text().to_string()),
+            Some(String::from("Ouvrir…"))
+        );
+        assert!(catalog.remove(Some("menu"), "Open").is_none());
+        assert_eq!(catalog.len(), 4);
+        assert_eq!(text(&catalog, Some("menu"), "Close"), Some("Fermer"));
+        assert_eq!(
+            catalog.get_by_plural(None, "%d files").map(|u| u.message().get_id()),
+            Some("One file")
+        );
+
+        catalog.remove(None, "One file");
+        assert!(catalog.get_by_plural(None, "%d files").is_none());
+        assert_eq!(
+            ids(&catalog),
+            vec![(None, "Open"), (Some(""), "Open"), (Some("menu"), "Close")]
+        );
+    }
+
+    #[test]
+    fn test_func_gettext() {
+        let mut catalog = parse_catalog(&format!("{}\n{}", SOURCE, TRANSLATIONS));
+
+        assert_eq!(catalog.gettext("Open"), "Ouvrir");
+        assert_eq!(catalog.gettext("Close"), "Close");
+        assert_eq!(catalog.gettext("Save"), "Save");
+        assert_eq!(catalog.gettext("Old"), "Old");
+        assert_eq!(catalog.pgettext("menu", "Open"), "Ouvrir…");
+        assert_eq!(catalog.pgettext("", "Open"), "Ouvre");
+        assert_eq!(catalog.pgettext("menu", "Quit"), "Quit");
+        assert_eq!(catalog.pgettext("other", "Open"), "Open");
+
+        catalog.set_allow_fuzzy(true);
+        assert_eq!(catalog.gettext("Save"), "Enregistrer");
+        assert_eq!(catalog.gettext("Old"), "Old");
+    }
+
+    #[test]
+    fn test_func_ngettext() {
+        let mut catalog = parse_catalog(&format!("{}\n{}", SOURCE, TRANSLATIONS));
+
+        assert_eq!(catalog.ngettext("One file", "%d files", 0), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "%d files", 1), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "%d files", 2), "%d fichiers");
+        assert_eq!(catalog.ngettext("Open", "Opens", 2), "Opens");
+        assert_eq!(catalog.ngettext("One dir", "%d dirs", 1), "One dir");
+        assert_eq!(catalog.ngettext("One dir", "%d dirs", 3), "%d dirs");
+        assert_eq!(catalog.npgettext("menu", "One window", "%d windows", 1), "One window");
+        assert_eq!(catalog.npgettext("menu", "One window", "%d windows", 5), "%d windows");
+
+        catalog.set_allow_fuzzy(true);
+        assert_eq!(catalog.npgettext("menu", "One window", "%d windows", 1), "Une fenêtre");
+        assert_eq!(catalog.npgettext("menu", "One window", "%d windows", 5), "%d fenêtres");
+        assert_eq!(catalog.npgettext("", "One window", "%d windows", 5), "%d windows");
+
+        let mut catalog = Catalog::new();
+
+        catalog.set_header_property("Plural-Forms", "nplurals=2; plural=(n > 1);");
+        catalog.insert(Unit::new(
+            None,
+            Message::Plural(Plural::new(
+                String::from("One file"),
+                String::from("%d files"),
+                vec![String::from("Un fichier"), String::from("%d fichiers")],
+                None,
+            )),
+        ));
+        assert_eq!(catalog.ngettext("One file", "%d files", 1), "Un fichier");
+        assert_eq!(catalog.ngettext("One file", "%d files", 5), "%d fichiers");
+
+        catalog.set_header_property("Plural-Forms", "nplurals=2; plural=0;");
+        assert_eq!(catalog.ngettext("One file", "%d files", 5), "Un fichier");
+    }
+
+    #[test]
+    fn test_func_check_plurals() {
+        let messages = |source: &str| -> Vec<(ErrorKind, usize, String)> {
+            parse_catalog(source)
+                .check_plurals()
+                .iter()
+                .map(|err| (err.kind(), err.line(), err.message().to_string()))
+                .collect()
+        };
+        let kind = ErrorKind::InvalidPluralFormula { offset: Some(19) };
+
+        assert!(make_catalog().check_plurals().is_empty());
+        assert_eq!(
+            messages(
+                "msgid \"\"\nmsgstr \"\"\n\"Plural-Forms: nplurals=3; plural=n > 1;\\n\"\n\n\
+                 msgid \"One file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"Un fichier\"\nmsgstr[1] \"%d fichiers\"\n\n\
+                 #~ msgid \"Old\"\n#~ msgid_plural \"Olds\"\n#~ msgstr[0] \"Vieux\"\n"
+            ),
+            vec![
+                (kind, 0, String::from("the plural formula never gives the forms 2")),
+                (
+                    ErrorKind::PluralCountMismatch,
+                    6,
+                    String::from("the message ‘One file’ has 2 translations, but ‘nplurals’ is 3")
+                ),
+            ]
+        );
+        assert_eq!(
+            messages("msgid \"One file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"Un fichier\"\n"),
+            vec![(
+                ErrorKind::PluralCountMismatch,
+                2,
+                String::from("the message ‘One file’ has 1 translations, but ‘nplurals’ is 2")
+            )]
+        );
+
+        assert_eq!(
+            messages(
+                "msgid \"\"\nmsgstr \"\"\n\"Language: ru\\n\"\n\n\
+                 msgid \"One file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"Файл\"\nmsgstr[1] \"Файла\"\n"
+            ),
+            vec![(
+                ErrorKind::PluralCountMismatch,
+                6,
+                String::from("the message ‘One file’ has 2 translations, but ‘nplurals’ is 3")
+            )]
+        );
+
+        let mut catalog = Catalog::new();
+
+        catalog.set_header_property("Plural-Forms", "nplurals=x; plural=n;");
+        assert_eq!(catalog.check_plurals().len(), 1);
+
+        catalog.set_header_property("Plural-Forms", "nplurals=99999999999999; plural=0;");
+        assert_eq!(
+            catalog.check_plurals().iter().map(|err| err.message()).collect::<Vec<_>>(),
+            vec!["‘nplurals’ must be at most 100"]
+        );
+    }
+
+    #[test]
+    fn test_func_set_target_language() {
+        let mut catalog = Catalog::new();
+
+        catalog.set_target_language(LanguageRange::new("pt-BR").unwrap());
+        assert_eq!(catalog.target_language().as_ref(), "pt-BR");
+        assert_eq!(catalog.header_properties().get("Language").unwrap(), "pt_BR");
+        assert_eq!(
+            catalog.header_properties().get("Plural-Forms").unwrap(),
+            "nplurals=2; plural=(n > 1);"
+        );
+
+        catalog.set_target_language(LanguageRange::new("ru").unwrap());
+        assert_eq!(catalog.header_properties().get("Language").unwrap(), "ru");
+        assert_eq!(
+            catalog.header_properties().get("Plural-Forms").map(String::as_str),
+            PluralForms::for_language(&LanguageRange::new("ru").unwrap())
+                .as_ref()
+                .map(PluralForms::get_definition)
+        );
+        assert_eq!(catalog.plural_forms().unwrap().map(|f| f.get_count()), Some(3));
+
+        catalog.set_target_language(LanguageRange::new("tlh").unwrap());
+        assert!(catalog.header_properties().get("Plural-Forms").is_none());
+
+        catalog.set_header_property("Plural-Forms", "nplurals=1; plural=0;");
+        catalog.set_target_language(LanguageRange::new("fr").unwrap());
+        assert_eq!(catalog.header_properties().get("Language").unwrap(), "fr");
+        assert_eq!(
+            catalog.header_properties().get("Plural-Forms").unwrap(),
+            "nplurals=1; plural=0;"
+        );
+
+        let mut catalog = Catalog::new();
+
+        catalog.set_target_language(LanguageRange::new("tlh").unwrap());
+        assert_eq!(catalog.header_properties().get("Language").unwrap(), "tlh");
+        assert!(catalog.header_properties().get("Plural-Forms").is_none());
+        assert!(catalog.plural_forms().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_trait_into_iterator() {
+        let catalog = make_catalog();
+
+        assert_eq!((&catalog).into_iter().count(), 5);
+
+        let mut writer = PoWriter::new(vec![]);
+
+        writer.write_catalogue(catalog.into_iter()).unwrap();
+
+        let parser = PoParser::new();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let catalog = Catalog::from_reader(parser.parse(output.as_bytes()).unwrap()).unwrap();
+
+        assert_eq!(catalog.target_language().as_ref(), "fr");
+        assert_eq!(ids(&catalog), ids(&make_catalog()));
+    }
+}
+// no-coverage:stop
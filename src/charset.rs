@@ -0,0 +1,122 @@
+use encoding_rs::{Encoding, UTF_8};
+use std::{
+    borrow::Cow,
+    io::{Error as IoError, ErrorKind},
+};
+
+/// Charset written by `xgettext` in templates, which are treated as UTF-8.
+const TEMPLATE_CHARSET: &str = "CHARSET";
+
+/// Get the charset in the value of a `Content-Type` property, like `text/plain; charset=UTF-8`.
+pub(crate) fn parse_charset(content_type: &str) -> Option<&str> {
+    let start = content_type.to_ascii_lowercase().find("charset=")? + 8;
+    let rest = &content_type[start..];
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_.:".contains(c)))
+        .unwrap_or(rest.len());
+
+    Some(&rest[..end]).filter(|charset| !charset.is_empty())
+}
+
+/// Find the encoding of a charset, only encodings compatible with ASCII are supported.
+pub(crate) fn find_encoding(charset: &str) -> Result<&'static Encoding, String> {
+    if charset == TEMPLATE_CHARSET {
+        return Ok(UTF_8);
+    }
+
+    Encoding::for_label(charset.trim().as_bytes())
+        .filter(|encoding| encoding.is_ascii_compatible())
+        .ok_or_else(|| format!("Unsupported charset ‘{}’", charset))
+}
+
+/// Decode a text, an invalid sequence is an error.
+pub(crate) fn decode<'a>(encoding: &'static Encoding, bytes: &'a [u8]) -> Result<Cow<'a, str>, IoError> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .ok_or_else(|| IoError::new(ErrorKind::InvalidData, format!("Invalid {} sequence", encoding.name())))
+}
+
+/// Encode a text, a character which cannot be represented in the encoding is an error.
+pub(crate) fn encode<'a>(encoding: &'static Encoding, text: &'a str) -> Result<Cow<'a, [u8]>, IoError> {
+    let (bytes, _, unmappable) = encoding.encode(text);
+
+    if unmappable {
+        Err(IoError::new(
+            ErrorKind::InvalidData,
+            format!("Character not representable in {}", encoding.name()),
+        ))
+    } else {
+        Ok(bytes)
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{ISO_8859_2, SHIFT_JIS, WINDOWS_1252};
+
+    #[test]
+    fn test_func_parse_charset() {
+        assert_eq!(parse_charset("text/plain; charset=UTF-8"), Some("UTF-8"));
+        assert_eq!(parse_charset("text/plain; Charset=ISO-8859-2\\n\""), Some("ISO-8859-2"));
+        assert_eq!(parse_charset("text/plain; charset=euc_jp; format=x"), Some("euc_jp"));
+        assert_eq!(parse_charset("text/plain; charset="), None);
+        assert_eq!(parse_charset("text/plain"), None);
+    }
+
+    #[test]
+    fn test_func_find_encoding() {
+        for (charset, expected) in [
+            ("UTF-8", "UTF-8"),
+            ("CHARSET", "UTF-8"),
+            ("ISO-8859-1", "windows-1252"),
+            ("ISO-8859-2", "ISO-8859-2"),
+            ("CP1251", "windows-1251"),
+            ("KOI8-R", "KOI8-R"),
+            ("EUC-JP", "EUC-JP"),
+            ("Shift_JIS", "Shift_JIS"),
+            ("GB2312", "GBK"),
+            ("Big5", "Big5"),
+        ] {
+            assert_eq!(
+                find_encoding(charset).map(Encoding::name),
+                Ok(expected),
+                "Charset {}",
+                charset
+            );
+        }
+
+        assert_eq!(
+            find_encoding("UTF-16"),
+            Err(String::from("Unsupported charset ‘UTF-16’"))
+        );
+        assert_eq!(
+            find_encoding("unknown"),
+            Err(String::from("Unsupported charset ‘unknown’"))
+        );
+    }
+
+    #[test]
+    fn test_func_decode() {
+        assert_eq!(decode(ISO_8859_2, b"\xbf\xf3\xb3w").unwrap(), "żółw");
+        assert_eq!(decode(SHIFT_JIS, b"\x83\x5c").unwrap(), "ソ");
+
+        match decode(UTF_8, b"AB\x80") {
+            Err(err) => assert_eq!(err.to_string(), "Invalid UTF-8 sequence"),
+            r => panic!("Unexpected result for an invalid sequence: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_func_encode() {
+        assert_eq!(encode(ISO_8859_2, "żółw").unwrap().as_ref(), b"\xbf\xf3\xb3w");
+        assert_eq!(encode(UTF_8, "żółw").unwrap().as_ref(), "żółw".as_bytes());
+
+        match encode(WINDOWS_1252, "żółw") {
+            Err(err) => assert_eq!(err.to_string(), "Character not representable in windows-1252"),
+            r => panic!("Unexpected result for an unmappable character: {:?}", r),
+        }
+    }
+}
+// no-coverage:stop
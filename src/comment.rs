@@ -5,7 +5,8 @@ pub struct Comment {
 }
 
 impl Comment {
-    pub(super) fn new(kind: char, content: String) -> Comment {
+    /// Create a comment, the kind being the character after `#` (a space for the translator comments).
+    pub fn new(kind: char, content: String) -> Comment {
         Comment { kind, content }
     }
 
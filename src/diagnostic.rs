@@ -0,0 +1,395 @@
+//! Rendering of the errors for the users, with the text of the source in error.
+
+use crate::{
+    error::{Error, ErrorKind},
+    span::Span,
+};
+use std::fmt::{Display, Formatter, Result, Write};
+
+/// Diagnostic of an error, rendered like the compilers do.
+///
+/// The [`Display`] implementation shows the message, the position, the line of the source in error with a caret under
+/// the bad text, and a hint for fixing it. The [`to_json`](Self::to_json) method gives the same information for tools.
+///
+/// Example:
+/// ```rust
+/// use poreader::{diagnostic::Diagnostic, PoParser};
+///
+/// let source = "msgid \"Hello\"\n#, fuzzy\n";
+/// let parser = PoParser::new();
+///
+/// if let Err(err) = parser.parse(source.as_bytes()) {
+///     let mut diagnostic = Diagnostic::new(&err, source);
+///
+///     diagnostic.set_path("fr.po");
+///     eprintln!("{}", diagnostic);
+/// }
+/// ```
+pub struct Diagnostic<'e> {
+    error: &'e Error,
+    source: &'e str,
+    path: Option<String>,
+}
+
+impl<'e> Diagnostic<'e> {
+    /// Create the diagnostic of an error met while reading the text `source`.
+    pub fn new(error: &'e Error, source: &'e str) -> Diagnostic<'e> {
+        Diagnostic {
+            error,
+            source,
+            path: None,
+        }
+    }
+
+    /// Set the path of the file shown with the position, the path of the error is shown by default.
+    pub fn set_path<P: Into<String>>(&mut self, path: P) {
+        self.path = Some(path.into());
+    }
+
+    /// Get the error.
+    pub fn error(&self) -> &Error {
+        self.error
+    }
+
+    /// Get the short message of the error, without the position.
+    pub fn message(&self) -> String {
+        let msg = self.error.message();
+
+        match (self.error.kind(), self.error.expected()) {
+            (ErrorKind::Parse | ErrorKind::MissingMsgid | ErrorKind::MissingMsgstr, None) => {
+                format!("unexpected ‘{}’", msg.trim())
+            }
+            (_, Some(exp)) if msg.is_empty() => format!("expected ‘{}’", exp),
+            (_, Some(exp)) => format!("expected ‘{}’, got ‘{}’", exp, msg),
+            (ErrorKind::InvalidPluralFormula { .. }, _) => format!("invalid plural forms: {}", msg),
+            _ => msg.to_string(),
+        }
+    }
+
+    /// Get the expected tokens.
+    pub fn expected(&self) -> Vec<&str> {
+        self.error.expected().into_iter().collect()
+    }
+
+    /// Get a hint for fixing the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        Some(match self.error.kind() {
+            ErrorKind::Parse => "strings must be enclosed in double quotes, and comment lines must begin with ‘#’",
+            ErrorKind::MissingMsgid => "an entry must have a ‘msgid’ line after its comments",
+            ErrorKind::MissingMsgstr if self.error.expected() == Some("msgstr") => {
+                "each ‘msgid’ must be followed by a ‘msgstr’ line"
+            }
+            ErrorKind::MissingMsgstr => "a plural message needs its translations in ‘msgstr[0]’, ‘msgstr[1]’ and so on",
+            ErrorKind::BadEscape => "a backslash begins an escape sequence, write ‘\\\\’ for a backslash",
+            ErrorKind::EmptyMsgid => "only the header entry may have an empty ‘msgid’",
+            ErrorKind::InvalidPluralFormula { .. } => {
+                "the header should have a property like ‘Plural-Forms: nplurals=2; plural=n != 1;’"
+            }
+            ErrorKind::PluralCountMismatch => {
+                "a plural message needs one translation for each of the ‘nplurals’ forms of the ‘Plural-Forms’ header"
+            }
+            ErrorKind::Encoding => "the ‘Content-Type’ property of the header should declare the charset of the file",
+            _ => {
+                return None;
+            }
+        })
+    }
+
+    fn path(&self) -> Option<String> {
+        self.path
+            .clone()
+            .or_else(|| self.error.path().map(|p| p.display().to_string()))
+    }
+
+    fn span(&self) -> Option<&Span> {
+        self.error.span()
+    }
+
+    fn line(&self) -> usize {
+        self.error.line()
+    }
+
+    fn column(&self) -> usize {
+        self.span().map_or(0, |span| span.start().column())
+    }
+
+    fn source_line(&self) -> Option<&str> {
+        match self.line() {
+            0 => None,
+            n => self.source.lines().nth(n - 1),
+        }
+    }
+
+    /// Number of carets under the text in error, the text is ended at the end of the line.
+    fn caret_len(&self, text: &str) -> usize {
+        self.span().map_or(0, |span| {
+            let start = span.start().column();
+            let end = if span.end().line() == span.start().line() {
+                span.end().column()
+            } else {
+                text.chars().count() + 1
+            };
+
+            end.saturating_sub(start).max(1)
+        })
+    }
+
+    fn location(&self) -> Option<String> {
+        let path = self.path();
+        let mut res = path.clone().unwrap_or_else(|| String::from("<source>"));
+
+        match (self.line(), self.column()) {
+            (0, _) if path.is_none() => {
+                return None;
+            }
+            (0, _) => (),
+            (line, 0) => write!(res, ":{}", line).unwrap_or_default(),
+            (line, column) => write!(res, ":{}:{}", line, column).unwrap_or_default(),
+        }
+
+        Some(res)
+    }
+
+    /// Render the diagnostic as a JSON object, for editors and other tools.
+    ///
+    /// Fields are `severity`, `message`, `file`, `line`, `column`, `offset`, `end_line`, `end_column`, `end_offset`,
+    /// `expected`, `hint` and `source_line`. Missing values are `null`.
+    pub fn to_json(&self) -> String {
+        let span = self.span();
+        let number = |v: Option<usize>| v.map_or(String::from("null"), |v| v.to_string());
+        let string = |v: Option<&str>| v.map_or(String::from("null"), json_string);
+
+        format!(
+            "{{\"severity\":\"error\",\"message\":{},\"file\":{},\"line\":{},\"column\":{},\"offset\":{},\
+             \"end_line\":{},\"end_column\":{},\"end_offset\":{},\"expected\":[{}],\"hint\":{},\"source_line\":{}}}",
+            json_string(&self.message()),
+            string(self.path().as_deref()),
+            number(Some(self.line()).filter(|l| *l > 0)),
+            number(span.map(|s| s.start().column())),
+            number(span.map(|s| s.start().offset())),
+            number(span.map(|s| s.end().line())),
+            number(span.map(|s| s.end().column())),
+            number(span.map(|s| s.end().offset())),
+            self.expected()
+                .into_iter()
+                .map(json_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            string(self.hint()),
+            string(self.source_line()),
+        )
+    }
+}
+
+impl<'e> Display for Diagnostic<'e> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        writeln!(f, "error: {}", self.message())?;
+
+        if let Some(location) = self.location() {
+            writeln!(f, " --> {}", location)?;
+        }
+
+        if let Some(text) = self.source_line() {
+            let number = self.line().to_string();
+            let margin = " ".repeat(number.len());
+
+            writeln!(f, "{} |", margin)?;
+            writeln!(f, "{} | {}", number, text)?;
+
+            if self.span().is_some() {
+                let indent: String = text
+                    .chars()
+                    .take(self.column().saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+
+                write!(f, "{} | {}{}", margin, indent, "^".repeat(self.caret_len(text)))?;
+
+                for exp in self.expected() {
+                    write!(f, " expected ‘{}’", exp)?;
+                }
+
+                writeln!(f)?;
+            }
+
+            if let Some(hint) = self.hint() {
+                writeln!(f, "{} |", margin)?;
+                writeln!(f, "{} = hint: {}", margin, hint)?;
+            }
+        } else if let Some(hint) = self.hint() {
+            writeln!(f, "  = hint: {}", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut res = String::with_capacity(text.len() + 2);
+
+    res.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(res, "\\u{:04x}", c as u32).unwrap_or_default(),
+            c => res.push(c),
+        }
+    }
+
+    res.push('"');
+    res
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{span::Position, PoParser};
+
+    const SOURCE: &str = "msgid \"\"\nmsgstr \"\"\n\nmsgid \"Hello\"\n#, fuzzy\n";
+
+    fn make_error() -> Error {
+        let start = Position::new(36, 5, 1);
+
+        Error::parse(ErrorKind::MissingMsgstr, Span::new(start, Position::new(44, 5, 9)), "#,", "msgstr")
+    }
+
+    fn make_plural_error() -> Error {
+        Error::new(ErrorKind::InvalidPluralFormula { offset: None }, "bad")
+    }
+
+    #[test]
+    fn test_func_message() {
+        let span = Span::default();
+
+        assert_eq!(
+            Diagnostic::new(&make_error(), SOURCE).message(),
+            "expected ‘msgstr’, got ‘#,’"
+        );
+        assert_eq!(
+            Diagnostic::new(&Error::parse(ErrorKind::Parse, span, "  garbage", ""), "").message(),
+            "unexpected ‘garbage’"
+        );
+        assert_eq!(
+            Diagnostic::new(&Error::parse(ErrorKind::MissingMsgid, span, "", "msgid"), "").message(),
+            "expected ‘msgid’"
+        );
+        assert_eq!(
+            Diagnostic::new(&make_plural_error(), "").message(),
+            "invalid plural forms: bad"
+        );
+    }
+
+    #[test]
+    fn test_func_hint() {
+        let span = Span::default();
+        let escape = Error::new(ErrorKind::BadEscape, "Invalid escape sequence ‘\\q’").with_span(span);
+
+        assert_eq!(
+            Diagnostic::new(&escape, "").hint().map(|h| h.contains("‘\\\\’")),
+            Some(true)
+        );
+        assert_eq!(
+            Diagnostic::new(&make_error(), SOURCE).hint(),
+            Some("each ‘msgid’ must be followed by a ‘msgstr’ line")
+        );
+        assert_eq!(
+            Diagnostic::new(&Error::new(ErrorKind::PluralCountMismatch, ""), "")
+                .hint()
+                .map(|h| h.contains("‘nplurals’")),
+            Some(true)
+        );
+        assert_eq!(
+            Diagnostic::new(&Error::new(ErrorKind::Other, "").with_line(1), "").hint(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_trait_display() {
+        let err = make_error();
+        let mut diagnostic = Diagnostic::new(&err, SOURCE);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: expected ‘msgstr’, got ‘#,’\n \
+             --> <source>:5:1\n  \
+             |\n\
+             5 | #, fuzzy\n  \
+             | ^^^^^^^^ expected ‘msgstr’\n  \
+             |\n  \
+             = hint: each ‘msgid’ must be followed by a ‘msgstr’ line\n"
+        );
+
+        diagnostic.set_path("fr.po");
+        assert!(diagnostic.to_string().contains(" --> fr.po:5:1\n"));
+
+        let err = make_error().with_path("po/de.po");
+
+        assert!(Diagnostic::new(&err, SOURCE)
+            .to_string()
+            .contains(" --> po/de.po:5:1\n"));
+
+        let err = make_plural_error();
+
+        assert_eq!(
+            Diagnostic::new(&err, SOURCE).to_string(),
+            "error: invalid plural forms: bad\n  \
+             = hint: the header should have a property like ‘Plural-Forms: nplurals=2; plural=n != 1;’\n"
+        );
+    }
+
+    #[test]
+    fn test_trait_display_from_reader() {
+        let source = "msgid \"\"\nmsgstr \"\"\n\nmsgid \"A\\qB\"\nmsgstr \"\"\n";
+        let parser = PoParser::new();
+
+        match parser.parse(source.as_bytes()) {
+            Err(err) => assert_eq!(
+                Diagnostic::new(&err, source).to_string(),
+                "error: Invalid escape sequence ‘\\q’\n \
+                 --> <source>:4:9\n  \
+                 |\n\
+                 4 | msgid \"A\\qB\"\n  \
+                 |         ^^\n  \
+                 |\n  \
+                 = hint: a backslash begins an escape sequence, write ‘\\\\’ for a backslash\n"
+            ),
+            Ok(_) => panic!("An error was expected"),
+        }
+    }
+
+    #[test]
+    fn test_func_to_json() {
+        let err = make_error();
+        let mut diagnostic = Diagnostic::new(&err, SOURCE);
+
+        diagnostic.set_path("dir/fr\"1\".po");
+        assert_eq!(
+            diagnostic.to_json(),
+            "{\"severity\":\"error\",\"message\":\"expected ‘msgstr’, got ‘#,’\",\"file\":\"dir/fr\\\"1\\\".po\",\
+             \"line\":5,\"column\":1,\"offset\":36,\"end_line\":5,\"end_column\":9,\"end_offset\":44,\
+             \"expected\":[\"msgstr\"],\"hint\":\"each ‘msgid’ must be followed by a ‘msgstr’ line\",\
+             \"source_line\":\"#, fuzzy\"}"
+        );
+
+        let err = Error::new(ErrorKind::Other, "Tab\there");
+
+        assert_eq!(
+            Diagnostic::new(&err, SOURCE).to_json(),
+            "{\"severity\":\"error\",\"message\":\"Tab\\there\",\"file\":null,\"line\":null,\"column\":null,\
+             \"offset\":null,\"end_line\":null,\"end_column\":null,\"end_offset\":null,\"expected\":[],\
+             \"hint\":null,\"source_line\":null}"
+        );
+    }
+
+    #[test]
+    fn test_func_json_string() {
+        assert_eq!(json_string("a\"b\\c\nd\r\u{1}"), "\"a\\\"b\\\\c\\nd\\r\\u0001\"");
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,403 @@
+//! Text domains, for translating the messages of several components at runtime like `bindtextdomain` does.
+
+use crate::{error::Error, header::parse_language, Catalog, MoReader, PoParser};
+
+pub use crate::header::language_chain;
+
+use locale_config::LanguageRange;
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// Name of the default text domain, like in gettext.
+const DEFAULT_DOMAIN: &str = "messages";
+
+/// Extensions of the catalogue files, in the order of search.
+const EXTENSIONS: [&str; 2] = ["po", "mo"];
+
+/// Find the catalogue files of a domain for a language, in the fallback order.
+///
+/// The files are searched as `<directory>/<lang>/LC_MESSAGES/<domain>.po`, then with the extension `.mo`, for each
+/// name given by [`language_chain`]. Only the first existing file is kept for each name.
+pub fn find_catalogues(directory: &Path, domain: &str, language: &LanguageRange) -> Vec<PathBuf> {
+    language_chain(language)
+        .into_iter()
+        .filter_map(|lang| {
+            let dir = directory.join(lang).join("LC_MESSAGES");
+
+            EXTENSIONS
+                .iter()
+                .map(|ext| dir.join(format!("{}.{}", domain, ext)))
+                .find(|path| path.is_file())
+        })
+        .collect()
+}
+
+fn load_catalogue(path: &Path) -> Result<Catalog, Error> {
+    let file = File::open(path).map_err(|err| Error::io(0, err))?;
+
+    if path.extension() == Some("mo".as_ref()) {
+        Catalog::from_reader(MoReader::new(file)?)
+    } else {
+        Catalog::from_reader(PoParser::new().parse(file)?)
+    }
+}
+
+fn load_domain(
+    name: &str,
+    directory: &Path,
+    language: &LanguageRange,
+    allow_fuzzy: bool,
+) -> Result<Vec<Catalog>, Error> {
+    find_catalogues(directory, name, language)
+        .into_iter()
+        .map(|path| {
+            load_catalogue(&path)
+                .map(|mut catalog| {
+                    catalog.set_allow_fuzzy(allow_fuzzy);
+                    catalog
+                })
+                .map_err(|err| err.with_path(path))
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+struct Domain {
+    directory: PathBuf,
+    catalogs: Vec<Catalog>,
+}
+
+/// Registry of text domains, each domain has its own locale directory.
+///
+/// The catalogues of a domain are loaded for the language of the registry and for its fallbacks, for instance `pt_BR`
+/// then `pt`. A message is searched in each catalogue in turn, and the message itself is returned if no catalogue
+/// translates it.
+///
+/// Example:
+/// ```rust,no_run
+/// use poreader::TextDomains;
+///
+/// fn main() -> Result<(), poreader::error::Error> {
+///     let mut domains = TextDomains::new();
+///
+///     domains.set_language("pt_BR")?;
+///     domains.bind_text_domain("app", "locale")?;
+///     domains.set_text_domain("app");
+///
+///     println!("{}", domains.gettext("Hello"));
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TextDomains {
+    language: LanguageRange<'static>,
+    text_domain: String,
+    allow_fuzzy: bool,
+    domains: HashMap<String, Domain>,
+}
+
+impl TextDomains {
+    /// Create a registry without domain, for the invariant language.
+    pub fn new() -> TextDomains {
+        TextDomains {
+            language: LanguageRange::invariant(),
+            text_domain: String::from(DEFAULT_DOMAIN),
+            allow_fuzzy: false,
+            domains: HashMap::new(),
+        }
+    }
+
+    /// Set the language of the translations, like `pt_BR` or `pt-BR`, the catalogues of all domains are reloaded.
+    ///
+    /// If a catalogue cannot be loaded, the error is returned and the registry keeps its language and catalogues.
+    pub fn set_language(&mut self, language: &str) -> Result<(), Error> {
+        let language = parse_language(language);
+        let catalogs = self
+            .domains
+            .iter()
+            .map(|(name, domain)| {
+                load_domain(name, &domain.directory, &language, self.allow_fuzzy).map(|c| (name.clone(), c))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        for (name, catalogs) in catalogs {
+            if let Some(domain) = self.domains.get_mut(&name) {
+                domain.catalogs = catalogs;
+            }
+        }
+        self.language = language;
+
+        Ok(())
+    }
+
+    /// The language of the translations.
+    pub fn language(&self) -> &LanguageRange<'static> {
+        &self.language
+    }
+
+    /// Use the fuzzy units for the translations.
+    pub fn set_allow_fuzzy(&mut self, allow: bool) {
+        self.allow_fuzzy = allow;
+
+        for catalog in self.domains.values_mut().flat_map(|d| d.catalogs.iter_mut()) {
+            catalog.set_allow_fuzzy(allow);
+        }
+    }
+
+    /// Set the locale directory of a domain and load its catalogues, like `bindtextdomain`.
+    ///
+    /// If a catalogue cannot be loaded, the error is returned and the domain keeps its previous directory and
+    /// catalogues, if it was bound.
+    pub fn bind_text_domain<D: Into<String>, P: Into<PathBuf>>(
+        &mut self,
+        domain: D,
+        directory: P,
+    ) -> Result<(), Error> {
+        let name = domain.into();
+        let directory = directory.into();
+        let catalogs = load_domain(&name, &directory, &self.language, self.allow_fuzzy)?;
+
+        self.domains.insert(name, Domain { directory, catalogs });
+
+        Ok(())
+    }
+
+    /// Set the default domain, like `textdomain`. The default domain is `messages`.
+    pub fn set_text_domain<D: Into<String>>(&mut self, domain: D) {
+        self.text_domain = domain.into();
+    }
+
+    /// The default domain.
+    pub fn text_domain(&self) -> &str {
+        &self.text_domain
+    }
+
+    /// The locale directory of a domain, if the domain is bound.
+    pub fn directory(&self, domain: &str) -> Option<&Path> {
+        self.domains.get(domain).map(|d| d.directory.as_path())
+    }
+
+    /// The catalogues of a domain, in the fallback order.
+    pub fn catalogs(&self, domain: &str) -> &[Catalog] {
+        self.domains.get(domain).map_or(&[], |d| d.catalogs.as_slice())
+    }
+
+    /// Translate a message of the default domain.
+    pub fn gettext<'a>(&'a self, id: &'a str) -> &'a str {
+        self.dgettext(&self.text_domain, id)
+    }
+
+    /// Translate a message of the default domain in a context.
+    pub fn pgettext<'a>(&'a self, context: &str, id: &'a str) -> &'a str {
+        self.dpgettext(&self.text_domain, context, id)
+    }
+
+    /// Translate a message of the default domain with a plural form for the number `count`.
+    pub fn ngettext<'a>(&'a self, id: &'a str, plural_id: &'a str, count: usize) -> &'a str {
+        self.dngettext(&self.text_domain, id, plural_id, count)
+    }
+
+    /// Translate a message of the default domain in a context with a plural form for the number `count`.
+    pub fn npgettext<'a>(&'a self, context: &str, id: &'a str, plural_id: &'a str, count: usize) -> &'a str {
+        self.dnpgettext(&self.text_domain, context, id, plural_id, count)
+    }
+
+    /// Translate a message of a domain.
+    pub fn dgettext<'a>(&'a self, domain: &str, id: &'a str) -> &'a str {
+        self.catalogs(domain)
+            .iter()
+            .find_map(|catalog| catalog.find_text(None, id))
+            .unwrap_or(id)
+    }
+
+    /// Translate a message of a domain in a context.
+    pub fn dpgettext<'a>(&'a self, domain: &str, context: &str, id: &'a str) -> &'a str {
+        self.catalogs(domain)
+            .iter()
+            .find_map(|catalog| catalog.find_text(Some(context), id))
+            .unwrap_or(id)
+    }
+
+    /// Translate a message of a domain with a plural form for the number `count`.
+    pub fn dngettext<'a>(&'a self, domain: &str, id: &'a str, plural_id: &'a str, count: usize) -> &'a str {
+        self.catalogs(domain)
+            .iter()
+            .find_map(|catalog| catalog.find_plural_text(None, id, count))
+            .unwrap_or(if count == 1 { id } else { plural_id })
+    }
+
+    /// Translate a message of a domain in a context with a plural form for the number `count`.
+    pub fn dnpgettext<'a>(
+        &'a self,
+        domain: &str,
+        context: &str,
+        id: &'a str,
+        plural_id: &'a str,
+        count: usize,
+    ) -> &'a str {
+        self.catalogs(domain)
+            .iter()
+            .find_map(|catalog| catalog.find_plural_text(Some(context), id, count))
+            .unwrap_or(if count == 1 { id } else { plural_id })
+    }
+}
+
+impl Default for TextDomains {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn locale_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("locale")
+    }
+
+    fn make_domains(language: &str) -> TextDomains {
+        let mut domains = TextDomains::new();
+
+        domains.set_language(language).unwrap();
+        domains.bind_text_domain("app", locale_dir()).unwrap();
+        domains.bind_text_domain("lib", locale_dir()).unwrap();
+        domains.set_text_domain("app");
+        domains
+    }
+
+    #[test]
+    fn test_func_find_catalogues() {
+        let dir = locale_dir();
+        let names = |domain, lang| -> Vec<PathBuf> {
+            find_catalogues(&dir, domain, &parse_language(lang))
+                .into_iter()
+                .map(|p| p.strip_prefix(&dir).unwrap().to_path_buf())
+                .collect()
+        };
+
+        assert_eq!(
+            names("app", "pt_BR"),
+            vec![
+                Path::new("pt_BR/LC_MESSAGES/app.po"),
+                Path::new("pt/LC_MESSAGES/app.po")
+            ]
+        );
+        assert_eq!(names("lib", "fr-FR"), vec![Path::new("fr/LC_MESSAGES/lib.mo")]);
+        assert!(names("app", "de").is_empty());
+    }
+
+    #[test]
+    fn test_func_new() {
+        let domains = TextDomains::default();
+
+        assert_eq!(domains.text_domain(), "messages");
+        assert_eq!(domains.language(), &LanguageRange::invariant());
+        assert!(domains.directory("app").is_none());
+        assert!(domains.catalogs("app").is_empty());
+        assert_eq!(domains.gettext("Hello"), "Hello");
+    }
+
+    #[test]
+    fn test_func_bind_text_domain() {
+        let domains = make_domains("pt-BR");
+
+        assert_eq!(domains.text_domain(), "app");
+        assert_eq!(domains.directory("app"), Some(locale_dir().as_path()));
+        assert_eq!(domains.catalogs("app").len(), 2);
+        assert!(domains.catalogs("lib").is_empty());
+    }
+
+    #[test]
+    fn test_func_bind_text_domain_with_error() {
+        let dir = std::env::temp_dir().join(format!("poreader-domain-{}", std::process::id()));
+        let lc_dir = dir.join("fr").join("LC_MESSAGES");
+
+        std::fs::create_dir_all(&lc_dir).unwrap();
+        std::fs::write(lc_dir.join("bad.po"), "msgid \"Hello\"\n#, fuzzy\n").unwrap();
+
+        let mut domains = TextDomains::new();
+
+        domains.set_language("fr").unwrap();
+
+        let res = domains.bind_text_domain("bad", &dir);
+
+        match res {
+            Err(err) => {
+                assert_eq!(err.kind(), ErrorKind::MissingMsgstr);
+                assert_eq!(err.path(), Some(lc_dir.join("bad.po").as_path()));
+            }
+            Ok(()) => panic!("An error was expected"),
+        }
+
+        assert!(domains.directory("bad").is_none());
+        std::fs::copy(lc_dir.join("bad.po"), lc_dir.join("lib.po")).unwrap();
+
+        let mut domains = make_domains("fr");
+        let res = domains.bind_text_domain("lib", &dir);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::MissingMsgstr);
+        assert_eq!(domains.directory("lib"), Some(locale_dir().as_path()));
+        assert_eq!(domains.dgettext("lib", "Hello"), "Bonjour");
+    }
+
+    #[test]
+    fn test_func_set_language_with_error() {
+        let dir = std::env::temp_dir().join(format!("poreader-language-{}", std::process::id()));
+        let lc_dir = dir.join("de").join("LC_MESSAGES");
+
+        std::fs::create_dir_all(&lc_dir).unwrap();
+        std::fs::write(lc_dir.join("app.po"), "msgid \"Hello\"\nmsgstr \"Hallo\"\n").unwrap();
+        std::fs::write(lc_dir.join("lib.po"), "msgid \"Hello\"\n#, fuzzy\n").unwrap();
+
+        let mut domains = make_domains("pt_BR");
+
+        domains.bind_text_domain("app", &dir).unwrap();
+        domains.bind_text_domain("lib", &dir).unwrap();
+
+        let res = domains.set_language("de");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::MissingMsgstr);
+        assert_eq!(domains.language(), &parse_language("pt_BR"));
+        assert!(domains.catalogs("app").is_empty());
+        assert!(domains.catalogs("lib").is_empty());
+        assert_eq!(domains.gettext("Hello"), "Hello");
+    }
+
+    #[test]
+    fn test_func_gettext() {
+        let mut domains = make_domains("pt_BR");
+
+        assert_eq!(domains.gettext("Hello"), "Oi");
+        assert_eq!(domains.gettext("Goodbye"), "Adeus");
+        assert_eq!(domains.gettext("Thanks"), "Thanks");
+        assert_eq!(domains.pgettext("menu", "Open"), "Open");
+        assert_eq!(domains.ngettext("One file", "%d files", 0), "Um arquivo");
+        assert_eq!(domains.ngettext("One file", "%d files", 2), "%d arquivos");
+        assert_eq!(domains.npgettext("menu", "One file", "%d files", 2), "%d files");
+        assert_eq!(domains.dgettext("lib", "Hello"), "Hello");
+
+        domains.set_allow_fuzzy(true);
+        assert_eq!(domains.pgettext("menu", "Open"), "Abrir");
+
+        domains.set_language("pt").unwrap();
+        assert_eq!(domains.gettext("Hello"), "Olá");
+        assert_eq!(domains.ngettext("One file", "%d files", 0), "%d ficheiros");
+
+        domains.set_language("fr").unwrap();
+        assert_eq!(domains.gettext("Hello"), "Hello");
+        assert_eq!(domains.dgettext("lib", "Hello"), "Bonjour");
+        assert_eq!(domains.dpgettext("lib", "menu", "Open"), "Ouvrir");
+        assert_eq!(domains.dngettext("lib", "One file", "%d files", 1), "One file");
+        assert_eq!(domains.dnpgettext("lib", "menu", "One file", "%d files", 3), "%d files");
+    }
+}
+// no-coverage:stop
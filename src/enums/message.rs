@@ -28,15 +28,12 @@ impl Message {
     }
 
     pub fn is_plural(&self) -> bool {
-        match self {
-            Message::Plural(_) => true,
-            _ => false,
-        }
+        matches!(self, Message::Plural(_))
     }
 
     pub fn is_blank(&self) -> bool {
         match self {
-            Message::Simple { text, .. } => text.as_ref().map_or(true, |s| s.is_empty()),
+            Message::Simple { text, .. } => text.as_deref().unwrap_or_default().is_empty(),
             Message::Plural(m) => m.is_blank(),
         }
     }
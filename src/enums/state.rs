@@ -4,9 +4,10 @@
 ///
 /// # TODO:
 /// - Rejected, Unreviewed, NeedsReview (from TT), possibly more (note: obsolete is a separate flag)
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub enum State {
     /// The unit is not translated.
+    #[default]
     Empty,
     /// The unit is a suggestion that might be embarrassingly wrong, possibly automatic. It needs
     /// checking by human translator before it can be used. (Used for `#,fuzzy` entries in `.po`.)
@@ -15,12 +16,6 @@ pub enum State {
     Final,
 }
 
-impl Default for State {
-    fn default() -> State {
-        State::Empty
-    }
-}
-
 // no-coverage:start
 #[cfg(test)]
 mod tests {
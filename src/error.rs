@@ -1,106 +1,246 @@
+use crate::span::Span;
 use std::{
+    error::Error as StdError,
     fmt::{Debug, Display, Formatter, Result},
-    io::Error as IoError,
+    io::{Error as IoError, ErrorKind as IoErrorKind},
+    path::{Path, PathBuf},
 };
 
-/// Error in reading (and, in future, writing) a catalogue.
-pub enum Error {
+/// Kind of an error, for handling the errors in programs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
     /// An I/O error from file operation.
-    ///
-    /// The first parameter is line number if applicable, the second is the system error.
-    Io(usize, IoError),
+    Io,
 
-    /// A parse error.
-    ///
-    /// Parameters are line number, the unexpected token (empty string if no token) and the expected tokens.
-    /// Unset unexpected token means the formula is not smart enough to remember what it stopped on.
-    /// Empty array of expected items means the formula is not smart enough to remember what it
-    /// could have accepted instead.
-    Parse(usize, String, String),
+    /// A line or a token which is not expected.
+    Parse,
 
-    /// An expected error.
-    ///
-    /// Something abnormal happened
-    Unexpected(usize, String),
+    /// An entry without `msgid`.
+    MissingMsgid,
 
-    /// A plural error
-    ///
-    /// Error detected while the parse of plural form header
-    PluralForms(String),
+    /// A `msgid` without `msgstr`.
+    MissingMsgstr,
+
+    /// An empty `msgid` in an entry which is not the header.
+    EmptyMsgid,
+
+    /// An invalid escape sequence in a string.
+    BadEscape,
+
+    /// An invalid `Plural-Forms` header, with the byte offset of the error in the value of the header if it is known.
+    InvalidPluralFormula { offset: Option<usize> },
+
+    /// Two entries with the same context and `msgid`.
+    DuplicateEntry,
+
+    /// A plural message with a number of translations different from the number of plural forms.
+    PluralCountMismatch,
+
+    /// An unsupported charset, or a text which cannot be decoded or encoded with the charset.
+    Encoding,
+
+    /// Something abnormal happened.
+    Other,
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter) -> Result {
-        match self {
-            &Error::Io(0, ref err) => Display::fmt(err, f),
-            &Error::Io(line, ref err) => write!(f, "{} at line {}", err, line),
-            &Error::Unexpected(line, ref msg) => {
-                if line > 0 {
-                    write!(f, "Unexpected error at line {}: {}", line, msg)
-                } else {
-                    write!(f, "Unexpected error: {}", msg)
-                }
-            }
-            Error::PluralForms(msg) => write!(f, "Error in plurals forms: {}", msg),
-            Error::Parse(line, got, exp) => {
-                write!(f, "Parse error at line {}", line)?;
+/// Error in reading or writing a catalogue.
+///
+/// The error has a [kind](ErrorKind), a message, the position of the error in the stream if it is known, and
+/// optionally the path of the file in error and the error which caused it.
+pub struct Error {
+    repr: Box<Repr>,
+}
 
-                if !exp.is_empty() {
-                    write!(f, " expected ‘{}’", exp)?;
-                }
+struct Repr {
+    kind: ErrorKind,
+    message: String,
+    expected: Option<String>,
+    line: usize,
+    span: Option<Span>,
+    path: Option<PathBuf>,
+    source: Option<Box<dyn StdError + Send + Sync>>,
+}
 
-                if !got.is_empty() {
-                    write!(f, ", got ‘{}’", got)?;
-                }
+impl Error {
+    /// Create an error with a kind and a message.
+    pub fn new<M: Into<String>>(kind: ErrorKind, message: M) -> Error {
+        Error {
+            repr: Box::new(Repr {
+                kind,
+                message: message.into(),
+                expected: None,
+                line: 0,
+                span: None,
+                path: None,
+                source: None,
+            }),
+        }
+    }
 
-                Ok(())
-            }
+    /// Create an error from an I/O error at the line `line` (0 if not applicable).
+    ///
+    /// Invalid data comes from decoding or encoding the text, the error is then an encoding error.
+    pub(crate) fn io(line: usize, err: IoError) -> Error {
+        let kind = if err.kind() == IoErrorKind::InvalidData {
+            ErrorKind::Encoding
+        } else {
+            ErrorKind::Io
+        };
+
+        Error::new(kind, err.to_string()).with_line(line).with_source(err)
+    }
+
+    /// Create a parse error of a kind, with the unexpected token `got` (empty string if no token) and the expected
+    /// token `expected` (empty string if unknown).
+    pub(crate) fn parse<G: Into<String>>(kind: ErrorKind, span: Span, got: G, expected: &str) -> Error {
+        let mut res = Error::new(kind, got).with_span(span);
+
+        if !expected.is_empty() {
+            res.repr.expected = Some(expected.to_string());
         }
+
+        res
     }
-}
 
-impl Debug for Error {
-    fn fmt(&self, f: &mut Formatter) -> Result {
-        match self {
-            &Error::Io(0, ref err) => Debug::fmt(err, f),
-            &Error::Io(line, ref err) => write!(f, "{:?} at line {}", err, line),
-            &Error::Unexpected(line, ref msg) => {
-                if line > 0 {
-                    write!(f, "Unexpected error at line {}: {}", line, msg)
-                } else {
-                    write!(f, "Unexpected error: {}", msg)
+    /// Set the line number of the error.
+    pub fn with_line(mut self, line: usize) -> Error {
+        self.repr.line = line;
+        self
+    }
+
+    /// Set the span of the text in error, and the line number with it.
+    pub fn with_span(mut self, span: Span) -> Error {
+        self.repr.line = span.start().line();
+        self.repr.span = Some(span);
+        self
+    }
+
+    /// Set the path of the file in error.
+    pub fn with_path<P: Into<PathBuf>>(mut self, path: P) -> Error {
+        self.repr.path = Some(path.into());
+        self
+    }
+
+    /// Set the error which caused this error.
+    pub fn with_source<E: Into<Box<dyn StdError + Send + Sync>>>(mut self, source: E) -> Error {
+        self.repr.source = Some(source.into());
+        self
+    }
+
+    /// Get the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.repr.kind
+    }
+
+    /// Get the message of the error, for parse errors it's the unexpected token (empty if no token).
+    pub fn message(&self) -> &str {
+        &self.repr.message
+    }
+
+    /// Get the expected token of a parse error, if it is known.
+    pub fn expected(&self) -> Option<&str> {
+        self.repr.expected.as_deref()
+    }
+
+    /// Get the line number of the error, 0 if not applicable.
+    pub fn line(&self) -> usize {
+        self.repr.line
+    }
+
+    /// Get the span of the text in error, if the error comes from a parsed text.
+    pub fn span(&self) -> Option<&Span> {
+        self.repr.span.as_ref()
+    }
+
+    /// Get the path of the file in error, if it is known.
+    pub fn path(&self) -> Option<&Path> {
+        self.repr.path.as_deref()
+    }
+
+    fn title(&self) -> &'static str {
+        match self.repr.kind {
+            ErrorKind::Parse | ErrorKind::MissingMsgid | ErrorKind::MissingMsgstr => "Parse error",
+            ErrorKind::EmptyMsgid | ErrorKind::BadEscape => "Syntax error",
+            ErrorKind::InvalidPluralFormula { .. } => "Error in plurals forms",
+            ErrorKind::DuplicateEntry => "Duplicate entry",
+            ErrorKind::PluralCountMismatch => "Plural count mismatch",
+            ErrorKind::Encoding => "Encoding error",
+            ErrorKind::Io | ErrorKind::Other => "Unexpected error",
+        }
+    }
+
+    fn fmt_location(&self, f: &mut Formatter) -> Result {
+        match self.repr.span {
+            Some(span) => write!(f, " at line {}, column {}", span.start().line(), span.start().column()),
+            None if self.repr.line > 0 => write!(f, " at line {}", self.repr.line),
+            None => Ok(()),
+        }
+    }
+
+    fn fmt_error(&self, f: &mut Formatter, debug: bool) -> Result {
+        if let Some(path) = &self.repr.path {
+            write!(f, "{}: ", path.display())?;
+        }
+
+        match self.repr.kind {
+            ErrorKind::Io => {
+                match &self.repr.source {
+                    Some(err) if debug => Debug::fmt(err, f)?,
+                    _ => f.write_str(&self.repr.message)?,
                 }
+
+                self.fmt_location(f)
             }
-            Error::PluralForms(msg) => write!(f, "Error in plurals forms: {}", msg),
-            &Error::Parse(line, ref got, ref exp) => {
-                write!(f, "Parse error at line {}", line)?;
+            ErrorKind::Parse | ErrorKind::MissingMsgid | ErrorKind::MissingMsgstr => {
+                f.write_str(self.title())?;
+                self.fmt_location(f)?;
 
-                if !exp.is_empty() {
+                if let Some(exp) = &self.repr.expected {
                     write!(f, " expected ‘{}’", exp)?;
                 }
 
-                if !got.is_empty() {
-                    write!(f, ", got ‘{}’", got)?;
+                if !self.repr.message.is_empty() {
+                    write!(f, ", got ‘{}’", self.repr.message)?;
                 }
 
                 Ok(())
             }
+            ErrorKind::InvalidPluralFormula { .. } => write!(f, "{}: {}", self.title(), self.repr.message),
+            _ => {
+                f.write_str(self.title())?;
+                self.fmt_location(f)?;
+
+                write!(f, ": {}", self.repr.message)
+            }
         }
     }
 }
 
-impl std::error::Error for Error {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            &Error::Io(_, ref err) => Some(err),
-            _ => None,
-        }
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        self.fmt_error(f, false)
     }
 }
 
-impl From<Error> for std::io::Error {
+impl Debug for Error {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        self.fmt_error(f, true)
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.repr
+            .source
+            .as_ref()
+            .map(|err| err.as_ref() as &(dyn StdError + 'static))
+    }
+}
+
+impl From<Error> for IoError {
     fn from(error: Error) -> Self {
-        std::io::Error::new(std::io::ErrorKind::Other, error)
+        IoError::other(error)
     }
 }
 
@@ -108,26 +248,24 @@ impl From<Error> for std::io::Error {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{error::Error as StdErr, io::ErrorKind};
+    use crate::span::Position;
 
     fn make_error() -> Error {
-        Error::PluralForms(String::from("message"))
+        Error::new(ErrorKind::InvalidPluralFormula { offset: None }, "message")
+    }
+
+    fn make_span() -> Span {
+        Span::new(Position::new(25, 10, 3), Position::new(31, 10, 9))
     }
 
     impl PartialEq for Error {
         fn eq(&self, other: &Self) -> bool {
-            match (self, other) {
-                (Error::PluralForms(l), Error::PluralForms(r)) => r == l,
-                (Error::Unexpected(ll, lm), Error::Unexpected(rl, rm)) => (ll == rl) && (lm == rm),
-                (Error::Parse(ll, lu, le), Error::Parse(rl, ru, re)) => (ll == rl) && (lu == ru) && (le == re),
-                (Error::Io(ll, le), Error::Io(rl, re)) => {
-                    (ll == rl)
-                        && (le.kind() == re.kind())
-                        && (le.raw_os_error() == re.raw_os_error())
-                        && (le.get_ref().map(|v| v.to_string()) == re.get_ref().map(|v| v.to_string()))
-                }
-                _ => false,
-            }
+            (self.repr.kind == other.repr.kind)
+                && (self.repr.message == other.repr.message)
+                && (self.repr.expected == other.repr.expected)
+                && (self.repr.line == other.repr.line)
+                && (self.repr.span == other.repr.span)
+                && (self.repr.path == other.repr.path)
         }
     }
 
@@ -143,94 +281,154 @@ mod tests {
     #[test]
     fn test_eq() {
         assert_eq!(
-            Error::Io(123, IoError::from(make_error())),
-            Error::Io(123, IoError::from(Error::PluralForms(String::from("message")))),
+            Error::io(123, IoError::from(make_error())),
+            Error::io(123, IoError::from(make_error())),
+        );
+        assert_ne!(make_error(), make_error().with_line(1));
+    }
+
+    #[test]
+    fn test_func_kind() {
+        let span = make_span();
+
+        assert_eq!(make_error().kind(), ErrorKind::InvalidPluralFormula { offset: None });
+        assert_eq!(Error::io(1, IoError::other("weird")).kind(), ErrorKind::Io);
+        assert_eq!(
+            Error::io(1, IoError::new(IoErrorKind::InvalidData, "bad")).kind(),
+            ErrorKind::Encoding
         );
+        assert_eq!(Error::parse(ErrorKind::MissingMsgid, span, "#,", "msgid").kind(), ErrorKind::MissingMsgid);
+        assert_eq!(Error::parse(ErrorKind::MissingMsgstr, span, "#,", "msgstr").kind(), ErrorKind::MissingMsgstr);
+        assert_eq!(Error::parse(ErrorKind::MissingMsgstr, span, "", "msgstr[0]").kind(), ErrorKind::MissingMsgstr);
+        assert_eq!(Error::parse(ErrorKind::Parse, span, "garbage", "").kind(), ErrorKind::Parse);
+    }
+
+    #[test]
+    fn test_func_accessors() {
+        let err = Error::parse(ErrorKind::MissingMsgstr, make_span(), "#,", "msgstr").with_path("po/fr.po");
+
+        assert_eq!(err.message(), "#,");
+        assert_eq!(err.expected(), Some("msgstr"));
+        assert_eq!(err.line(), 10);
+        assert_eq!(err.span(), Some(&make_span()));
+        assert_eq!(err.path(), Some(Path::new("po/fr.po")));
+
+        let err = Error::new(ErrorKind::Other, "weird").with_line(15);
+
+        assert_eq!(err.expected(), None);
+        assert_eq!(err.line(), 15);
+        assert_eq!(err.span(), None);
+        assert_eq!(err.path(), None);
     }
 
     #[test]
     fn test_func_source() {
-        let err = Error::Io(10, std::io::Error::new(ErrorKind::Other, make_error()));
-        let other = Error::Unexpected(15, String::from("weird"));
+        let err = Error::io(10, IoError::other(make_error()));
+        let other = Error::new(ErrorKind::Other, "weird").with_line(15);
 
         assert!(other.source().is_none(), "Other error should have no source");
         assert_eq!(
             format!("{}", err.source().unwrap_or(&other)),
             format!("{}", make_error())
         );
+
+        let chained = Error::new(ErrorKind::Other, "outer").with_source(err);
+
+        assert_eq!(
+            chained.source().and_then(StdError::source).map(|e| e.to_string()),
+            Some(make_error().to_string())
+        );
     }
 
     #[test]
     fn test_trait_from() {
-        let err = std::io::Error::from(make_error());
+        let err = IoError::from(make_error());
 
-        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(err.kind(), IoErrorKind::Other);
         assert_eq!(format!("{}", err), format!("{}", make_error()));
     }
 
     #[test]
     fn test_trait_display() {
         assert_eq!(
-            format!("{}", Error::Io(0, std::io::Error::from(make_error()))),
+            format!("{}", Error::io(0, IoError::from(make_error()))),
             String::from("Error in plurals forms: message"),
         );
 
         assert_eq!(
-            format!("{}", Error::Io(10, std::io::Error::from(make_error()))),
+            format!("{}", Error::io(10, IoError::from(make_error()))),
             String::from("Error in plurals forms: message at line 10"),
         );
 
         assert_eq!(
-            format!("{}", Error::Unexpected(0, String::from("message"))),
+            format!("{}", Error::new(ErrorKind::Other, "message")),
             String::from("Unexpected error: message"),
         );
 
         assert_eq!(
-            format!("{}", Error::Unexpected(10, String::from("message"))),
+            format!("{}", Error::new(ErrorKind::Other, "message").with_line(10)),
             String::from("Unexpected error at line 10: message"),
         );
 
         assert_eq!(
-            format!("{}", Error::Parse(10, String::from("token1"), String::from("token2"))),
-            String::from("Parse error at line 10 expected ‘token2’, got ‘token1’"),
+            format!("{}", Error::parse(ErrorKind::Parse, make_span(), "token1", "token2")),
+            String::from("Parse error at line 10, column 3 expected ‘token2’, got ‘token1’"),
         );
 
         assert_eq!(
-            format!("{}", Error::PluralForms(String::from("message"))),
-            format!("Error in plurals forms: message"),
+            format!("{}", Error::new(ErrorKind::BadEscape, "message").with_span(make_span())),
+            String::from("Syntax error at line 10, column 3: message"),
+        );
+
+        assert_eq!(
+            format!("{}", Error::new(ErrorKind::Encoding, "message").with_line(2)),
+            String::from("Encoding error at line 2: message"),
+        );
+
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(ErrorKind::DuplicateEntry, "message").with_path("fr.po")
+            ),
+            String::from("fr.po: Duplicate entry: message"),
+        );
+
+        assert_eq!(
+            format!("{}", make_error()),
+            String::from("Error in plurals forms: message"),
         );
     }
 
     #[test]
     fn test_trait_debug() {
         assert_eq!(
-            format!("{:?}", Error::Io(0, std::io::Error::from(make_error()))),
+            format!("{:?}", Error::io(0, IoError::from(make_error()))),
             String::from("Custom { kind: Other, error: Error in plurals forms: message }"),
         );
 
         assert_eq!(
-            format!("{:?}", Error::Io(10, std::io::Error::from(make_error()))),
+            format!("{:?}", Error::io(10, IoError::from(make_error()))),
             String::from("Custom { kind: Other, error: Error in plurals forms: message } at line 10"),
         );
 
         assert_eq!(
-            format!("{:?}", Error::Unexpected(0, String::from("message"))),
+            format!("{:?}", Error::new(ErrorKind::Other, "message")),
             String::from("Unexpected error: message"),
         );
 
         assert_eq!(
-            format!("{:?}", Error::Unexpected(10, String::from("message"))),
+            format!("{:?}", Error::new(ErrorKind::Other, "message").with_line(10)),
             String::from("Unexpected error at line 10: message"),
         );
 
         assert_eq!(
-            format!("{:?}", Error::Parse(10, String::from("token1"), String::from("token2"))),
-            String::from("Parse error at line 10 expected ‘token2’, got ‘token1’"),
+            format!("{:?}", Error::parse(ErrorKind::Parse, make_span(), "token1", "token2")),
+            String::from("Parse error at line 10, column 3 expected ‘token2’, got ‘token1’"),
         );
 
         assert_eq!(
-            format!("{:?}", Error::PluralForms(String::from("message"))),
-            format!("Error in plurals forms: message"),
+            format!("{:?}", make_error()),
+            String::from("Error in plurals forms: message"),
         );
     }
 }
@@ -0,0 +1,270 @@
+use std::{
+    fmt::{Display, Formatter, Result},
+    ops::RangeInclusive,
+};
+
+/// Language of the format strings, as named in the flags `<lang>-format` of gettext.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum FormatLanguage {
+    C,
+    ObjC,
+    Cplusplus,
+    Python,
+    PythonBrace,
+    Java,
+    JavaPrintf,
+    CSharp,
+    JavaScript,
+    Scheme,
+    Lisp,
+    ELisp,
+    Librep,
+    Rust,
+    Go,
+    Ruby,
+    Sh,
+    Awk,
+    Lua,
+    ObjectPascal,
+    Modula2,
+    D,
+    Smalltalk,
+    Qt,
+    QtPlural,
+    Kde,
+    KdeKuit,
+    Boost,
+    Tcl,
+    Perl,
+    PerlBrace,
+    Php,
+    GccInternal,
+    GfcInternal,
+    Ycp,
+}
+
+/// Names of the languages in the flags.
+const LANGUAGE_NAMES: [(FormatLanguage, &str); 35] = [
+    (FormatLanguage::C, "c"),
+    (FormatLanguage::ObjC, "objc"),
+    (FormatLanguage::Cplusplus, "c++"),
+    (FormatLanguage::Python, "python"),
+    (FormatLanguage::PythonBrace, "python-brace"),
+    (FormatLanguage::Java, "java"),
+    (FormatLanguage::JavaPrintf, "java-printf"),
+    (FormatLanguage::CSharp, "csharp"),
+    (FormatLanguage::JavaScript, "javascript"),
+    (FormatLanguage::Scheme, "scheme"),
+    (FormatLanguage::Lisp, "lisp"),
+    (FormatLanguage::ELisp, "elisp"),
+    (FormatLanguage::Librep, "librep"),
+    (FormatLanguage::Rust, "rust"),
+    (FormatLanguage::Go, "go"),
+    (FormatLanguage::Ruby, "ruby"),
+    (FormatLanguage::Sh, "sh"),
+    (FormatLanguage::Awk, "awk"),
+    (FormatLanguage::Lua, "lua"),
+    (FormatLanguage::ObjectPascal, "object-pascal"),
+    (FormatLanguage::Modula2, "modula2"),
+    (FormatLanguage::D, "d"),
+    (FormatLanguage::Smalltalk, "smalltalk"),
+    (FormatLanguage::Qt, "qt"),
+    (FormatLanguage::QtPlural, "qt-plural"),
+    (FormatLanguage::Kde, "kde"),
+    (FormatLanguage::KdeKuit, "kde-kuit"),
+    (FormatLanguage::Boost, "boost"),
+    (FormatLanguage::Tcl, "tcl"),
+    (FormatLanguage::Perl, "perl"),
+    (FormatLanguage::PerlBrace, "perl-brace"),
+    (FormatLanguage::Php, "php"),
+    (FormatLanguage::GccInternal, "gcc-internal"),
+    (FormatLanguage::GfcInternal, "gfc-internal"),
+    (FormatLanguage::Ycp, "ycp"),
+];
+
+impl FormatLanguage {
+    /// Get the language from its name in the flags, like `c` or `python-brace`.
+    pub fn from_name(name: &str) -> Option<FormatLanguage> {
+        LANGUAGE_NAMES.iter().find(|(_, n)| *n == name).map(|(lang, _)| *lang)
+    }
+
+    /// Get the name of the language in the flags.
+    pub fn name(&self) -> &'static str {
+        LANGUAGE_NAMES
+            .iter()
+            .find(|(lang, _)| lang == self)
+            .map(|(_, name)| *name)
+            .unwrap_or_default()
+    }
+}
+
+impl Display for FormatLanguage {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Flag of a unit, from a `#,` comment
+///
+/// The flags which are not known are kept as is.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Flag {
+    /// The translation needs to be reviewed.
+    Fuzzy,
+
+    /// The message is a format string of the language, flag `<lang>-format`.
+    Format(FormatLanguage),
+
+    /// The message is not a format string of the language, flag `no-<lang>-format`.
+    NoFormat(FormatLanguage),
+
+    /// The message may be a format string of the language, flag `possible-<lang>-format`.
+    PossibleFormat(FormatLanguage),
+
+    /// Range of the numbers given to a plural message, flag `range: min..max`.
+    Range(RangeInclusive<u64>),
+
+    /// The strings of the entry are wrapped, flag `wrap`.
+    Wrap,
+
+    /// The strings of the entry are not wrapped, flag `no-wrap`.
+    NoWrap,
+
+    /// Any other flag.
+    Other(String),
+}
+
+impl Flag {
+    /// Parse one flag of a `#,` comment.
+    pub fn parse(text: &str) -> Flag {
+        let text = text.trim();
+
+        match text {
+            "fuzzy" => Flag::Fuzzy,
+            "wrap" => Flag::Wrap,
+            "no-wrap" => Flag::NoWrap,
+            _ => parse_range(text)
+                .or_else(|| parse_format(text))
+                .unwrap_or_else(|| Flag::Other(text.to_string())),
+        }
+    }
+
+    /// Get the language of a format flag.
+    pub fn format_language(&self) -> Option<FormatLanguage> {
+        match self {
+            Flag::Format(lang) | Flag::NoFormat(lang) | Flag::PossibleFormat(lang) => Some(*lang),
+            _ => None,
+        }
+    }
+}
+
+fn parse_range(text: &str) -> Option<Flag> {
+    let (min, max) = text.strip_prefix("range:")?.split_once("..")?;
+    let (min, max) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+
+    if min <= max {
+        Some(Flag::Range(min..=max))
+    } else {
+        None
+    }
+}
+
+fn parse_format(text: &str) -> Option<Flag> {
+    let name = text.strip_suffix("-format")?;
+
+    if let Some(lang) = name.strip_prefix("no-").and_then(FormatLanguage::from_name) {
+        Some(Flag::NoFormat(lang))
+    } else if let Some(lang) = name.strip_prefix("possible-").and_then(FormatLanguage::from_name) {
+        Some(Flag::PossibleFormat(lang))
+    } else {
+        FormatLanguage::from_name(name).map(Flag::Format)
+    }
+}
+
+/// Format the flag like in a `#,` comment.
+impl Display for Flag {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Flag::Fuzzy => f.write_str("fuzzy"),
+            Flag::Format(lang) => write!(f, "{}-format", lang),
+            Flag::NoFormat(lang) => write!(f, "no-{}-format", lang),
+            Flag::PossibleFormat(lang) => write!(f, "possible-{}-format", lang),
+            Flag::Range(range) => write!(f, "range: {}..{}", range.start(), range.end()),
+            Flag::Wrap => f.write_str("wrap"),
+            Flag::NoWrap => f.write_str("no-wrap"),
+            Flag::Other(flag) => f.write_str(flag),
+        }
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_func_language_names() {
+        for (lang, name) in LANGUAGE_NAMES {
+            assert_eq!(FormatLanguage::from_name(name), Some(lang));
+            assert_eq!(lang.name(), name);
+            assert_eq!(lang.to_string(), name);
+        }
+
+        assert_eq!(FormatLanguage::from_name("cobol"), None);
+    }
+
+    #[test]
+    fn test_func_parse() {
+        assert_eq!(Flag::parse("fuzzy"), Flag::Fuzzy);
+        assert_eq!(Flag::parse(" wrap "), Flag::Wrap);
+        assert_eq!(Flag::parse("no-wrap"), Flag::NoWrap);
+        assert_eq!(Flag::parse("c-format"), Flag::Format(FormatLanguage::C));
+        assert_eq!(Flag::parse("c++-format"), Flag::Format(FormatLanguage::Cplusplus));
+        assert_eq!(
+            Flag::parse("python-brace-format"),
+            Flag::Format(FormatLanguage::PythonBrace)
+        );
+        assert_eq!(Flag::parse("no-python-format"), Flag::NoFormat(FormatLanguage::Python));
+        assert_eq!(
+            Flag::parse("possible-kde-kuit-format"),
+            Flag::PossibleFormat(FormatLanguage::KdeKuit)
+        );
+        assert_eq!(Flag::parse("range: 0..10"), Flag::Range(0..=10));
+        assert_eq!(Flag::parse("range:1 .. 5"), Flag::Range(1..=5));
+        assert_eq!(Flag::parse("range: 5..1"), Flag::Other(String::from("range: 5..1")));
+        assert_eq!(Flag::parse("cobol-format"), Flag::Other(String::from("cobol-format")));
+        assert_eq!(Flag::parse("no-format"), Flag::Other(String::from("no-format")));
+        assert_eq!(Flag::parse("my-flag"), Flag::Other(String::from("my-flag")));
+    }
+
+    #[test]
+    fn test_func_format_language() {
+        assert_eq!(
+            Flag::parse("possible-c-format").format_language(),
+            Some(FormatLanguage::C)
+        );
+        assert_eq!(
+            Flag::parse("no-rust-format").format_language(),
+            Some(FormatLanguage::Rust)
+        );
+        assert_eq!(Flag::Fuzzy.format_language(), None);
+    }
+
+    #[test]
+    fn test_trait_display() {
+        for text in [
+            "fuzzy",
+            "wrap",
+            "no-wrap",
+            "c-format",
+            "no-qt-plural-format",
+            "possible-object-pascal-format",
+            "range: 0..10",
+            "any",
+        ] {
+            assert_eq!(Flag::parse(text).to_string(), text);
+        }
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,302 @@
+//! Format strings of `printf` in C.
+
+use super::Arguments;
+
+const FLAGS: &str = "-+ #0'I";
+
+/// Highest argument number, like `NL_ARGMAX` of glibc.
+const MAX_ARGUMENT: usize = 4096;
+
+/// Length modifiers, the longest first.
+const LENGTHS: [&str; 10] = ["hh", "ll", "h", "l", "q", "L", "j", "z", "Z", "t"];
+
+/// Parse a `printf` format string, with the numbered arguments like `%2$s` and the widths and precisions `*`.
+pub(super) fn parse(text: &str) -> Result<Arguments, String> {
+    parse_with(text, false)
+}
+
+/// Parse an Objective-C format string, which is a `printf` format string with the object conversion `%@`.
+pub(super) fn parse_objc(text: &str) -> Result<Arguments, String> {
+    parse_with(text, true)
+}
+
+fn parse_with(text: &str, objc: bool) -> Result<Arguments, String> {
+    let mut parser = FormatParser {
+        text,
+        pos: 0,
+        types: vec![],
+        numbered: None,
+        next: 0,
+        objc,
+    };
+
+    while let Some(start) = parser.text[parser.pos..].find('%') {
+        parser.pos += start + 1;
+        parser.parse_spec()?;
+    }
+
+    let numbered = parser
+        .types
+        .into_iter()
+        .enumerate()
+        .map(|(i, t)| t.ok_or_else(|| format!("missing argument {}", i + 1)))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Arguments {
+        numbered,
+        ..Arguments::default()
+    })
+}
+
+struct FormatParser<'t> {
+    text: &'t str,
+    pos: usize,
+    types: Vec<Option<String>>,
+
+    /// Whether the arguments are numbered, known from the first specification.
+    numbered: Option<bool>,
+    next: usize,
+
+    /// Whether the conversion `%@` of Objective-C is accepted.
+    objc: bool,
+}
+
+impl FormatParser<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        let found = self.rest().starts_with(prefix);
+
+        if found {
+            self.pos += prefix.len();
+        }
+
+        found
+    }
+
+    fn digits(&mut self) -> Option<usize> {
+        let len = self.rest().bytes().take_while(u8::is_ascii_digit).count();
+        let res = self.rest()[..len].parse().ok();
+
+        self.pos += len;
+        res
+    }
+
+    /// Read an argument number `n$`, if there is one.
+    fn number(&mut self) -> Result<Option<usize>, String> {
+        let start = self.pos;
+
+        match self.digits() {
+            Some(n) if self.eat("$") => {
+                if n == 0 {
+                    Err(String::from("argument numbers start at 1"))
+                } else if n > MAX_ARGUMENT {
+                    Err(format!("argument number {} is greater than {}", n, MAX_ARGUMENT))
+                } else {
+                    Ok(Some(n))
+                }
+            }
+            _ => {
+                self.pos = start;
+                Ok(None)
+            }
+        }
+    }
+
+    fn add(&mut self, number: Option<usize>, arg_type: &str) -> Result<(), String> {
+        if self.numbered.get_or_insert(number.is_some()) != &number.is_some() {
+            return Err(String::from("numbered and unnumbered specifications are mixed"));
+        }
+
+        let number = number.unwrap_or_else(|| {
+            self.next += 1;
+            self.next
+        });
+
+        if number > MAX_ARGUMENT {
+            return Err(format!("more than {} arguments", MAX_ARGUMENT));
+        }
+
+        if self.types.len() < number {
+            self.types.resize(number, None);
+        }
+
+        match &self.types[number - 1] {
+            Some(t) if t != arg_type => Err(format!("argument {} has the types ‘{}’ and ‘{}’", number, t, arg_type)),
+            _ => {
+                self.types[number - 1] = Some(arg_type.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Read a width or a precision `*` or `*n$`, which takes an argument.
+    fn star(&mut self) -> Result<(), String> {
+        if self.eat("*") {
+            let number = self.number()?;
+
+            self.add(number, "int")
+        } else {
+            self.digits();
+            Ok(())
+        }
+    }
+
+    /// Parse a specification after the `%`.
+    fn parse_spec(&mut self) -> Result<(), String> {
+        if self.eat("%") {
+            return Ok(());
+        }
+
+        let number = self.number()?;
+
+        while self.rest().starts_with(|c| FLAGS.contains(c)) {
+            self.pos += 1;
+        }
+
+        self.star()?;
+        if self.eat(".") {
+            self.star()?;
+        }
+
+        let length = LENGTHS
+            .into_iter()
+            .find(|l| self.rest().starts_with(l))
+            .unwrap_or_default();
+
+        self.pos += length.len();
+
+        let conversion = match self.rest().chars().next() {
+            Some(c) => c,
+            None => return Err(String::from("the string ends in the middle of a specification")),
+        };
+
+        self.pos += conversion.len_utf8();
+
+        let arg_type = match conversion {
+            'd' | 'i' => int_type("", length),
+            'o' | 'u' | 'x' | 'X' => int_type("unsigned ", length),
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' | 'a' | 'A' if length == "L" => String::from("long double"),
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' | 'a' | 'A' => String::from("double"),
+            'c' if length == "l" => String::from("wint_t"),
+            'C' => String::from("wint_t"),
+            'c' => String::from("char"),
+            's' if length == "l" => String::from("wide string"),
+            'S' => String::from("wide string"),
+            's' => String::from("string"),
+            'p' => String::from("pointer"),
+            '@' if self.objc => String::from("object"),
+            'n' => format!("{} pointer", int_type("", length)),
+            'm' => return Ok(()),
+            '<' => self.macro_type()?,
+            c => return Err(format!("invalid conversion ‘{}’", c)),
+        };
+
+        self.add(number, &arg_type)
+    }
+
+    /// Get the type of a conversion macro of `<inttypes.h>`, like `%<PRId64>`.
+    fn macro_type(&mut self) -> Result<String, String> {
+        let end = self.rest().find('>').ok_or("unterminated conversion macro")?;
+        let name = &self.rest()[..end];
+        let mut chars = name.strip_prefix("PRI").unwrap_or_default().chars();
+        let res = match (chars.next(), chars.as_str()) {
+            (Some('d' | 'i'), size) if !size.is_empty() => format!("int{}_t", size),
+            (Some('o' | 'u' | 'x' | 'X'), size) if !size.is_empty() => format!("uint{}_t", size),
+            _ => return Err(format!("invalid conversion macro ‘{}’", name)),
+        };
+
+        self.pos += end + 1;
+        Ok(res)
+    }
+}
+
+/// Get the name of an integer type with a length modifier.
+fn int_type(sign: &str, length: &str) -> String {
+    match length {
+        "hh" => format!("{}char", sign),
+        "h" => format!("{}short", sign),
+        "l" => format!("{}long", sign),
+        "ll" | "q" | "L" => format!("{}long long", sign),
+        "j" => format!("{}intmax_t", sign),
+        "z" | "Z" => String::from("size_t"),
+        "t" => String::from("ptrdiff_t"),
+        _ => format!("{}int", sign),
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(text: &str) -> Vec<String> {
+        parse(text).unwrap().numbered
+    }
+
+    #[test]
+    fn test_func_parse() {
+        assert!(types("No format, 100%% sure").is_empty());
+        assert_eq!(types("%s has %d files"), vec!["string", "int"]);
+        assert_eq!(
+            types("%-10.3lu %hhd %zd %Lf %e %c %lc %ls %p %n %m"),
+            vec![
+                "unsigned long",
+                "char",
+                "size_t",
+                "long double",
+                "double",
+                "char",
+                "wint_t",
+                "wide string",
+                "pointer",
+                "int pointer",
+            ]
+        );
+        assert_eq!(types("%*.*s"), vec!["int", "int", "string"]);
+        assert_eq!(types("%<PRId64> %<PRIx32>"), vec!["int64_t", "uint32_t"]);
+    }
+
+    #[test]
+    fn test_func_parse_numbered() {
+        assert_eq!(types("%2$d files in %1$s"), vec!["string", "int"]);
+        assert_eq!(types("%1$s %1$s"), vec!["string"]);
+        assert_eq!(types("%2$*1$d"), vec!["int", "int"]);
+    }
+
+    #[test]
+    fn test_func_parse_objc() {
+        assert_eq!(parse_objc("%@ has %d files").unwrap().numbered, vec!["object", "int"]);
+        assert_eq!(parse_objc("%2$@ in %1$@").unwrap().numbered, vec!["object", "object"]);
+        assert_eq!(parse("%@").unwrap_err(), "invalid conversion ‘@’");
+    }
+
+    #[test]
+    fn test_func_parse_invalid() {
+        assert_eq!(parse("%y").unwrap_err(), "invalid conversion ‘y’");
+        assert_eq!(
+            parse("100%").unwrap_err(),
+            "the string ends in the middle of a specification"
+        );
+        assert_eq!(
+            parse("%1$s %d").unwrap_err(),
+            "numbered and unnumbered specifications are mixed"
+        );
+        assert_eq!(parse("%2$s").unwrap_err(), "missing argument 1");
+        assert_eq!(parse("%0$s").unwrap_err(), "argument numbers start at 1");
+        assert_eq!(
+            parse("%999999999$s").unwrap_err(),
+            "argument number 999999999 is greater than 4096"
+        );
+        assert_eq!(parse(&"%d".repeat(4097)).unwrap_err(), "more than 4096 arguments");
+        assert_eq!(
+            parse("%1$s %1$d").unwrap_err(),
+            "argument 1 has the types ‘string’ and ‘int’"
+        );
+        assert_eq!(parse("%<PRId64").unwrap_err(), "unterminated conversion macro");
+        assert_eq!(parse("%<FOO>").unwrap_err(), "invalid conversion macro ‘FOO’");
+    }
+}
+// no-coverage:stop
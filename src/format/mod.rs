@@ -0,0 +1,485 @@
+//! Checks of the format strings in the translations, like `msgfmt --check-format` does.
+//!
+//! The units flagged with the format of a language, like `c-format`, must use the same arguments with the same types in
+//! the message and in its translations, else the translated program can fail at runtime.
+
+mod c;
+mod python;
+mod python_brace;
+mod rust;
+
+use crate::{flag::FormatLanguage, location::Location, plural::Plural, span::Span, unit::Unit, Catalog, Message};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Result},
+};
+
+/// Count of numbers below 1000 from which a plural form is general.
+const GENERAL_FORM_COUNT: usize = 5;
+
+/// Arguments used by a format string, with their types.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Arguments {
+    /// Types of the numbered arguments, from the argument 1.
+    numbered: Vec<String>,
+
+    /// Types of the named arguments.
+    named: BTreeMap<String, String>,
+}
+
+/// Parser of the format strings of a language, giving the arguments of a format string or why it is not valid.
+type Parser = fn(&str) -> std::result::Result<Arguments, String>;
+
+/// Get the parser of the format strings of a language, if the language is checked.
+fn parser(language: FormatLanguage) -> Option<Parser> {
+    match language {
+        FormatLanguage::C => Some(c::parse),
+        FormatLanguage::ObjC => Some(c::parse_objc),
+        FormatLanguage::Python => Some(python::parse),
+        FormatLanguage::PythonBrace => Some(python_brace::parse),
+        FormatLanguage::Rust => Some(rust::parse),
+        _ => None,
+    }
+}
+
+/// Problem found in the format strings of a unit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatIssue {
+    language: FormatLanguage,
+    field: String,
+    message: String,
+    locations: Vec<Location>,
+    span: Option<Span>,
+}
+
+impl FormatIssue {
+    fn new(unit: &Unit, language: FormatLanguage, field: &str, message: String) -> FormatIssue {
+        FormatIssue {
+            language,
+            field: field.to_string(),
+            message,
+            locations: unit.locations().clone(),
+            span: unit.field_span(field).or(unit.span()).copied(),
+        }
+    }
+
+    /// Get the language of the format strings.
+    pub fn language(&self) -> FormatLanguage {
+        self.language
+    }
+
+    /// Get the field in error, like `msgstr` or `msgstr[1]`.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Get the description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the locations of the unit in the source files.
+    pub fn locations(&self) -> &Vec<Location> {
+        &self.locations
+    }
+
+    /// Get the span of the field in error in the catalogue, for units read from a PO stream.
+    pub fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+/// Format the issue with the first location of the unit, or the line of the field in the catalogue.
+impl Display for FormatIssue {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if let Some(location) = self.locations.first() {
+            write!(f, "{}: ", location)?;
+        } else if let Some(span) = &self.span {
+            write!(f, "line {}: ", span.start().line())?;
+        }
+
+        f.write_str(&self.message)
+    }
+}
+
+/// Check the format strings of a unit, for each language of its format flags.
+///
+/// The obsolete units and the empty translations are not checked. All translations of a plural message are compared
+/// with `msgid_plural`, like `msgfmt` does. The translations of the plural forms used for only a few numbers, like the
+/// singular, may omit some arguments of the message, as the number is often not written in them.
+pub fn check_unit(unit: &Unit) -> Vec<FormatIssue> {
+    if unit.is_obsolete() {
+        return vec![];
+    }
+
+    unit.format_languages()
+        .into_iter()
+        .filter_map(|language| parser(language).map(|parse| (language, parse)))
+        .flat_map(|(language, parse)| check_message(unit, language, parse))
+        .collect()
+}
+
+/// Check the format strings of all units of a catalogue.
+pub fn check_catalog(catalog: &Catalog) -> Vec<FormatIssue> {
+    catalog.iter().flat_map(check_unit).collect()
+}
+
+fn check_message(unit: &Unit, language: FormatLanguage, parse: Parser) -> Vec<FormatIssue> {
+    let mut res = vec![];
+    let mut sources = vec![];
+    let mut targets = vec![];
+
+    match unit.message() {
+        Message::Simple { id, text } => {
+            sources.push(("msgid", id.as_str()));
+            targets.extend(
+                text.as_deref()
+                    .map(|text| ("msgid", String::from("msgstr"), text, true)),
+            );
+        }
+        Message::Plural(plural) => {
+            sources.push(("msgid", plural.singular()));
+            sources.push(("msgid_plural", plural.plural()));
+            targets.extend(
+                plural.values().iter().enumerate().map(|(i, text)| {
+                    ("msgid_plural", format!("msgstr[{}]", i), text.as_str(), is_general(plural, i))
+                }),
+            );
+        }
+    }
+
+    let mut parsed = BTreeMap::new();
+
+    for (field, text) in sources {
+        match parse(text) {
+            Ok(args) => {
+                parsed.insert(field, args);
+            }
+            Err(reason) => res.push(invalid(unit, language, field, &reason)),
+        }
+    }
+
+    if !res.is_empty() {
+        return res;
+    }
+
+    for (source, field, text, strict) in targets.into_iter().filter(|t| !t.2.is_empty()) {
+        match parse(text) {
+            Ok(args) => res.extend(
+                compare(source, &parsed[source], &field, &args, strict)
+                    .into_iter()
+                    .map(|message| FormatIssue::new(unit, language, &field, message)),
+            ),
+            Err(reason) => res.push(invalid(unit, language, &field, &reason)),
+        }
+    }
+
+    res
+}
+
+/// Whether a plural form is used for many numbers (more than 5 numbers below 1000, like `msgfmt` does), its
+/// translation must then use all the arguments of the message.
+///
+/// Without plural forms, the first form is supposed to be the singular, used for the number 1 only.
+fn is_general(plural: &Plural, index: usize) -> bool {
+    match plural.get_forms() {
+        Some(forms) => (0..1000)
+            .filter(|n| forms.get_value(*n) == Some(index))
+            .nth(GENERAL_FORM_COUNT)
+            .is_some(),
+        None => index > 0,
+    }
+}
+
+fn invalid(unit: &Unit, language: FormatLanguage, field: &str, reason: &str) -> FormatIssue {
+    let message = format!("‘{}’ is not a valid {} format string: {}", field, language, reason);
+
+    FormatIssue::new(unit, language, field, message)
+}
+
+/// Compare the arguments of a translation with the ones of its message.
+///
+/// When the comparison is not strict, the translation may omit the last numbered arguments and some named arguments.
+fn compare(source: &str, expected: &Arguments, field: &str, found: &Arguments, strict: bool) -> Vec<String> {
+    let mut res = vec![];
+    let (expected_count, found_count) = (expected.numbered.len(), found.numbered.len());
+
+    if found_count > expected_count || (strict && found_count < expected_count) {
+        res.push(format!(
+            "number of format specifications in ‘{}’ and ‘{}’ does not match ({} and {})",
+            source, field, expected_count, found_count
+        ));
+    }
+
+    for (i, (a, b)) in expected.numbered.iter().zip(&found.numbered).enumerate() {
+        if a != b {
+            res.push(format!(
+                "format specifications in ‘{}’ and ‘{}’ for argument {} are not the same (‘{}’ and ‘{}’)",
+                source,
+                field,
+                i + 1,
+                a,
+                b
+            ));
+        }
+    }
+
+    for (name, a) in &expected.named {
+        match found.named.get(name) {
+            Some(b) if a != b => res.push(format!(
+                "format specifications in ‘{}’ and ‘{}’ for argument ‘{}’ are not the same (‘{}’ and ‘{}’)",
+                source, field, name, a, b
+            )),
+            None if strict => res.push(format!(
+                "a format specification for argument ‘{}’ doesn't exist in ‘{}’",
+                name, field
+            )),
+            _ => {}
+        }
+    }
+
+    for name in found.named.keys().filter(|name| !expected.named.contains_key(*name)) {
+        res.push(format!(
+            "a format specification for argument ‘{}’, as in ‘{}’, doesn't exist in ‘{}’",
+            name, field, source
+        ));
+    }
+
+    res
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{flag::Flag, plural::PluralForms, PoParser};
+    use std::rc::Rc;
+
+    const SOURCE: &str = r#"#: src/main.c:10
+#, c-format
+msgid "%s has %d files"
+msgstr "%s a %d fichiers"
+
+#: src/main.c:20
+#, c-format
+msgid "%s has %d folders"
+msgstr "%d dossiers pour %s"
+
+#: src/main.c:30
+#, c-format
+msgid "%1$s has %2$d files"
+msgstr "%2$d fichiers pour %1$s"
+
+#, c-format
+msgid "%d file"
+msgid_plural "%d files"
+msgstr[0] "un fichier"
+msgstr[1] "%d fichiers %s"
+
+msgid "%s without flag"
+msgstr "%d sans drapeau"
+
+#, c-format
+msgid "Bad %y"
+msgstr "Mauvais %y"
+
+#, c-format
+msgid "Not translated %s"
+msgstr ""
+"#;
+
+    fn make_catalog() -> Catalog {
+        let parser = PoParser::new();
+
+        Catalog::from_reader(parser.parse(SOURCE.as_bytes()).unwrap()).unwrap()
+    }
+
+    fn messages(issues: &[FormatIssue]) -> Vec<String> {
+        issues.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_func_check_catalog() {
+        let issues = check_catalog(&make_catalog());
+
+        assert_eq!(
+            messages(&issues),
+            vec![
+                "src/main.c:20: format specifications in ‘msgid’ and ‘msgstr’ for argument 1 are not the same \
+                 (‘string’ and ‘int’)",
+                "src/main.c:20: format specifications in ‘msgid’ and ‘msgstr’ for argument 2 are not the same (‘int’ \
+                 and ‘string’)",
+                "line 20: number of format specifications in ‘msgid_plural’ and ‘msgstr[1]’ does not match (1 and \
+                 2)",
+                "line 26: ‘msgid’ is not a valid c format string: invalid conversion ‘y’",
+            ]
+        );
+
+        assert_eq!(issues[0].language(), FormatLanguage::C);
+        assert_eq!(issues[0].field(), "msgstr");
+        assert_eq!(issues[0].locations(), &vec![Location::new("src/main.c", Some(20))]);
+        assert_eq!(issues[0].span().map(|s| s.start().line()), Some(9));
+        assert_eq!(issues[2].field(), "msgstr[1]");
+        assert_eq!(issues[3].field(), "msgid");
+    }
+
+    #[test]
+    fn test_func_check_unit() {
+        let mut unit = Unit {
+            message: Message::Simple {
+                id: String::from("%d files"),
+                text: Some(String::from("%s fichiers")),
+            },
+            ..Unit::default()
+        };
+
+        assert!(
+            check_unit(&unit).is_empty(),
+            "Unit without format flag should not be checked"
+        );
+
+        unit.flags.insert(Flag::Format(FormatLanguage::C));
+        assert_eq!(
+            messages(&check_unit(&unit)),
+            vec![
+                "format specifications in ‘msgid’ and ‘msgstr’ for argument 1 are not the same (‘int’ and \
+                  ‘string’)"
+            ]
+        );
+
+        unit.message = Message::Simple {
+            id: String::from("%s files"),
+            text: Some(String::from("%999999999$s fichiers")),
+        };
+        assert_eq!(
+            messages(&check_unit(&unit)),
+            vec!["‘msgstr’ is not a valid c format string: argument number 999999999 is greater than 4096"]
+        );
+
+        unit.obsolete = true;
+        assert!(check_unit(&unit).is_empty(), "Obsolete unit should not be checked");
+    }
+
+    #[test]
+    fn test_func_check_languages() {
+        let source = r#"msgid ""
+msgstr ""
+"Plural-Forms: nplurals=3; plural=n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2;\n"
+
+#, python-format
+msgid "%(name)s has %(count)d files"
+msgstr "%(nom)s a %(count)d fichiers"
+
+#, python-brace-format
+msgid "{name} has {count} files"
+msgstr "{count} fichiers pour {name!r}"
+
+#, objc-format
+msgid "%@ has %d files"
+msgstr "%@ a %@ fichiers"
+
+#, rust-format
+msgid "{user} has {count:?} files"
+msgstr "{user} a {count} fichiers"
+
+#, rust-format
+msgid "One file for {user}"
+msgid_plural "{count} files for {user}"
+msgstr[0] "Jeden plik"
+msgstr[1] "{count} pliki"
+msgstr[2] "{count} plików dla {user}"
+"#;
+        let parser = PoParser::new();
+        let catalog = Catalog::from_reader(parser.parse(source.as_bytes()).unwrap()).unwrap();
+
+        assert_eq!(
+            messages(&check_catalog(&catalog)),
+            vec![
+                "line 7: a format specification for argument ‘name’ doesn't exist in ‘msgstr’",
+                "line 7: a format specification for argument ‘nom’, as in ‘msgstr’, doesn't exist in ‘msgid’",
+                "line 15: format specifications in ‘msgid’ and ‘msgstr’ for argument 2 are not the same (‘int’ \
+                 and ‘object’)",
+                "line 19: format specifications in ‘msgid’ and ‘msgstr’ for argument ‘count’ are not the same \
+                 (‘Debug’ and ‘Display’)",
+                "line 25: a format specification for argument ‘user’ doesn't exist in ‘msgstr[1]’",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_func_check_plural_forms() {
+        let source = r#"msgid ""
+msgstr ""
+"Plural-Forms: nplurals=3; plural=n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2;\n"
+
+#, c-format
+msgid "One file"
+msgid_plural "%d files"
+msgstr[0] "%d файл"
+msgstr[1] "%d файла"
+msgstr[2] "%d файлов"
+
+#, c-format
+msgid "One folder"
+msgid_plural "%d folders"
+msgstr[0] "Одна папка"
+msgstr[1] "%d папки"
+msgstr[2] "%d папок"
+"#;
+        let parser = PoParser::new();
+        let catalog = Catalog::from_reader(parser.parse(source.as_bytes()).unwrap()).unwrap();
+
+        assert_eq!(
+            messages(&check_catalog(&catalog)),
+            vec!["line 15: number of format specifications in ‘msgid_plural’ and ‘msgstr[0]’ does not match (1 and 0)"]
+        );
+    }
+
+    #[test]
+    fn test_func_is_general() {
+        let forms = PluralForms::parse("nplurals=2; plural=n>1;", &PoParser::new()).unwrap();
+        let plural = Plural::new(String::new(), String::new(), vec![], Some(Rc::new(forms)));
+
+        assert!(!is_general(&plural, 0));
+        assert!(is_general(&plural, 1));
+        assert!(!is_general(&plural, 2));
+        assert!(!is_general(&Plural::new_empty(), 0));
+        assert!(is_general(&Plural::new_empty(), 1));
+    }
+
+    #[test]
+    fn test_func_compare() {
+        let make = |numbered: &[&str], named: &[(&str, &str)]| Arguments {
+            numbered: numbered.iter().map(|t| t.to_string()).collect(),
+            named: named.iter().map(|(n, t)| (n.to_string(), t.to_string())).collect(),
+        };
+
+        let expected = make(&["int", "string"], &[("a", "int"), ("b", "string")]);
+
+        assert!(compare("msgid", &expected, "msgstr", &expected, true).is_empty());
+        assert!(compare("msgid", &expected, "msgstr", &make(&["int"], &[("a", "int")]), false).is_empty());
+        assert_eq!(
+            compare("msgid", &expected, "msgstr", &make(&["int"], &[("a", "int")]), true),
+            vec![
+                "number of format specifications in ‘msgid’ and ‘msgstr’ does not match (2 and 1)",
+                "a format specification for argument ‘b’ doesn't exist in ‘msgstr’",
+            ]
+        );
+        assert_eq!(
+            compare(
+                "msgid",
+                &expected,
+                "msgstr",
+                &make(&["int", "string", "int"], &[("a", "string"), ("c", "int")]),
+                false
+            ),
+            vec![
+                "number of format specifications in ‘msgid’ and ‘msgstr’ does not match (2 and 3)",
+                "format specifications in ‘msgid’ and ‘msgstr’ for argument ‘a’ are not the same (‘int’ and \
+                 ‘string’)",
+                "a format specification for argument ‘c’, as in ‘msgstr’, doesn't exist in ‘msgid’",
+            ]
+        );
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,141 @@
+//! Format strings of the `%` operator in Python.
+
+use super::Arguments;
+
+const FLAGS: &str = "#0- +";
+
+/// Parse a Python format string, with the named arguments like `%(name)s` or the unnamed arguments like `%s`.
+pub(super) fn parse(text: &str) -> Result<Arguments, String> {
+    let mut res = Arguments::default();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('%') {
+        rest = &rest[start + 1..];
+
+        if let Some(after) = rest.strip_prefix('%') {
+            rest = after;
+            continue;
+        }
+
+        let name = match rest.strip_prefix('(') {
+            Some(after) => {
+                let end = after.find(')').ok_or("unterminated argument name")?;
+
+                rest = &after[end + 1..];
+                Some(&after[..end])
+            }
+            None => None,
+        };
+
+        rest = rest.trim_start_matches(|c| FLAGS.contains(c));
+
+        let mut stars = 0;
+
+        rest = skip_width(rest, &mut stars);
+        if let Some(after) = rest.strip_prefix('.') {
+            rest = skip_width(after, &mut stars);
+        }
+
+        rest = rest.trim_start_matches(['h', 'l', 'L']);
+
+        let conversion = rest
+            .chars()
+            .next()
+            .ok_or("the string ends in the middle of a specification")?;
+
+        rest = &rest[conversion.len_utf8()..];
+
+        let arg_type = match conversion {
+            'd' | 'i' | 'o' | 'u' | 'x' | 'X' => "integer",
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' => "float",
+            'c' => "character",
+            's' | 'r' | 'a' => "any",
+            c => return Err(format!("invalid conversion ‘{}’", c)),
+        };
+
+        match name {
+            Some(_) if stars > 0 => {
+                return Err(String::from("a named argument cannot have a width or a precision ‘*’"))
+            }
+            Some(_) if !res.numbered.is_empty() => return Err(String::from("named and unnamed arguments are mixed")),
+            None if !res.named.is_empty() => return Err(String::from("named and unnamed arguments are mixed")),
+            Some(name) => match res.named.get(name) {
+                Some(t) if t != arg_type => {
+                    return Err(format!("argument ‘{}’ has the types ‘{}’ and ‘{}’", name, t, arg_type))
+                }
+                _ => {
+                    res.named.insert(name.to_string(), arg_type.to_string());
+                }
+            },
+            None => {
+                res.numbered.extend((0..stars).map(|_| String::from("integer")));
+                res.numbered.push(arg_type.to_string());
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// Skip a width or a precision, counting the `*` which take an argument.
+fn skip_width<'t>(text: &'t str, stars: &mut usize) -> &'t str {
+    match text.strip_prefix('*') {
+        Some(rest) => {
+            *stars += 1;
+            rest
+        }
+        None => text.trim_start_matches(|c: char| c.is_ascii_digit()),
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_func_parse() {
+        let args = parse("%s has %5.2f%% of %*d").unwrap();
+
+        assert_eq!(args.numbered, vec!["any", "float", "integer", "integer"]);
+        assert!(args.named.is_empty());
+
+        let args = parse("%(name)s has %(count)d files, %(name)r").unwrap();
+
+        assert!(args.numbered.is_empty());
+        assert_eq!(
+            args.named.into_iter().collect::<Vec<_>>(),
+            vec![
+                (String::from("count"), String::from("integer")),
+                (String::from("name"), String::from("any")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_func_parse_invalid() {
+        assert_eq!(parse("%y").unwrap_err(), "invalid conversion ‘y’");
+        assert_eq!(
+            parse("100%").unwrap_err(),
+            "the string ends in the middle of a specification"
+        );
+        assert_eq!(parse("%(name").unwrap_err(), "unterminated argument name");
+        assert_eq!(
+            parse("%(name)s %d").unwrap_err(),
+            "named and unnamed arguments are mixed"
+        );
+        assert_eq!(
+            parse("%d %(name)s").unwrap_err(),
+            "named and unnamed arguments are mixed"
+        );
+        assert_eq!(
+            parse("%(name)*d").unwrap_err(),
+            "a named argument cannot have a width or a precision ‘*’"
+        );
+        assert_eq!(
+            parse("%(n)s %(n)d").unwrap_err(),
+            "argument ‘n’ has the types ‘any’ and ‘integer’"
+        );
+    }
+}
+// no-coverage:stop
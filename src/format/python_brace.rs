@@ -0,0 +1,165 @@
+//! Format strings of the `format` method in Python.
+
+use super::Arguments;
+
+/// Parse a Python brace format string, like `{0}` or `{name!r:>10}`.
+///
+/// The arguments are given by name, the positional arguments being named by their number. A format specification can
+/// contain replacement fields, like `{:{width}}`.
+pub(super) fn parse(text: &str) -> Result<Arguments, String> {
+    let mut parser = FormatParser {
+        res: Arguments::default(),
+        automatic: None,
+        next: 0,
+    };
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        let (c, after) = (&rest[start..start + 1], &rest[start + 1..]);
+
+        rest = if let Some(after) = after.strip_prefix(c) {
+            after
+        } else if c == "}" {
+            return Err(String::from("unmatched ‘}’"));
+        } else {
+            parser.parse_field(after, false)?
+        };
+    }
+
+    Ok(parser.res)
+}
+
+struct FormatParser {
+    res: Arguments,
+
+    /// Whether the arguments are numbered automatically, known from the first positional field.
+    automatic: Option<bool>,
+    next: usize,
+}
+
+impl FormatParser {
+    /// Parse a replacement field after its `{`, and give the text after its `}`.
+    fn parse_field<'t>(&mut self, text: &'t str, nested: bool) -> Result<&'t str, String> {
+        let end = text.find(['!', ':', '{', '}']).unwrap_or(text.len());
+        let name = text[..end].split(['.', '[']).next().unwrap_or_default();
+        let mut rest = &text[end..];
+
+        self.add(name)?;
+
+        if let Some(after) = rest.strip_prefix('!') {
+            let mut chars = after.chars();
+
+            match chars.next() {
+                Some('r' | 's' | 'a') => rest = chars.as_str(),
+                Some(c) => return Err(format!("invalid conversion ‘{}’", c)),
+                None => return Err(String::from("unterminated replacement field")),
+            }
+        }
+
+        if let Some(after) = rest.strip_prefix(':') {
+            rest = after;
+
+            loop {
+                match rest.find(['{', '}']) {
+                    Some(start) if rest[start..].starts_with('}') => {
+                        rest = &rest[start..];
+                        break;
+                    }
+                    Some(_) if nested => return Err(String::from("replacement fields are nested too deeply")),
+                    Some(start) => rest = self.parse_field(&rest[start + 1..], true)?,
+                    None => return Err(String::from("unterminated replacement field")),
+                }
+            }
+        }
+
+        match rest.find('}') {
+            Some(0) => Ok(&rest[1..]),
+            Some(_) if rest.starts_with('{') => Err(String::from("unexpected ‘{’ in a replacement field")),
+            Some(_) => Err(format!(
+                "invalid replacement field ‘{{{}’",
+                &text[..text.len() - rest.len()]
+            )),
+            None => Err(String::from("unterminated replacement field")),
+        }
+    }
+
+    fn add(&mut self, name: &str) -> Result<(), String> {
+        let name = if name.is_empty() {
+            self.set_automatic(true)?;
+            self.next += 1;
+            (self.next - 1).to_string()
+        } else if name.bytes().all(|b| b.is_ascii_digit()) {
+            self.set_automatic(false)?;
+            name.parse::<usize>()
+                .map_err(|_| format!("invalid argument number ‘{}’", name))?
+                .to_string()
+        } else if is_identifier(name) {
+            name.to_string()
+        } else {
+            return Err(format!("invalid argument name ‘{}’", name));
+        };
+
+        self.res.named.insert(name, String::from("any"));
+        Ok(())
+    }
+
+    fn set_automatic(&mut self, automatic: bool) -> Result<(), String> {
+        if *self.automatic.get_or_insert(automatic) == automatic {
+            Ok(())
+        } else {
+            Err(String::from(
+                "automatic and manual numbering of the arguments are mixed",
+            ))
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(text: &str) -> Vec<String> {
+        parse(text).unwrap().named.into_keys().collect()
+    }
+
+    #[test]
+    fn test_func_parse() {
+        assert!(names("No {{format}}").is_empty());
+        assert_eq!(names("{} has {} files"), vec!["0", "1"]);
+        assert_eq!(names("{1} has {0} files"), vec!["0", "1"]);
+        assert_eq!(names("{name!r:>10} has {count:d} files"), vec!["count", "name"]);
+        assert_eq!(names("{user.name} {items[0]}"), vec!["items", "user"]);
+        assert_eq!(
+            names("{value:{width}.{precision}}"),
+            vec!["precision", "value", "width"]
+        );
+        assert_eq!(names("{été}"), vec!["été"]);
+    }
+
+    #[test]
+    fn test_func_parse_invalid() {
+        assert_eq!(parse("{name").unwrap_err(), "unterminated replacement field");
+        assert_eq!(parse("name}").unwrap_err(), "unmatched ‘}’");
+        assert_eq!(parse("{name!x}").unwrap_err(), "invalid conversion ‘x’");
+        assert_eq!(parse("{name!").unwrap_err(), "unterminated replacement field");
+        assert_eq!(parse("{a b}").unwrap_err(), "invalid argument name ‘a b’");
+        assert_eq!(
+            parse("{} {1}").unwrap_err(),
+            "automatic and manual numbering of the arguments are mixed"
+        );
+        assert_eq!(
+            parse("{:{:{}}}").unwrap_err(),
+            "replacement fields are nested too deeply"
+        );
+        assert_eq!(parse("{a{b}").unwrap_err(), "unexpected ‘{’ in a replacement field");
+        assert_eq!(parse("{a!rx}").unwrap_err(), "invalid replacement field ‘{a!r’");
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,206 @@
+//! Format strings of the `format!` macro in Rust.
+
+use super::Arguments;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Parse a Rust format string, like `{}`, `{0}` or `{name:?}`.
+///
+/// The arguments are given by name, the positional arguments being named by their number. The type of an argument is
+/// the formatting trait it needs, like `Display` or `Debug`, and `usize` for the widths and precisions.
+pub(super) fn parse(text: &str) -> Result<Arguments, String> {
+    let mut parser = FormatParser {
+        traits: BTreeMap::new(),
+        next: 0,
+    };
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        let (c, after) = (&rest[start..start + 1], &rest[start + 1..]);
+
+        rest = if let Some(after) = after.strip_prefix(c) {
+            after
+        } else if c == "}" {
+            return Err(String::from("unmatched ‘}’"));
+        } else {
+            let end = after.find('}').ok_or("unterminated placeholder")?;
+
+            parser.parse_placeholder(&after[..end])?;
+            &after[end + 1..]
+        };
+    }
+
+    let named = parser
+        .traits
+        .into_iter()
+        .map(|(name, traits)| (name, traits.into_iter().collect::<Vec<_>>().join(" + ")))
+        .collect();
+
+    Ok(Arguments {
+        named,
+        ..Arguments::default()
+    })
+}
+
+struct FormatParser {
+    traits: BTreeMap<String, BTreeSet<&'static str>>,
+
+    /// Number of the next implicit positional argument.
+    next: usize,
+}
+
+impl FormatParser {
+    /// Parse the text of a placeholder, between its braces.
+    fn parse_placeholder(&mut self, text: &str) -> Result<(), String> {
+        if text.contains('{') {
+            return Err(format!("invalid placeholder ‘{{{}}}’", text));
+        }
+
+        let (name, spec) = text.split_once(':').unwrap_or((text, ""));
+        let mut spec = spec;
+
+        if !name.is_empty() {
+            check_name(name)?;
+        }
+
+        if spec.chars().nth(1).is_some_and(|c| "<^>".contains(c)) {
+            spec = &spec[spec.chars().next().map(char::len_utf8).unwrap_or_default() + 1..];
+        } else if spec.starts_with(['<', '^', '>']) {
+            spec = &spec[1..];
+        }
+
+        spec = spec.strip_prefix(['+', '-']).unwrap_or(spec);
+        spec = spec.strip_prefix('#').unwrap_or(spec);
+        if spec.starts_with('0') && !spec.starts_with("0$") {
+            spec = &spec[1..];
+        }
+
+        spec = self.parse_count(spec)?;
+        if let Some(after) = spec.strip_prefix('.') {
+            spec = match after.strip_prefix('*') {
+                Some(after) => {
+                    self.add_next("usize");
+                    after
+                }
+                None => self.parse_count(after)?,
+            };
+        }
+
+        let value_trait = match spec {
+            "" => "Display",
+            "?" | "x?" | "X?" => "Debug",
+            "x" => "LowerHex",
+            "X" => "UpperHex",
+            "o" => "Octal",
+            "b" => "Binary",
+            "e" => "LowerExp",
+            "E" => "UpperExp",
+            "p" => "Pointer",
+            _ => return Err(format!("invalid format specification ‘{}’", text)),
+        };
+
+        if name.is_empty() {
+            self.add_next(value_trait);
+        } else {
+            self.add(name, value_trait);
+        }
+
+        Ok(())
+    }
+
+    /// Parse a width or a precision, which is a number or an argument like `width$`, and give the text after it.
+    fn parse_count<'t>(&mut self, text: &'t str) -> Result<&'t str, String> {
+        let end = text
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(text.len());
+
+        match text[end..].strip_prefix('$') {
+            Some(after) => {
+                check_name(&text[..end])?;
+                self.add(&text[..end], "usize");
+                Ok(after)
+            }
+            None => Ok(text.trim_start_matches(|c: char| c.is_ascii_digit())),
+        }
+    }
+
+    fn add(&mut self, name: &str, value_trait: &'static str) {
+        let name = name
+            .parse::<usize>()
+            .map_or_else(|_| name.to_string(), |n| n.to_string());
+
+        self.traits.entry(name).or_default().insert(value_trait);
+    }
+
+    fn add_next(&mut self, value_trait: &'static str) {
+        self.next += 1;
+        self.add(&(self.next - 1).to_string(), value_trait);
+    }
+}
+
+/// Check that an argument is a number or an identifier.
+fn check_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = if name.bytes().all(|b| b.is_ascii_digit()) {
+        name.parse::<usize>().is_ok()
+    } else {
+        chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_')
+            && name != "_"
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid argument name ‘{}’", name))
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(text: &str) -> Vec<(String, String)> {
+        parse(text).unwrap().named.into_iter().collect()
+    }
+
+    fn make(args: &[(&str, &str)]) -> Vec<(String, String)> {
+        args.iter().map(|(n, t)| (n.to_string(), t.to_string())).collect()
+    }
+
+    #[test]
+    fn test_func_parse() {
+        assert!(types("No {{format}}").is_empty());
+        assert_eq!(types("{} has {} files"), make(&[("0", "Display"), ("1", "Display")]));
+        assert_eq!(types("{1} has {0:?} files"), make(&[("0", "Debug"), ("1", "Display")]));
+        assert_eq!(types("{name} {name:?}"), make(&[("name", "Debug + Display")]));
+        assert_eq!(
+            types("{value:>width$.prec$x} {:#010b} {:.*}"),
+            make(&[
+                ("0", "Binary"),
+                ("1", "usize"),
+                ("2", "Display"),
+                ("prec", "usize"),
+                ("value", "LowerHex"),
+                ("width", "usize"),
+            ])
+        );
+        assert_eq!(types("{:*^5} {:é<3?}"), make(&[("0", "Display"), ("1", "Debug")]));
+        assert_eq!(
+            types("{:1$} {:0$}"),
+            make(&[("0", "Display + usize"), ("1", "Display + usize")])
+        );
+    }
+
+    #[test]
+    fn test_func_parse_invalid() {
+        assert_eq!(parse("{name").unwrap_err(), "unterminated placeholder");
+        assert_eq!(parse("name}").unwrap_err(), "unmatched ‘}’");
+        assert_eq!(parse("{a b}").unwrap_err(), "invalid argument name ‘a b’");
+        assert_eq!(parse("{_}").unwrap_err(), "invalid argument name ‘_’");
+        assert_eq!(parse("{a{b}").unwrap_err(), "invalid placeholder ‘{a{b}’");
+        assert_eq!(parse("{:q}").unwrap_err(), "invalid format specification ‘:q’");
+        assert_eq!(parse("{:a-b$}").unwrap_err(), "invalid format specification ‘:a-b$’");
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,146 @@
+use locale_config::LanguageRange;
+use std::collections::HashMap;
+
+/// Usual order of the properties in the header entry.
+const HEADER_ORDER: [&str; 11] = [
+    "Project-Id-Version",
+    "Report-Msgid-Bugs-To",
+    "POT-Creation-Date",
+    "PO-Revision-Date",
+    "Last-Translator",
+    "Language-Team",
+    "Language",
+    "MIME-Version",
+    "Content-Type",
+    "Content-Transfer-Encoding",
+    "Plural-Forms",
+];
+
+/// Sort header properties like gettext tools do, unknown properties come last in alphabetic order.
+pub(crate) fn ordered_properties(properties: &HashMap<String, String>) -> Vec<(&str, &str)> {
+    let mut res: Vec<_> = properties.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+
+    res.sort_by_key(|(k, _)| {
+        (
+            HEADER_ORDER.iter().position(|h| h == k).unwrap_or(HEADER_ORDER.len()),
+            *k,
+        )
+    });
+    res
+}
+
+/// Format properties as the text of a header entry.
+pub(crate) fn format_properties<K, V, P>(properties: P) -> String
+where
+    K: AsRef<str>,
+    V: AsRef<str>,
+    P: IntoIterator<Item = (K, V)>, {
+    properties
+        .into_iter()
+        .map(|(k, v)| format!("{}: {}\n", k.as_ref(), v.as_ref()))
+        .collect()
+}
+
+/// Parse the properties of a header entry, values of repeated keys are joined with a space.
+pub(crate) fn parse_properties(text: &str) -> HashMap<String, String> {
+    let mut properties: HashMap<String, Vec<String>> = HashMap::new();
+
+    for line in text.split('\n') {
+        if let Some(n) = line.find(':') {
+            let key = line[..n].trim();
+            let val = line[(n + 1)..].trim();
+
+            properties.entry(key.to_owned()).or_default().push(val.to_owned());
+        }
+    }
+
+    properties.into_iter().map(|(k, l)| (k, l.join(" "))).collect()
+}
+
+/// Parse the value of the `Language` property, the invariant language is returned if the value is invalid.
+pub(crate) fn parse_language(lang: &str) -> LanguageRange<'static> {
+    LanguageRange::new(lang)
+        .map(LanguageRange::into_static)
+        .or_else(|_| LanguageRange::from_unix(lang))
+        .unwrap_or_else(|_| LanguageRange::invariant())
+}
+
+/// Get the names of the locale directories for a language, from the most specific to the most general.
+///
+/// For instance, the language `pt-BR` gives `pt_BR` then `pt`, and the invariant language gives no name.
+pub fn language_chain(language: &LanguageRange) -> Vec<String> {
+    let tags: Vec<&str> = language.as_ref().split('-').filter(|t| !t.is_empty()).collect();
+
+    (1..=tags.len()).rev().map(|n| tags[..n].join("_")).collect()
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_func_parse_properties() {
+        let properties = parse_properties("Header-1: Value1\nLanguage: en\nNo property\nHeader-1: Value2\n");
+
+        assert_eq!(
+            properties,
+            [("Header-1", "Value1 Value2"), ("Language", "en")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        );
+    }
+
+    #[test]
+    fn test_func_ordered_properties() {
+        let properties = [
+            ("X-Generator", "test"),
+            ("Plural-Forms", "nplurals=2; plural=n>1;"),
+            ("Language", "fr"),
+            ("Another", "value"),
+            ("Project-Id-Version", "poreader"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        assert_eq!(
+            ordered_properties(&properties),
+            vec![
+                ("Project-Id-Version", "poreader"),
+                ("Language", "fr"),
+                ("Plural-Forms", "nplurals=2; plural=n>1;"),
+                ("Another", "value"),
+                ("X-Generator", "test"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_func_format_properties() {
+        assert_eq!(format_properties(Vec::<(&str, &str)>::new()), "");
+        assert_eq!(
+            format_properties([("Language", "fr"), ("Header", "Value")]),
+            "Language: fr\nHeader: Value\n"
+        );
+    }
+
+    #[test]
+    fn test_func_parse_language() {
+        assert_eq!(parse_language("fr").as_ref(), "fr");
+        assert_eq!(parse_language("pt_BR").as_ref(), "pt-BR");
+        assert_eq!(parse_language("#!").as_ref(), "");
+    }
+
+    #[test]
+    fn test_func_language_chain() {
+        let chain = |lang| language_chain(&parse_language(lang));
+
+        assert_eq!(chain("pt_BR"), vec!["pt_BR", "pt"]);
+        assert_eq!(chain("zh-Hant-TW"), vec!["zh_Hant_TW", "zh_Hant", "zh"]);
+        assert_eq!(chain("fr"), vec!["fr"]);
+        assert!(chain("").is_empty());
+    }
+}
+// no-coverage:stop
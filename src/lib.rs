@@ -59,18 +59,36 @@
 extern crate locale_config;
 extern crate regex;
 
+mod charset;
 mod enums;
+mod header;
+mod merge;
+mod mo;
 mod po;
+mod xliff;
 
+pub mod catalog;
 pub mod comment;
+pub mod diagnostic;
+pub mod domain;
 pub mod error;
+pub mod flag;
+pub mod format;
+pub mod location;
 pub mod note;
 pub mod plural;
+pub mod similarity;
+pub mod span;
 pub mod unit;
 
 pub use self::{
+    catalog::Catalog,
+    domain::TextDomains,
     enums::{Message, Origin, State},
-    po::{PoParser, PoReader},
+    merge::Merger,
+    mo::{MoCompiler, MoReader},
+    po::{PoParser, PoReader, PoWriter},
+    xliff::{XliffReader, XliffWriter},
 };
 
 use locale_config::LanguageRange;
@@ -93,5 +111,10 @@ pub trait CatalogueReader: Iterator<Item = Result<unit::Unit, error::Error>> {
     /// Header properties as a map
     fn header_properties(&self) -> &HashMap<String, String>;
 
+    /// Original text of the header entry, only kept by readers in lossless mode
+    fn header_source(&self) -> Option<&str> {
+        None
+    }
+
     // TODO: More attributes, possibly a generic API
 }
@@ -0,0 +1,181 @@
+use std::fmt::{Display, Formatter, Result};
+
+/// First strong isolate, put before the file names containing spaces since gettext 0.20.
+const FSI: char = '\u{2068}';
+
+/// Pop directional isolate, put after the file names containing spaces since gettext 0.20.
+const PDI: char = '\u{2069}';
+
+/// Location of a message in the source files, from a `#:` comment
+///
+/// It contains the file name and optionally the line number
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Location {
+    file: String,
+    line: Option<u32>,
+}
+
+impl Location {
+    pub fn new<F: Into<String>>(file: F, line: Option<u32>) -> Location {
+        Location {
+            file: file.into(),
+            line,
+        }
+    }
+
+    /// Parse one location like `file.c:12`, the isolation marks around the file name are removed.
+    pub fn parse(text: &str) -> Location {
+        let (file, line) = split_line(text.trim());
+
+        Location::new(file.trim_start_matches(FSI).trim_end_matches(PDI), line)
+    }
+
+    /// Parse the locations of a `#:` comment, separated by spaces.
+    ///
+    /// A file name between the isolation marks U+2068 and U+2069 can contain spaces.
+    pub(crate) fn parse_list(text: &str) -> Vec<Location> {
+        let mut res = vec![];
+        let mut rest = text.trim_start();
+
+        while !rest.is_empty() {
+            let end = match rest.strip_prefix(FSI).and_then(|s| s.find(PDI)) {
+                Some(n) => {
+                    let after = FSI.len_utf8() + n + PDI.len_utf8();
+
+                    after + rest[after..].find(char::is_whitespace).unwrap_or(rest.len() - after)
+                }
+                None => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+            };
+
+            res.push(Location::parse(&rest[..end]));
+            rest = rest[end..].trim_start();
+        }
+
+        res
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+}
+
+/// Split the line number at the end of a location, if there is one.
+fn split_line(text: &str) -> (&str, Option<u32>) {
+    if let Some((file, line)) = text.rsplit_once(':') {
+        if !file.is_empty() && !line.is_empty() && line.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(line) = line.parse() {
+                return (file, Some(line));
+            }
+        }
+    }
+
+    (text, None)
+}
+
+/// Format the location like in a `#:` comment, a file name with spaces is put between isolation marks.
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if self.file.contains(char::is_whitespace) {
+            write!(f, "{}{}{}", FSI, self.file, PDI)?;
+        } else {
+            f.write_str(&self.file)?;
+        }
+
+        match self.line {
+            Some(line) => write!(f, ":{}", line),
+            None => Ok(()),
+        }
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_location() -> Location {
+        Location::new("src/main.rs", Some(12))
+    }
+
+    #[test]
+    fn test_struct() {
+        let location = make_location();
+
+        assert_eq!(location.clone(), location);
+        assert_eq!(
+            format!("{:?}", location),
+            format!("Location {{ file: {:?}, line: {:?} }}", location.file, location.line),
+        );
+    }
+
+    #[test]
+    fn test_func_accessors() {
+        let location = make_location();
+
+        assert_eq!(location.file(), "src/main.rs");
+        assert_eq!(location.line(), Some(12));
+        assert_eq!(Location::new("main.c", None).line(), None);
+    }
+
+    #[test]
+    fn test_func_parse() {
+        assert_eq!(Location::parse("src/main.rs:12"), make_location());
+        assert_eq!(Location::parse(" main.c "), Location::new("main.c", None));
+        assert_eq!(Location::parse("a:b:3"), Location::new("a:b", Some(3)));
+        assert_eq!(Location::parse("C:\\main.c"), Location::new("C:\\main.c", None));
+        assert_eq!(Location::parse("main.c:"), Location::new("main.c:", None));
+        assert_eq!(Location::parse(":12"), Location::new(":12", None));
+        assert_eq!(
+            Location::parse("main.c:99999999999"),
+            Location::new("main.c:99999999999", None)
+        );
+        assert_eq!(
+            Location::parse("\u{2068}my file.c\u{2069}:7"),
+            Location::new("my file.c", Some(7))
+        );
+    }
+
+    #[test]
+    fn test_func_parse_list() {
+        assert_eq!(
+            Location::parse_list(" src/main.rs:12  other.c\tlib.rs:3 "),
+            vec![
+                make_location(),
+                Location::new("other.c", None),
+                Location::new("lib.rs", Some(3)),
+            ]
+        );
+        assert_eq!(
+            Location::parse_list("\u{2068}my file.c\u{2069}:7 \u{2068}a  b\u{2069} c.c:1"),
+            vec![
+                Location::new("my file.c", Some(7)),
+                Location::new("a  b", None),
+                Location::new("c.c", Some(1)),
+            ]
+        );
+        assert_eq!(
+            Location::parse_list("\u{2068}unterminated file.c:1"),
+            vec![Location::new("unterminated", None), Location::new("file.c", Some(1)),]
+        );
+        assert!(Location::parse_list("  ").is_empty());
+    }
+
+    #[test]
+    fn test_trait_display() {
+        assert_eq!(make_location().to_string(), "src/main.rs:12");
+        assert_eq!(Location::new("main.c", None).to_string(), "main.c");
+        assert_eq!(
+            Location::new("my file.c", Some(7)).to_string(),
+            "\u{2068}my file.c\u{2069}:7"
+        );
+
+        for text in ["src/main.rs:12", "main.c", "\u{2068}my file.c\u{2069}:7"] {
+            assert_eq!(Location::parse_list(text)[0].to_string(), text);
+        }
+    }
+}
+// no-coverage:stop
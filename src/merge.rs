@@ -0,0 +1,510 @@
+//! Update of a translated catalogue from a new template, like `msgmerge` does.
+
+use crate::{
+    flag::Flag,
+    note::Note,
+    plural::{Plural, PluralForms},
+    similarity::SimilarityIndex,
+    unit::Unit,
+    Catalog, Message, Origin, State,
+};
+
+use std::rc::Rc;
+
+/// Minimal similarity of two messages for a fuzzy match, as given by `similarity::similarity`.
+const FUZZY_THRESHOLD: f64 = 0.6;
+
+/// Merger of a translated catalogue with a template.
+///
+/// The result has the units of the template, in the order of the template, with:
+///
+/// - the translations of the units with the same context and message, the locations, the developer notes and the
+///   flags (except `fuzzy` and the wrap flags, set by the translator) coming from the template,
+/// - otherwise, the translation of the most similar message, marked as fuzzy, with the previous context and message
+///   set to the ones of the translated unit,
+/// - otherwise, no translation.
+///
+/// The translated units which are not in the template, and whose translation was not used for a fuzzy match, are kept as
+/// obsolete units, after the others.
+/// The header is the header of the translated catalogue, with the `POT-Creation-Date` of the template.
+#[derive(Clone, Debug)]
+pub struct Merger {
+    fuzzy_matching: bool,
+}
+
+impl Merger {
+    pub fn new() -> Merger {
+        Merger { fuzzy_matching: true }
+    }
+
+    /// Enable or disable the fuzzy matching, it is enabled by default like in `msgmerge`.
+    pub fn set_fuzzy_matching(&mut self, enabled: bool) {
+        self.fuzzy_matching = enabled;
+    }
+
+    /// Merge the translated catalogue `definitions` with the template `template`.
+    pub fn merge(&self, definitions: &Catalog, template: &Catalog) -> Catalog {
+        let forms = definitions.plural_forms().ok().flatten().map(Rc::new);
+
+        let mut res = definitions.without_units();
+        let mut used = vec![false; definitions.len()];
+        let index = SimilarityIndex::from_units(definitions.iter().filter(|def| !def.message.is_blank()));
+
+        if let Some(date) = template.header_properties().get("POT-Creation-Date") {
+            res.set_header_property("POT-Creation-Date", date);
+        }
+
+        for unit in template.iter().filter(|u| !u.is_obsolete()) {
+            let merged = match definitions.position(unit.context(), unit.message().get_id()) {
+                Some(pos) => {
+                    used[pos] = true;
+
+                    definitions
+                        .iter()
+                        .nth(pos)
+                        .map(|def| merge_exact(unit, def, &forms))
+                        .unwrap_or_default()
+                }
+                None => match self.find_similar(unit, definitions, &index) {
+                    Some(pos) => {
+                        used[pos] = true;
+
+                        definitions
+                            .iter()
+                            .nth(pos)
+                            .map(|def| merge_fuzzy(unit, def, &forms))
+                            .unwrap_or_default()
+                    }
+                    None => untranslated(unit, &forms),
+                },
+            };
+
+            res.insert(merged);
+        }
+
+        for (def, _) in definitions.iter().zip(used).filter(|(_, used)| !used) {
+            if def.obsolete {
+                res.insert(def.clone());
+            } else if !def.message.is_blank() {
+                res.insert(make_obsolete(def));
+            }
+        }
+
+        res
+    }
+
+    /// Find the position of the translated unit with the most similar message.
+    fn find_similar(&self, unit: &Unit, definitions: &Catalog, index: &SimilarityIndex) -> Option<usize> {
+        let id = unit.message().get_id();
+
+        if !self.fuzzy_matching || id.is_empty() {
+            return None;
+        }
+
+        index
+            .best(id, FUZZY_THRESHOLD)
+            .and_then(|candidate| definitions.position(candidate.unit().context(), candidate.unit().message().get_id()))
+    }
+}
+
+impl Default for Merger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Remove what refers to the original text of a unit.
+fn detach(unit: &mut Unit) {
+    unit.source = None;
+    unit.span = None;
+    unit.field_spans.clear();
+    unit.comment_spans.clear();
+}
+
+/// Build the message of a template unit with the translations of another message, the boolean is true if a message is
+/// plural and not the other one.
+fn combine(message: &Message, translated: &Message, forms: &Option<Rc<PluralForms>>) -> (Message, bool) {
+    match (message, translated) {
+        (Message::Simple { id, .. }, Message::Simple { text, .. }) => (
+            Message::Simple {
+                id: id.clone(),
+                text: text.clone(),
+            },
+            false,
+        ),
+        (Message::Simple { id, .. }, Message::Plural(plural)) => (
+            Message::Simple {
+                id: id.clone(),
+                text: Some(plural.first().to_string()),
+            },
+            true,
+        ),
+        (Message::Plural(plural), Message::Simple { text, .. }) => (
+            Message::Plural(Plural::new(
+                plural.singular().to_string(),
+                plural.plural().to_string(),
+                text.iter().cloned().collect(),
+                forms.clone(),
+            )),
+            true,
+        ),
+        (Message::Plural(plural), Message::Plural(other)) => (
+            Message::Plural(Plural::new(
+                plural.singular().to_string(),
+                plural.plural().to_string(),
+                other.values().clone(),
+                other.shared_forms().or_else(|| forms.clone()),
+            )),
+            false,
+        ),
+    }
+}
+
+/// Build a template unit with the translations and the translator comments of another unit.
+fn translate(unit: &Unit, def: &Unit, forms: &Option<Rc<PluralForms>>) -> (Unit, bool) {
+    let mut res = unit.clone();
+    let (message, mismatch) = combine(&unit.message, &def.message, forms);
+
+    detach(&mut res);
+    res.message = message;
+    res.comments = def.comments.clone();
+    res.notes = def
+        .notes
+        .iter()
+        .filter(|n| *n.origin() == Origin::Translator)
+        .chain(unit.notes.iter().filter(|n| *n.origin() == Origin::Developer))
+        .cloned()
+        .collect::<Vec<Note>>();
+
+    res.flags = unit
+        .flags
+        .iter()
+        .filter(|f| !is_translator_flag(f))
+        .chain(def.flags.iter().filter(|f| is_translator_flag(f)))
+        .cloned()
+        .collect();
+    res.flags.remove(&Flag::Fuzzy);
+
+    (res, mismatch)
+}
+
+/// Whether a flag is set by the translator, the other flags (like the format flags) come from the source code.
+fn is_translator_flag(flag: &Flag) -> bool {
+    matches!(flag, Flag::Fuzzy | Flag::Wrap | Flag::NoWrap)
+}
+
+fn set_state(unit: &mut Unit, state: State) {
+    unit.state = if unit.message.is_blank() { State::Empty } else { state };
+
+    if unit.state == State::NeedsWork {
+        unit.flags.insert(Flag::Fuzzy);
+    }
+}
+
+fn merge_exact(unit: &Unit, def: &Unit, forms: &Option<Rc<PluralForms>>) -> Unit {
+    let (mut res, mismatch) = translate(unit, def, forms);
+
+    if mismatch || def.obsolete {
+        set_state(&mut res, State::NeedsWork);
+    } else {
+        set_state(&mut res, def.state);
+    }
+
+    if res.state == State::NeedsWork {
+        res.prev_context = def.prev_context.clone();
+        res.prev_message = def.prev_message.clone();
+    }
+
+    res
+}
+
+fn merge_fuzzy(unit: &Unit, def: &Unit, forms: &Option<Rc<PluralForms>>) -> Unit {
+    let (mut res, _) = translate(unit, def, forms);
+
+    set_state(&mut res, State::NeedsWork);
+    res.prev_context = def.context.clone();
+    res.prev_message = match &def.message {
+        Message::Simple { id, .. } => Message::Simple {
+            id: id.clone(),
+            text: None,
+        },
+        Message::Plural(plural) => Message::Plural(Plural::new(
+            plural.singular().to_string(),
+            plural.plural().to_string(),
+            vec![],
+            None,
+        )),
+    };
+
+    res
+}
+
+fn untranslated(unit: &Unit, forms: &Option<Rc<PluralForms>>) -> Unit {
+    let mut res = unit.clone();
+
+    detach(&mut res);
+    res.state = State::Empty;
+    res.flags.remove(&Flag::Fuzzy);
+    res.message = match &unit.message {
+        Message::Simple { id, .. } => Message::Simple {
+            id: id.clone(),
+            text: None,
+        },
+        Message::Plural(plural) => Message::Plural(Plural::new(
+            plural.singular().to_string(),
+            plural.plural().to_string(),
+            vec![],
+            forms.clone(),
+        )),
+    };
+
+    res
+}
+
+fn make_obsolete(def: &Unit) -> Unit {
+    let mut res = def.clone();
+
+    detach(&mut res);
+    res.obsolete = true;
+    res.locations.clear();
+    res.notes.retain(|n| *n.origin() == Origin::Translator);
+
+    res
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{flag::FormatLanguage, location::Location, PoParser, PoWriter};
+
+    const DEFINITIONS: &str = r#"# French translation
+msgid ""
+msgstr ""
+"POT-Creation-Date: 2024-01-01 10:00+0000\n"
+"Language: fr\n"
+"Plural-Forms: nplurals=2; plural=n > 1;\n"
+
+# Translator note
+#. Old developer note
+#: src/old.rs:1
+msgid "Open the file"
+msgstr "Ouvrir le fichier"
+
+#, c-format
+msgid "Delete %s"
+msgstr "Supprimer %s"
+
+msgid "One file"
+msgid_plural "%d files"
+msgstr[0] "Un fichier"
+msgstr[1] "%d fichiers"
+
+msgid "Removed message"
+msgstr "Message supprimé"
+
+msgid "Untranslated"
+msgstr ""
+
+#~ msgid "Very old"
+#~ msgstr "Très vieux"
+"#;
+
+    const TEMPLATE: &str = r#"msgid ""
+msgstr ""
+"POT-Creation-Date: 2024-06-01 10:00+0000\n"
+
+#. New developer note
+#: src/main.rs:10
+msgid "Open the file"
+msgstr ""
+
+#: src/main.rs:20
+msgid "Open the files"
+msgstr ""
+
+#, c-format
+msgid "Delete %s now"
+msgstr ""
+
+msgid "One file"
+msgid_plural "%d files"
+msgstr[0] ""
+msgstr[1] ""
+
+msgid "A brand new message"
+msgid_plural "Brand new messages"
+msgstr[0] ""
+msgstr[1] ""
+"#;
+
+    fn parse(source: &str) -> Catalog {
+        let parser = PoParser::new();
+
+        Catalog::from_reader(parser.parse(source.as_bytes()).unwrap()).unwrap()
+    }
+
+    fn make_merge(fuzzy_matching: bool) -> Catalog {
+        let mut merger = Merger::default();
+
+        merger.set_fuzzy_matching(fuzzy_matching);
+        merger.merge(&parse(DEFINITIONS), &parse(TEMPLATE))
+    }
+
+    #[test]
+    fn test_func_merge_exact() {
+        let catalog = make_merge(true);
+        let unit = catalog.get(None, "Open the file").unwrap();
+
+        assert_eq!(unit.message().get_text(), "Ouvrir le fichier");
+        assert_eq!(unit.state(), State::Final);
+        assert_eq!(unit.locations(), &vec![Location::new("src/main.rs", Some(10))]);
+        assert_eq!(
+            unit.notes(),
+            &vec![
+                Note::new(Origin::Translator, String::from("Translator note")),
+                Note::new(Origin::Developer, String::from("New developer note")),
+            ]
+        );
+        assert!(unit.span().is_none());
+
+        let unit = catalog.get(None, "One file").unwrap();
+
+        assert_eq!(unit.message().get_plural_text(2), Some("%d fichiers"));
+        assert_eq!(unit.state(), State::Final);
+    }
+
+    #[test]
+    fn test_func_merge_fuzzy() {
+        let catalog = make_merge(true);
+        let unit = catalog.get(None, "Open the files").unwrap();
+
+        assert_eq!(unit.message().get_text(), "Ouvrir le fichier");
+        assert_eq!(unit.state(), State::NeedsWork);
+        assert!(unit.flags().contains(&Flag::Fuzzy));
+        assert_eq!(unit.prev_context(), None);
+        assert_eq!(unit.prev_message().get_id(), "Open the file");
+        assert_eq!(unit.locations(), &vec![Location::new("src/main.rs", Some(20))]);
+
+        let unit = catalog.get(None, "Delete %s now").unwrap();
+
+        assert_eq!(unit.message().get_text(), "Supprimer %s");
+        assert_eq!(unit.state(), State::NeedsWork);
+        assert!(unit.flags().contains(&Flag::Format(FormatLanguage::C)));
+        assert_eq!(unit.prev_message().get_id(), "Delete %s");
+    }
+
+    #[test]
+    fn test_func_merge_flags() {
+        let definitions = parse("#, c-format, no-wrap\nmsgid \"Save %(name)s\"\nmsgstr \"Enregistrer %(name)s\"\n");
+        let template = parse("#, python-format\nmsgid \"Save %(name)s\"\nmsgstr \"\"\n");
+        let catalog = Merger::new().merge(&definitions, &template);
+        let unit = catalog.get(None, "Save %(name)s").unwrap();
+
+        assert_eq!(
+            unit.flags(),
+            &[Flag::Format(FormatLanguage::Python), Flag::NoWrap].into_iter().collect()
+        );
+        assert_eq!(unit.state(), State::Final);
+    }
+
+    #[test]
+    fn test_func_merge_untranslated() {
+        let catalog = make_merge(true);
+        let unit = catalog.get(None, "A brand new message").unwrap();
+
+        assert!(unit.message().is_blank());
+        assert_eq!(unit.state(), State::Empty);
+        assert_eq!(
+            unit.message()
+                .plural()
+                .and_then(|p| p.get_forms())
+                .map(|f| f.get_count()),
+            Some(2)
+        );
+
+        let catalog = make_merge(false);
+        let unit = catalog.get(None, "Open the files").unwrap();
+
+        assert!(unit.message().is_blank());
+        assert_eq!(unit.state(), State::Empty);
+        assert!(unit.prev_message().is_empty());
+    }
+
+    #[test]
+    fn test_func_merge_obsolete() {
+        let catalog = make_merge(true);
+        let ids: Vec<(&str, bool)> = catalog
+            .iter()
+            .map(|u| (u.message().get_id(), u.is_obsolete()))
+            .collect();
+
+        assert_eq!(
+            ids,
+            vec![
+                ("Open the file", false),
+                ("Open the files", false),
+                ("Delete %s now", false),
+                ("One file", false),
+                ("A brand new message", false),
+                ("Removed message", true),
+                ("Very old", true),
+            ]
+        );
+
+        let unit = catalog.get(None, "Removed message").unwrap();
+
+        assert_eq!(unit.message().get_text(), "Message supprimé");
+        assert!(unit.locations().is_empty());
+    }
+
+    #[test]
+    fn test_func_merge_header() {
+        let catalog = make_merge(true);
+
+        assert_eq!(
+            catalog.header_properties().get("POT-Creation-Date").map(String::as_str),
+            Some("2024-06-01 10:00+0000")
+        );
+        assert_eq!(
+            catalog.header_properties().get("Language").map(String::as_str),
+            Some("fr")
+        );
+        assert_eq!(catalog.header_notes().len(), 1);
+    }
+
+    #[test]
+    fn test_func_merge_revived() {
+        let template = parse("msgid \"Very old\"\nmsgstr \"\"\n");
+        let catalog = Merger::new().merge(&parse(DEFINITIONS), &template);
+        let unit = catalog.get(None, "Very old").unwrap();
+
+        assert!(!unit.is_obsolete());
+        assert_eq!(unit.message().get_text(), "Très vieux");
+        assert_eq!(unit.state(), State::NeedsWork);
+    }
+
+    #[test]
+    fn test_func_merge_write() {
+        let mut writer = PoWriter::new(vec![]);
+
+        writer.write_catalogue(make_merge(true).into_iter()).unwrap();
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert!(
+            output.contains(
+                "#: src/main.rs:20\n#, fuzzy\n#| msgid \"Open the file\"\nmsgid \"Open the files\"\n\
+                 msgstr \"Ouvrir le fichier\"\n"
+            ),
+            "Unexpected output:\n{}",
+            output
+        );
+        assert!(
+            output.contains("#~ msgid \"Removed message\"\n#~ msgstr \"Message supprimé\"\n"),
+            "Unexpected output:\n{}",
+            output
+        );
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,518 @@
+use super::{CONTEXT_SEPARATOR, MAGIC, PLURAL_SEPARATOR};
+use crate::{
+    charset::{encode, find_encoding, parse_charset},
+    error::{Error, ErrorKind},
+    header::{format_properties, ordered_properties, parse_properties},
+    unit::Unit,
+    CatalogueReader, Message, State,
+};
+
+use encoding_rs::{Encoding, UTF_8};
+use std::{borrow::Borrow, collections::BTreeMap, io::Write};
+
+/// Size of the MO header, before the tables of strings.
+const HEADER_SIZE: usize = 28;
+
+/// Hash function used by gettext to fill and search the hash table.
+fn hash_string(text: &[u8]) -> u64 {
+    let mut hval: u64 = 0;
+
+    for byte in text {
+        hval = (hval << 4) + u64::from(*byte);
+
+        let g = hval & (0xf << 28);
+
+        if g != 0 {
+            hval ^= g >> 24;
+            hval ^= g;
+        }
+    }
+
+    hval
+}
+
+fn is_prime(n: usize) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| n / d * d != n)
+}
+
+/// Size of the hash table as gettext computes it, the first odd prime above 4/3 of the number of entries.
+fn hash_table_size(count: usize) -> usize {
+    let mut size = (count * 4 / 3).max(3) | 1;
+
+    while !is_prime(size) {
+        size += 2;
+    }
+
+    size
+}
+
+/// Compiler of units of translation into MO streams, like the `msgfmt` tool.
+///
+/// As with `msgfmt`, fuzzy units, obsolete units and untranslated units are left out by default. Strings are encoded
+/// with the charset declared in the `Content-Type` property of the header, or in UTF-8.
+#[derive(Clone, Debug)]
+pub struct MoCompiler {
+    include_fuzzy: bool,
+    include_obsolete: bool,
+    hash_table: bool,
+    big_endian: bool,
+}
+
+impl MoCompiler {
+    pub fn new() -> MoCompiler {
+        MoCompiler {
+            include_fuzzy: false,
+            include_obsolete: false,
+            hash_table: true,
+            big_endian: false,
+        }
+    }
+
+    /// Include fuzzy units in the output, like the option `--use-fuzzy` of `msgfmt`.
+    pub fn set_include_fuzzy(&mut self, include: bool) {
+        self.include_fuzzy = include;
+    }
+
+    /// Include obsolete units in the output.
+    pub fn set_include_obsolete(&mut self, include: bool) {
+        self.include_obsolete = include;
+    }
+
+    /// Enable or disable the hash table used to speed up lookups, it is written by default.
+    pub fn set_hash_table(&mut self, hash_table: bool) {
+        self.hash_table = hash_table;
+    }
+
+    /// Write the MO stream in big endian byte order instead of little endian.
+    pub fn set_big_endian(&mut self, big_endian: bool) {
+        self.big_endian = big_endian;
+    }
+
+    /// Compile units of translation, the header entry is the unit with an empty message and no context.
+    pub fn compile_units<I, W>(&self, units: I, writer: W) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Unit>,
+        W: Write, {
+        let mut entries = BTreeMap::new();
+
+        for unit in units {
+            self.add_unit(&mut entries, unit.borrow())?;
+        }
+
+        self.write_entries(&entries, writer)
+    }
+
+    /// Compile a whole catalogue, the header then all units.
+    pub fn compile<C: CatalogueReader, W: Write>(&self, catalogue: C, writer: W) -> Result<(), Error> {
+        let mut entries = BTreeMap::new();
+        let header = format_properties(ordered_properties(catalogue.header_properties()));
+
+        if !header.is_empty() {
+            entries.insert(String::new(), header);
+        }
+
+        for unit in catalogue {
+            self.add_unit(&mut entries, &unit?)?;
+        }
+
+        self.write_entries(&entries, writer)
+    }
+
+    fn add_unit(&self, entries: &mut BTreeMap<String, String>, unit: &Unit) -> Result<(), Error> {
+        let is_header = unit.context.is_none() && unit.message.get_id().is_empty();
+
+        if unit.message.is_blank()
+            || (unit.obsolete && !self.include_obsolete)
+            || (unit.state == State::NeedsWork && !self.include_fuzzy && !is_header)
+        {
+            return Ok(());
+        }
+
+        let mut key = match &unit.context {
+            Some(context) => format!("{}{}", context, CONTEXT_SEPARATOR),
+            None => String::new(),
+        };
+
+        let value = match &unit.message {
+            Message::Simple { id, text } => {
+                key.push_str(id);
+                text.clone().unwrap_or_default()
+            }
+            Message::Plural(plural) => {
+                key.push_str(plural.singular());
+                key.push(PLURAL_SEPARATOR);
+                key.push_str(plural.plural());
+                plural.values().join(&PLURAL_SEPARATOR.to_string())
+            }
+        };
+
+        if entries.contains_key(&key) {
+            return Err(Error::new(
+                ErrorKind::DuplicateEntry,
+                format!("Duplicate message definition: {}", unit.message.get_id()),
+            ));
+        }
+
+        entries.insert(key, value);
+
+        Ok(())
+    }
+
+    /// Encode the entries with the charset of the header, they are sorted by their encoded keys.
+    fn encode_entries(entries: &BTreeMap<String, String>) -> Result<BTreeMap<Vec<u8>, Vec<u8>>, Error> {
+        let mut encoding: &'static Encoding = UTF_8;
+
+        if let Some(header) = entries.get("") {
+            if let Some(charset) = parse_properties(header)
+                .get("Content-Type")
+                .and_then(|c| parse_charset(c))
+            {
+                encoding = find_encoding(charset).map_err(|msg| Error::new(ErrorKind::Encoding, msg))?;
+            }
+        }
+
+        entries
+            .iter()
+            .map(|(k, v)| Ok((encode(encoding, k)?.into_owned(), encode(encoding, v)?.into_owned())))
+            .collect::<Result<_, _>>()
+            .map_err(|err| Error::io(0, err))
+    }
+
+    fn write_entries<W: Write>(&self, entries: &BTreeMap<String, String>, mut writer: W) -> Result<(), Error> {
+        let entries = Self::encode_entries(entries)?;
+        let count = entries.len();
+        let hash_size = if self.hash_table { hash_table_size(count) } else { 0 };
+        let mut hash_table = vec![0usize; hash_size];
+        let mut offset = HEADER_SIZE + count * 16 + hash_size * 4;
+        let mut tables = Vec::with_capacity(count * 4);
+
+        for s in entries.keys().chain(entries.values()) {
+            tables.push(s.len());
+            tables.push(offset);
+            offset += s.len() + 1;
+        }
+
+        for (index, key) in entries.keys().enumerate().filter(|_| hash_size > 0) {
+            let id = key
+                .split(|b| char::from(*b) == PLURAL_SEPARATOR)
+                .next()
+                .unwrap_or_default();
+            // Like msgfmt and libintl, the hash is truncated to 32 bits before use
+            let hash = hash_string(id) as u32;
+            let mut pos = (hash % hash_size as u32) as usize;
+            let incr = 1 + (hash % (hash_size as u32 - 2)) as usize;
+
+            while hash_table[pos] != 0 {
+                pos = if pos >= hash_size - incr {
+                    pos - (hash_size - incr)
+                } else {
+                    pos + incr
+                };
+            }
+
+            hash_table[pos] = index + 1;
+        }
+
+        let header = [
+            MAGIC as usize,
+            0,
+            count,
+            HEADER_SIZE,
+            HEADER_SIZE + count * 8,
+            hash_size,
+            HEADER_SIZE + count * 16,
+        ];
+
+        let mut data = Vec::with_capacity(offset);
+
+        for v in header.into_iter().chain(tables).chain(hash_table) {
+            if self.big_endian {
+                data.extend_from_slice(&(v as u32).to_be_bytes());
+            } else {
+                data.extend_from_slice(&(v as u32).to_le_bytes());
+            }
+        }
+
+        for s in entries.keys().chain(entries.values()) {
+            data.extend_from_slice(s);
+            data.push(0);
+        }
+
+        writer
+            .write_all(&data)
+            .and_then(|_| writer.flush())
+            .map_err(|err| Error::io(0, err))
+    }
+}
+
+impl Default for MoCompiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
+mod tests {
+    use super::*;
+    use crate::{plural::Plural, MoReader, PoParser};
+    use std::collections::HashMap;
+
+    fn compile<F: FnOnce(&MoCompiler, &mut Vec<u8>) -> Result<(), Error>>(f: F) -> Vec<u8> {
+        let mut output = vec![];
+
+        f(&MoCompiler::new(), &mut output).unwrap();
+        output
+    }
+
+    fn read_u32(data: &[u8], offset: usize) -> usize {
+        u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize
+    }
+
+    fn make_unit(context: Option<&str>, id: &str, text: &str) -> Unit {
+        let mut unit = Unit::default();
+
+        unit.context = context.map(String::from);
+        unit.message = Message::Simple {
+            id: id.to_string(),
+            text: Some(text.to_string()),
+        };
+
+        unit.state = State::Final;
+        unit
+    }
+
+    #[test]
+    fn test_func_hash_string() {
+        assert_eq!(hash_string(b""), 0);
+        assert_eq!(hash_string(b"a"), 0x61);
+        assert_eq!(hash_string(b"ab"), 0x61 * 16 + 0x62);
+        assert_eq!(hash_string(b"Hello, world"), 0x0a92_5c34);
+    }
+
+    #[test]
+    fn test_func_hash_table_size() {
+        assert_eq!(hash_table_size(0), 3);
+        assert_eq!(hash_table_size(3), 5);
+        assert_eq!(hash_table_size(10), 13);
+        assert_eq!(hash_table_size(100), 137);
+    }
+
+    #[test]
+    fn test_func_compile_units() {
+        let mut fuzzy = make_unit(None, "Fuzzy", "Flou");
+        let mut obsolete = make_unit(None, "Obsolete", "Obsolète");
+
+        fuzzy.state = State::NeedsWork;
+        obsolete.obsolete = true;
+
+        let units = vec![
+            make_unit(None, "Hello", "Salut"),
+            make_unit(Some("Menu"), "File", "Fichier"),
+            make_unit(None, "Untranslated", ""),
+            fuzzy,
+            obsolete,
+        ];
+
+        let output = compile(|c, w| c.compile_units(&units, w));
+
+        assert_eq!(read_u32(&output, 0), MAGIC as usize);
+        assert_eq!(read_u32(&output, 8), 2);
+        assert_eq!(read_u32(&output, 20), 3);
+
+        let ids: Vec<_> = MoReader::new(&output[..])
+            .unwrap()
+            .map(|u| u.map(|u| (u.context().map(String::from), u.message().get_id().to_string())))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            ids,
+            vec![
+                (None, String::from("Hello")),
+                (Some(String::from("Menu")), String::from("File"))
+            ]
+        );
+
+        let mut compiler = MoCompiler::new();
+        let mut output = vec![];
+
+        compiler.set_include_fuzzy(true);
+        compiler.set_include_obsolete(true);
+        compiler.set_hash_table(false);
+        compiler.compile_units(&units, &mut output).unwrap();
+
+        assert_eq!(read_u32(&output, 8), 4);
+        assert_eq!(read_u32(&output, 20), 0);
+        assert_eq!(read_u32(&output, 24), HEADER_SIZE + 4 * 16);
+    }
+
+    #[test]
+    fn test_func_compile_units_with_error() {
+        let units = vec![make_unit(None, "Hello", "Salut"), make_unit(None, "Hello", "Bonjour")];
+
+        match MoCompiler::new().compile_units(&units, vec![]) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Duplicate entry: Duplicate message definition: Hello"
+            ),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_func_compile_plural() {
+        let mut unit = Unit::default();
+
+        unit.state = State::Final;
+        unit.message = Message::Plural(Plural::new(
+            String::from("One file"),
+            String::from("Several files"),
+            vec![String::from("Un fichier"), String::from("Des fichiers")],
+            None,
+        ));
+
+        let output = compile(|c, w| c.compile_units([unit], w));
+        let strings = &output[(HEADER_SIZE + 16 + 3 * 4)..];
+
+        assert_eq!(strings, b"One file\0Several files\0Un fichier\0Des fichiers\0");
+    }
+
+    #[test]
+    fn test_func_compile_hash_table() {
+        let units = vec![
+            make_unit(None, "a", "A"),
+            make_unit(None, "b", "B"),
+            make_unit(None, "d", "D"),
+        ];
+        let output = compile(|c, w| c.compile_units(&units, w));
+        let hash_offset = read_u32(&output, 24);
+        let table: Vec<_> = (0..5).map(|i| read_u32(&output, hash_offset + i * 4)).collect();
+
+        // 'a' = 97 -> 2, 'b' = 98 -> 3, 'd' = 100 -> 0
+        assert_eq!(table, vec![3, 0, 1, 2, 0]);
+    }
+
+    /// Search a message in the hash table of a MO stream like libintl, giving the index of the message.
+    fn lookup(mo: &[u8], id: &str) -> Option<usize> {
+        let (size, offset, originals) = (read_u32(mo, 20), read_u32(mo, 24), read_u32(mo, 12));
+        let hash = hash_string(id.as_bytes()) as u32 as usize;
+        let incr = 1 + hash % (size - 2);
+        let mut pos = hash % size;
+
+        loop {
+            let index = read_u32(mo, offset + pos * 4).checked_sub(1)?;
+            let (len, start) = (read_u32(mo, originals + index * 8), read_u32(mo, originals + index * 8 + 4));
+
+            if &mo[start..start + len] == id.as_bytes() {
+                return Some(index);
+            }
+
+            pos = if pos >= size - incr { pos - (size - incr) } else { pos + incr };
+        }
+    }
+
+    #[test]
+    fn test_func_compile_hash_table_long_msgid() {
+        // The hash of this message goes beyond 32 bits before it is truncated
+        let id = "This long message checks the hash table of the MO files written by the compiler: nvknmdiiz";
+        let msgfmt = include_bytes!("../../tests/mo/long_msgid.mo");
+        let source = include_str!("../../tests/mo/long_msgid.po");
+        let output = compile(|c, w| c.compile(PoParser::new().parse(source.as_bytes())?, w));
+
+        assert_eq!(hash_string(id.as_bytes()), 0x1_0000_000a);
+        assert_eq!(lookup(msgfmt, id), Some(2));
+        assert_eq!(lookup(&output, id), Some(2));
+        assert_eq!(lookup(&output, "Hello"), Some(1));
+        assert_eq!(lookup(&output, "World"), Some(3));
+    }
+
+    #[test]
+    fn test_func_compile_big_endian() {
+        let mut compiler = MoCompiler::new();
+        let mut output = vec![];
+
+        compiler.set_big_endian(true);
+        compiler
+            .compile_units([make_unit(None, "Hello", "Salut")], &mut output)
+            .unwrap();
+
+        assert_eq!(&output[..4], &MAGIC.to_be_bytes());
+
+        match MoReader::new(&output[..]).unwrap().next() {
+            Some(Ok(unit)) => assert_eq!(unit.message().get_text(), "Salut"),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_func_compile() {
+        let source = "\
+            msgid \"\"\n\
+            msgstr \"\"\n\
+            \"Plural-Forms: nplurals=2; plural=(n > 1);\\n\"\n\
+            \"Language: fr\\n\"\n\
+            \n\
+            msgid \"Hello\"\n\
+            msgstr \"Salut\"\n\
+            \n\
+            msgid \"One file\"\n\
+            msgid_plural \"Several files\"\n\
+            msgstr[0] \"Un fichier\"\n\
+            msgstr[1] \"Des fichiers\"\n\
+        ";
+
+        let parser = PoParser::new();
+        let output = compile(|c, w| c.compile(parser.parse(source.as_bytes())?, w));
+        let mut reader = MoReader::new(&output[..]).unwrap();
+
+        assert_eq!(reader.target_language().as_ref(), "fr");
+        assert_eq!(
+            reader.header_properties(),
+            &[("Language", "fr"), ("Plural-Forms", "nplurals=2; plural=(n > 1);")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        );
+
+        match reader.next() {
+            Some(Ok(unit)) => assert_eq!(unit.message().get_text(), "Salut"),
+            r => panic!("Unexpected result for the first unit: {:?}", r),
+        }
+
+        match reader.next() {
+            Some(Ok(unit)) => assert_eq!(unit.message().get_plural_text(1), Some("Un fichier")),
+            r => panic!("Unexpected result for the second unit: {:?}", r),
+        }
+
+        assert!(reader.next().is_none(), "There should be no more unit");
+    }
+
+    #[test]
+    fn test_func_compile_with_charset() {
+        let source = b"\
+            msgid \"\"\n\
+            msgstr \"\"\n\
+            \"Content-Type: text/plain; charset=KOI8-R\\n\"\n\
+            \n\
+            msgid \"Yes\"\n\
+            msgstr \"\xe4\xc1\"\n\
+        ";
+
+        let parser = PoParser::new();
+        let output = compile(|c, w| c.compile(parser.parse(&source[..])?, w));
+
+        assert_eq!(&output[(output.len() - 3)..], b"\xe4\xc1\0");
+
+        let units = MoReader::new(&output[..])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].message().get_text(), "Да");
+    }
+}
+// no-coverage:stop
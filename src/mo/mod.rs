@@ -0,0 +1,22 @@
+//! Handling of [GNU Machine Objects][MO]
+//!
+//! This format is the binary form of the PO files, produced by the `msgfmt` tool of the [gettext]
+//! suite and loaded by the applications at run time. It only keeps the context, the original
+//! message and its translations: notes, locations, flags and obsolete units are lost.
+//!
+//! [MO]: https://www.gnu.org/software/gettext/manual/html_node/MO-Files.html
+//! [gettext]: https://www.gnu.org/software/gettext/
+
+mod compiler;
+mod reader;
+
+pub use self::{compiler::MoCompiler, reader::MoReader};
+
+/// Magic number at the beginning of MO files, read with the byte order of the file.
+const MAGIC: u32 = 0x9504_12de;
+
+/// Separator between the context and the original message.
+const CONTEXT_SEPARATOR: char = '\x04';
+
+/// Separator between the plural variants of the messages.
+const PLURAL_SEPARATOR: char = '\0';
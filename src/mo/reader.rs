@@ -0,0 +1,442 @@
+use super::{CONTEXT_SEPARATOR, MAGIC, PLURAL_SEPARATOR};
+use crate::{
+    charset::{decode, find_encoding, parse_charset},
+    comment::Comment,
+    error::{Error, ErrorKind},
+    header::{parse_language, parse_properties},
+    note::Note,
+    plural::{Plural, PluralForms},
+    unit::Unit,
+    CatalogueReader, Message, PoParser, State,
+};
+
+use encoding_rs::{Encoding, UTF_8};
+use locale_config::LanguageRange;
+use std::{collections::HashMap, io::Read, rc::Rc};
+
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::Other, format!("Invalid MO file, {}", msg))
+}
+
+/// Object for reading MO streams
+///
+/// The whole stream is loaded when the reader is created, then an iterator is implemented for reading each unit of
+/// translation. Units of MO files have no note, location, flag nor comment, and they are never obsolete.
+///
+/// Strings are decoded with the charset declared in the `Content-Type` property of the header, or as UTF-8.
+pub struct MoReader {
+    data: Vec<u8>,
+    big_endian: bool,
+    encoding: &'static Encoding,
+    count: usize,
+    originals: usize,
+    translations: usize,
+    index: usize,
+    header_index: Option<usize>,
+    header_notes: Vec<Note>,
+    header_comments: Vec<Comment>,
+    header_properties: HashMap<String, String>,
+    target_language: LanguageRange<'static>,
+    plural_forms: Option<Rc<PluralForms>>,
+}
+
+impl MoReader {
+    /// Read a MO stream, the byte order is detected from the magic number.
+    pub fn new<R: Read>(mut reader: R) -> Result<MoReader, Error> {
+        let mut data = vec![];
+
+        reader.read_to_end(&mut data).map_err(|err| Error::io(0, err))?;
+
+        let big_endian = match data.get(..4) {
+            Some(magic) if u32::from_le_bytes([magic[0], magic[1], magic[2], magic[3]]) == MAGIC => false,
+            Some(magic) if u32::from_be_bytes([magic[0], magic[1], magic[2], magic[3]]) == MAGIC => true,
+            _ => {
+                return Err(invalid("bad magic number"));
+            }
+        };
+
+        let mut res = MoReader {
+            data,
+            big_endian,
+            encoding: UTF_8,
+            count: 0,
+            originals: 0,
+            translations: 0,
+            index: 0,
+            header_index: None,
+            header_notes: vec![],
+            header_comments: vec![],
+            header_properties: HashMap::new(),
+            target_language: LanguageRange::invariant(),
+            plural_forms: None,
+        };
+
+        if res.read_u32(4)? >> 16 > 1 {
+            return Err(invalid("unsupported revision"));
+        }
+
+        res.count = res.read_u32(8)?;
+        res.originals = res.read_u32(12)?;
+        res.translations = res.read_u32(16)?;
+        res.parse_mo_header()?;
+
+        Ok(res)
+    }
+
+    fn read_u32(&self, offset: usize) -> Result<usize, Error> {
+        let bytes = match self.data.get(offset..(offset + 4)) {
+            Some(bytes) => [bytes[0], bytes[1], bytes[2], bytes[3]],
+            None => {
+                return Err(invalid("unexpected end of data"));
+            }
+        };
+
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        } as usize)
+    }
+
+    fn read_entry(&self, table: usize, index: usize) -> Result<&[u8], Error> {
+        let length = self.read_u32(table + index * 8)?;
+        let offset = self.read_u32(table + index * 8 + 4)?;
+
+        self.data
+            .get(offset..(offset + length))
+            .ok_or_else(|| invalid("string out of data"))
+    }
+
+    fn parse_mo_header(&mut self) -> Result<(), Error> {
+        for index in 0..self.count {
+            if self.read_entry(self.originals, index)?.is_empty() {
+                let bytes = self.read_entry(self.translations, index)?;
+                let properties = parse_properties(&String::from_utf8_lossy(bytes));
+
+                if let Some(charset) = properties.get("Content-Type").and_then(|c| parse_charset(c)) {
+                    self.encoding = find_encoding(charset).map_err(|msg| Error::new(ErrorKind::Encoding, msg))?;
+                }
+
+                self.header_index = Some(index);
+                self.header_properties = parse_properties(&self.decode(self.read_entry(self.translations, index)?)?);
+                break;
+            }
+        }
+
+        if let Some(lang) = self.header_properties.get("Language") {
+            self.target_language = parse_language(lang);
+        }
+
+        match self
+            .header_properties
+            .get("Plural-Forms")
+            .filter(|forms| !forms.is_empty())
+        {
+            Some(forms) => {
+                self.plural_forms
+                    .replace(Rc::new(PluralForms::parse(forms, &PoParser::new())?));
+            }
+            None => self.plural_forms = PluralForms::for_language(&self.target_language).map(Rc::new),
+        }
+
+        Ok(())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, Error> {
+        decode(self.encoding, bytes)
+            .map(String::from)
+            .map_err(|err| Error::io(0, err))
+    }
+
+    fn read_unit(&self, index: usize) -> Result<Unit, Error> {
+        let original = self.decode(self.read_entry(self.originals, index)?)?;
+        let translation = self.decode(self.read_entry(self.translations, index)?)?;
+        let mut unit = Unit::default();
+
+        let original = match original.split_once(CONTEXT_SEPARATOR) {
+            Some((context, original)) => {
+                unit.context = Some(context.to_string());
+                original.to_string()
+            }
+            None => original,
+        };
+
+        unit.message = match original.split_once(PLURAL_SEPARATOR) {
+            None => Message::Simple {
+                id: original,
+                text: Some(translation),
+            },
+            Some((singular, plural)) => Message::Plural(Plural::new(
+                singular.to_string(),
+                plural.to_string(),
+                translation.split(PLURAL_SEPARATOR).map(String::from).collect(),
+                self.plural_forms.as_ref().map(Rc::clone),
+            )),
+        };
+
+        if !unit.message.is_blank() {
+            unit.state = State::Final;
+        }
+
+        Ok(unit)
+    }
+}
+
+impl Iterator for MoReader {
+    type Item = Result<Unit, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.count {
+            let index = self.index;
+
+            self.index += 1;
+            if self.header_index != Some(index) {
+                let res = self.read_unit(index);
+
+                if res.is_err() {
+                    self.index = self.count;
+                }
+
+                return Some(res);
+            }
+        }
+
+        None
+    }
+}
+
+impl CatalogueReader for MoReader {
+    fn target_language(&self) -> &LanguageRange<'static> {
+        &self.target_language
+    }
+
+    fn header_notes(&self) -> &Vec<Note> {
+        &self.header_notes
+    }
+
+    fn header_comments(&self) -> &Vec<Comment> {
+        &self.header_comments
+    }
+
+    fn header_properties(&self) -> &HashMap<String, String> {
+        &self.header_properties
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static HEADER: &str = "Language: fr\nPlural-Forms: nplurals=2; plural=(n > 1);\n";
+
+    fn make_mo(entries: &[(&str, &str)], big_endian: bool) -> Vec<u8> {
+        let to_bytes = |v: usize| {
+            if big_endian {
+                (v as u32).to_be_bytes()
+            } else {
+                (v as u32).to_le_bytes()
+            }
+        };
+
+        let count = entries.len();
+        let mut tables = vec![];
+        let mut strings = vec![];
+        let mut offset = 28 + count * 16;
+
+        for i in 0..2 {
+            for entry in entries {
+                let s = if i == 0 { entry.0 } else { entry.1 };
+
+                tables.extend_from_slice(&to_bytes(s.len()));
+                tables.extend_from_slice(&to_bytes(offset));
+                strings.extend_from_slice(s.as_bytes());
+                strings.push(0);
+                offset += s.len() + 1;
+            }
+        }
+
+        let mut res = vec![];
+
+        for v in [MAGIC as usize, 0, count, 28, 28 + count * 8, 0, 28 + count * 16] {
+            res.extend_from_slice(&to_bytes(v));
+        }
+
+        res.extend(tables);
+        res.extend(strings);
+        res
+    }
+
+    fn make_entries() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("", HEADER),
+            ("Hello", "Salut"),
+            ("Menu\x04File", "Fichier"),
+            ("One file\0Several files", "Un fichier\0Des fichiers"),
+            ("Untranslated\0Untranslated ones", "\0"),
+        ]
+    }
+
+    #[test]
+    fn test_func_new_with_error() {
+        match MoReader::new(&b"\x01\x02\x03\x04\x05"[..]) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error: Invalid MO file, bad magic number"
+            ),
+            Ok(_) => panic!("Unexpected success with a bad magic number"),
+        }
+
+        match MoReader::new(&make_mo(&[], false)[..12]) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error: Invalid MO file, unexpected end of data"
+            ),
+            Ok(_) => panic!("Unexpected success with truncated data"),
+        }
+
+        let mut data = make_mo(&[], true);
+
+        data[4] = 2;
+        match MoReader::new(&data[..]) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error: Invalid MO file, unsupported revision"
+            ),
+            Ok(_) => panic!("Unexpected success with an unsupported revision"),
+        }
+
+        let data = make_mo(&[("", "Language: fr\n")], false);
+
+        match MoReader::new(&data[..(data.len() - 4)]) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error: Invalid MO file, string out of data"
+            ),
+            Ok(_) => panic!("Unexpected success with a string out of data"),
+        }
+
+        match MoReader::new(&make_mo(&[("", "Plural-Forms: nplurals=x; plural=0;\n")], false)[..]) {
+            Err(err) if matches!(err.kind(), ErrorKind::InvalidPluralFormula { .. }) => (),
+            r => panic!("Unexpected result with bad plural forms: {:?}", r.err()),
+        }
+    }
+
+    #[test]
+    fn test_func_new_normal() {
+        for big_endian in [false, true] {
+            match MoReader::new(&make_mo(&make_entries(), big_endian)[..]) {
+                Ok(reader) => {
+                    assert_eq!(reader.big_endian, big_endian);
+                    assert_eq!(reader.header_index, Some(0));
+                    assert_eq!(reader.target_language().as_ref(), "fr");
+                    assert!(reader.header_notes().is_empty(), "MO files have no note");
+                    assert!(reader.header_comments().is_empty(), "MO files have no comment");
+                    assert_eq!(
+                        reader.header_properties(),
+                        &[("Language", "fr"), ("Plural-Forms", "nplurals=2; plural=(n > 1);")]
+                            .into_iter()
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
+                            .collect::<HashMap<_, _>>()
+                    );
+
+                    match reader.plural_forms {
+                        Some(forms) => assert_eq!(forms.get_count(), 2),
+                        None => panic!("Plural forms expected"),
+                    }
+                }
+                Err(err) => panic!("Unexpected error: {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_func_new_without_header() {
+        match MoReader::new(&make_mo(&[("Hello", "Salut")], false)[..]) {
+            Ok(reader) => {
+                assert_eq!(reader.header_index, None);
+                assert!(reader.header_properties().is_empty(), "There should be no property");
+                assert_eq!(reader.target_language().as_ref(), "");
+                assert!(reader.plural_forms.is_none(), "There should be no plural forms");
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_func_new_without_plural_forms() {
+        match MoReader::new(&make_mo(&[("", "Language: ru\n")], false)[..]) {
+            Ok(reader) => {
+                assert_eq!(reader.plural_forms.as_ref().map(|f| f.get_count()), Some(3));
+                assert_eq!(reader.plural_forms.as_ref().and_then(|f| f.get_value(22)), Some(1));
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_trait_iterator_normal() {
+        for big_endian in [false, true] {
+            let units = match MoReader::new(&make_mo(&make_entries(), big_endian)[..]) {
+                Ok(reader) => reader.collect::<Result<Vec<_>, _>>().unwrap(),
+                Err(err) => panic!("Unexpected error: {:?}", err),
+            };
+
+            assert_eq!(units.len(), 4);
+
+            assert_eq!(units[0].context(), None);
+            assert_eq!(units[0].message().get_id(), "Hello");
+            assert_eq!(units[0].message().get_text(), "Salut");
+            assert_eq!(units[0].state(), State::Final);
+
+            assert_eq!(units[1].context(), Some("Menu"));
+            assert_eq!(units[1].message().get_id(), "File");
+            assert_eq!(units[1].message().get_text(), "Fichier");
+
+            match units[2].message().plural() {
+                Some(plural) => {
+                    assert_eq!(plural.singular(), "One file");
+                    assert_eq!(plural.plural(), "Several files");
+                    assert_eq!(
+                        plural.values(),
+                        &vec![String::from("Un fichier"), String::from("Des fichiers")]
+                    );
+                    assert_eq!(plural.get(5), Some("Des fichiers"));
+                }
+                None => panic!("Plural message expected, got {:?}", units[2].message()),
+            }
+
+            assert!(units[3].message().is_plural(), "Plural message expected");
+            assert!(units[3].message().is_blank(), "Blank message expected");
+            assert_eq!(units[3].state(), State::Empty);
+            assert!(
+                units.iter().all(|u| !u.is_obsolete()),
+                "MO units should not be obsolete"
+            );
+        }
+    }
+
+    #[test]
+    fn test_trait_iterator_with_error() {
+        let mut reader = MoReader::new(&make_mo(&[("Hello", "Salut"), ("Bad", "\u{80}")], false)[..]).unwrap();
+        let len = reader.data.len();
+
+        reader.data[len - 3] = 0xff;
+
+        match reader.next() {
+            Some(Ok(unit)) => assert_eq!(unit.message().get_id(), "Hello"),
+            r => panic!("Unexpected result after the first call of `next()`: {:?}", r),
+        }
+
+        match reader.next() {
+            Some(Err(err)) => assert_eq!(err.kind(), ErrorKind::Encoding),
+            r => panic!("Unexpected result after the second call of `next()`: {:?}", r),
+        }
+
+        match reader.next() {
+            None => (),
+            Some(r) => panic!("Unexpected result after the third call of `next()`: {:?}", r),
+        }
+    }
+}
+// no-coverage:stop
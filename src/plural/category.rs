@@ -0,0 +1,512 @@
+use super::PluralForms;
+use crate::header::language_chain;
+use locale_config::LanguageRange;
+use std::fmt::{Display, Formatter, Result};
+use PluralCategory::{Few, Many, One, Other, Two, Zero};
+
+/// Plural category of CLDR, as used by ICU and the `Intl.PluralRules` of JavaScript.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+/// Names of the categories in CLDR.
+const CATEGORY_NAMES: [(PluralCategory, &str); 6] = [
+    (PluralCategory::Zero, "zero"),
+    (PluralCategory::One, "one"),
+    (PluralCategory::Two, "two"),
+    (PluralCategory::Few, "few"),
+    (PluralCategory::Many, "many"),
+    (PluralCategory::Other, "other"),
+];
+
+impl PluralCategory {
+    /// Get the category from its name in CLDR, like `one` or `other`.
+    pub fn from_name(name: &str) -> Option<PluralCategory> {
+        CATEGORY_NAMES.iter().find(|(_, n)| *n == name).map(|(cat, _)| *cat)
+    }
+
+    /// Get the name of the category in CLDR.
+    pub fn name(&self) -> &'static str {
+        CATEGORY_NAMES
+            .iter()
+            .find(|(cat, _)| cat == self)
+            .map(|(_, name)| *name)
+            .unwrap_or_default()
+    }
+}
+
+impl Display for PluralCategory {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Integer samples of the categories of a language, in the syntax of CLDR, like `0, 2~16, 100`.
+type Samples = &'static [(PluralCategory, &'static str)];
+
+const ONLY_OTHER: Samples = &[(Other, "0~15, 100, 1000, 10000, 100000, 1000000")];
+const ONE_IS_ONE: Samples = &[(One, "1"), (Other, "0, 2~16, 100, 1000, 10000, 100000, 1000000")];
+const ONE_IS_ZERO_OR_ONE: Samples = &[(One, "0, 1"), (Other, "2~17, 100, 1000, 10000, 100000, 1000000")];
+const ONE_IS_ONE_MANY_MILLIONS: Samples = &[
+    (One, "1"),
+    (Many, "1000000"),
+    (Other, "0, 2~16, 100, 1000, 10000, 100000"),
+];
+const ONE_IS_ZERO_OR_ONE_MANY_MILLIONS: Samples = &[
+    (One, "0, 1"),
+    (Many, "1000000"),
+    (Other, "2~17, 100, 1000, 10000, 100000"),
+];
+const ONE_ENDS_WITH_ONE: Samples = &[
+    (One, "1, 21, 31, 41, 51, 61, 71, 81, 101, 1001"),
+    (Other, "0, 2~16, 100, 1000, 10000, 100000, 1000000"),
+];
+const EAST_SLAVIC: Samples = &[
+    (One, "1, 21, 31, 41, 51, 61, 71, 81, 101, 1001"),
+    (Few, "2~4, 22~24, 32~34, 42~44, 52~54, 62, 102, 1002"),
+    (Many, "0, 5~19, 100, 1000, 10000, 100000, 1000000"),
+];
+const SOUTH_SLAVIC: Samples = &[
+    (One, "1, 21, 31, 41, 51, 61, 71, 81, 101, 1001"),
+    (Few, "2~4, 22~24, 32~34, 42~44, 52~54, 62, 102, 1002"),
+    (Other, "0, 5~19, 100, 1000, 10000, 100000, 1000000"),
+];
+const WEST_SLAVIC: Samples = &[
+    (One, "1"),
+    (Few, "2~4"),
+    (Other, "0, 5~19, 100, 1000, 10000, 100000, 1000000"),
+];
+
+/// CLDR integer samples of the categories by language, sorted by language.
+const SAMPLES: [(&str, Samples); 97] = [
+    ("af", ONE_IS_ONE),
+    ("ak", ONE_IS_ZERO_OR_ONE),
+    ("am", ONE_IS_ZERO_OR_ONE),
+    (
+        "ar",
+        &[
+            (Zero, "0"),
+            (One, "1"),
+            (Two, "2"),
+            (Few, "3~10, 103~110, 1003"),
+            (Many, "11~26, 111, 1011"),
+            (
+                Other,
+                "100~102, 200~202, 300~302, 400~402, 500~502, 600, 1000, 10000, 100000, 1000000",
+            ),
+        ],
+    ),
+    ("ast", ONE_IS_ONE),
+    ("az", ONE_IS_ONE),
+    ("be", EAST_SLAVIC),
+    ("bg", ONE_IS_ONE),
+    ("bn", ONE_IS_ZERO_OR_ONE),
+    ("bo", ONLY_OTHER),
+    (
+        "br",
+        &[
+            (One, "1, 21, 31, 41, 51, 61, 81, 101, 1001"),
+            (Two, "2, 22, 32, 42, 52, 62, 82, 102, 1002"),
+            (Few, "3, 4, 9, 23, 24, 29, 33, 34, 39, 43, 44, 49, 103, 1003"),
+            (Many, "1000000"),
+            (Other, "0, 5~8, 10~20, 100, 1000, 10000, 100000"),
+        ],
+    ),
+    ("bs", SOUTH_SLAVIC),
+    ("ca", ONE_IS_ONE_MANY_MILLIONS),
+    ("cs", WEST_SLAVIC),
+    (
+        "cy",
+        &[
+            (Zero, "0"),
+            (One, "1"),
+            (Two, "2"),
+            (Few, "3"),
+            (Many, "6"),
+            (Other, "4, 5, 7~20, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("da", ONE_IS_ONE),
+    ("de", ONE_IS_ONE),
+    ("dz", ONLY_OTHER),
+    ("el", ONE_IS_ONE),
+    ("en", ONE_IS_ONE),
+    ("eo", ONE_IS_ONE),
+    ("es", ONE_IS_ONE_MANY_MILLIONS),
+    ("et", ONE_IS_ONE),
+    ("eu", ONE_IS_ONE),
+    ("fa", ONE_IS_ZERO_OR_ONE),
+    ("fi", ONE_IS_ONE),
+    (
+        "fil",
+        &[
+            (
+                One,
+                "0~3, 5, 7, 8, 10~13, 15, 17, 18, 20, 21, 100, 1000, 10000, 100000, 1000000",
+            ),
+            (Other, "4, 6, 9, 14, 16, 19, 24, 26, 104, 1004"),
+        ],
+    ),
+    ("fo", ONE_IS_ONE),
+    ("fr", ONE_IS_ZERO_OR_ONE_MANY_MILLIONS),
+    ("fur", ONE_IS_ONE),
+    ("fy", ONE_IS_ONE),
+    (
+        "ga",
+        &[
+            (One, "1"),
+            (Two, "2"),
+            (Few, "3~6"),
+            (Many, "7~10"),
+            (Other, "0, 11~25, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    (
+        "gd",
+        &[
+            (One, "1, 11"),
+            (Two, "2, 12"),
+            (Few, "3~10, 13~19"),
+            (Other, "0, 20~34, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("gl", ONE_IS_ONE),
+    ("gu", ONE_IS_ZERO_OR_ONE),
+    (
+        "he",
+        &[
+            (One, "1"),
+            (Two, "2"),
+            (Other, "0, 3~17, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("hi", ONE_IS_ZERO_OR_ONE),
+    ("hr", SOUTH_SLAVIC),
+    ("hu", ONE_IS_ONE),
+    ("hy", ONE_IS_ZERO_OR_ONE),
+    ("ia", ONE_IS_ONE),
+    ("id", ONLY_OTHER),
+    ("is", ONE_ENDS_WITH_ONE),
+    ("it", ONE_IS_ONE_MANY_MILLIONS),
+    ("ja", ONLY_OTHER),
+    ("jbo", ONLY_OTHER),
+    ("ka", ONE_IS_ONE),
+    ("kk", ONE_IS_ONE),
+    ("km", ONLY_OTHER),
+    ("kn", ONE_IS_ZERO_OR_ONE),
+    ("ko", ONLY_OTHER),
+    ("ku", ONE_IS_ONE),
+    ("ky", ONE_IS_ONE),
+    ("lb", ONE_IS_ONE),
+    ("ln", ONE_IS_ZERO_OR_ONE),
+    ("lo", ONLY_OTHER),
+    (
+        "lt",
+        &[
+            (One, "1, 21, 31, 41, 51, 61, 71, 81, 101, 1001"),
+            (Few, "2~9, 22~29, 102, 1002"),
+            (Other, "0, 10~20, 30, 40, 50, 60, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    (
+        "lv",
+        &[
+            (Zero, "0, 10~20, 30, 40, 50, 60, 100, 1000, 10000, 100000, 1000000"),
+            (One, "1, 21, 31, 41, 51, 61, 71, 81, 101, 1001"),
+            (Other, "2~9, 22~29, 102, 1002"),
+        ],
+    ),
+    ("mg", ONE_IS_ZERO_OR_ONE),
+    (
+        "mk",
+        &[
+            (One, "1, 21, 31, 41, 51, 61, 71, 81, 101, 1001"),
+            (Other, "0, 2~10, 12~17, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("ml", ONE_IS_ONE),
+    ("mn", ONE_IS_ONE),
+    ("mr", ONE_IS_ONE),
+    ("ms", ONLY_OTHER),
+    (
+        "mt",
+        &[
+            (One, "1"),
+            (Two, "2"),
+            (Few, "0, 3~10, 103~109, 1003"),
+            (Many, "11~19, 111~117, 1011"),
+            (Other, "20~35, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("my", ONLY_OTHER),
+    ("nb", ONE_IS_ONE),
+    ("ne", ONE_IS_ONE),
+    ("nl", ONE_IS_ONE),
+    ("nn", ONE_IS_ONE),
+    ("no", ONE_IS_ONE),
+    ("or", ONE_IS_ONE),
+    ("pa", ONE_IS_ZERO_OR_ONE),
+    (
+        "pl",
+        &[
+            (One, "1"),
+            (Few, "2~4, 22~24, 32~34, 42~44, 52~54, 62, 102, 1002"),
+            (Many, "0, 5~19, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("ps", ONE_IS_ONE),
+    ("pt", ONE_IS_ZERO_OR_ONE_MANY_MILLIONS),
+    ("pt_PT", ONE_IS_ONE_MANY_MILLIONS),
+    (
+        "ro",
+        &[
+            (One, "1"),
+            (Few, "0, 2~16, 102, 1002"),
+            (Other, "20~35, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("ru", EAST_SLAVIC),
+    ("sk", WEST_SLAVIC),
+    (
+        "sl",
+        &[
+            (One, "1, 101, 201, 301, 401, 501, 601, 701, 1001"),
+            (Two, "2, 102, 202, 302, 402, 502, 602, 702, 1002"),
+            (
+                Few,
+                "3, 4, 103, 104, 203, 204, 303, 304, 403, 404, 503, 504, 603, 604, 703, 704, 1003",
+            ),
+            (Other, "0, 5~19, 100, 1000, 10000, 100000, 1000000"),
+        ],
+    ),
+    ("sq", ONE_IS_ONE),
+    ("sr", SOUTH_SLAVIC),
+    ("sv", ONE_IS_ONE),
+    ("sw", ONE_IS_ONE),
+    ("ta", ONE_IS_ONE),
+    ("te", ONE_IS_ONE),
+    ("th", ONLY_OTHER),
+    ("ti", ONE_IS_ZERO_OR_ONE),
+    ("tk", ONE_IS_ONE),
+    ("tr", ONE_IS_ONE),
+    ("uk", EAST_SLAVIC),
+    ("ur", ONE_IS_ONE),
+    ("uz", ONE_IS_ONE),
+    ("vi", ONLY_OTHER),
+    ("wa", ONE_IS_ZERO_OR_ONE),
+    ("zh", ONLY_OTHER),
+];
+
+/// Find the CLDR samples of a language, from the most specific name of the language to the most general.
+fn find_samples(language: &LanguageRange) -> Option<Samples> {
+    language_chain(language).iter().find_map(|name| {
+        SAMPLES
+            .iter()
+            .find(|(lang, _)| lang.eq_ignore_ascii_case(name))
+            .map(|(_, samples)| *samples)
+    })
+}
+
+/// Expand samples like `0, 2~4, 100` into the numbers.
+fn expand(samples: &str) -> impl Iterator<Item = usize> + '_ {
+    samples.split(',').flat_map(|sample| {
+        let (start, end) = sample.split_once('~').unwrap_or((sample, sample));
+        let parse = |n: &str| n.trim().parse::<usize>().unwrap_or_default();
+
+        parse(start)..=parse(end)
+    })
+}
+
+/// Count the samples of each category of a language which give each form.
+fn hits(forms: &PluralForms, samples: Samples) -> Vec<Vec<usize>> {
+    samples
+        .iter()
+        .map(|(_, numbers)| {
+            let mut res = vec![0; forms.get_count()];
+
+            for index in expand(numbers).filter_map(|n| forms.get_value(n)) {
+                res[index] += 1;
+            }
+
+            res
+        })
+        .collect()
+}
+
+/// Get the position of the greatest count, the first one if there are several, or `None` if all counts are 0.
+fn best<I: Iterator<Item = (usize, usize)>>(counts: I) -> Option<usize> {
+    counts
+        .filter(|(_, count)| *count > 0)
+        .fold(None, |best: Option<(usize, usize)>, (pos, count)| match best {
+            Some((_, best_count)) if best_count >= count => best,
+            _ => Some((pos, count)),
+        })
+        .map(|(pos, _)| pos)
+}
+
+/// Map each CLDR category of a language to the form given by most of its samples, the first one on ties.
+///
+/// The categories whose samples give no form are not mapped.
+pub(super) fn category_forms(forms: &PluralForms, language: &LanguageRange) -> Option<Vec<(PluralCategory, usize)>> {
+    let samples = find_samples(language)?;
+    let hits = hits(forms, samples);
+
+    Some(
+        samples
+            .iter()
+            .zip(&hits)
+            .filter_map(|((category, _), counts)| best(counts.iter().copied().enumerate()).map(|form| (*category, form)))
+            .collect(),
+    )
+}
+
+/// Map each form of the plural forms to its CLDR category, for a language.
+///
+/// This is the reverse of [`category_forms`]: a form gets the category `other` if it is mapped to it, else the mapped
+/// category whose samples mostly give it. A form to which no category is mapped gets the category whose samples mostly
+/// give it, and `other` if no sample gives it.
+pub(super) fn categories(forms: &PluralForms, language: &LanguageRange) -> Option<Vec<PluralCategory>> {
+    let samples = find_samples(language)?;
+    let hits = hits(forms, samples);
+    let mapped = category_forms(forms, language)?;
+
+    Some(
+        (0..forms.get_count())
+            .map(|form| {
+                let is_mapped = |category: &PluralCategory| mapped.contains(&(*category, form));
+                let any_mapped = samples.iter().any(|(category, _)| is_mapped(category));
+
+                if is_mapped(&Other) {
+                    return Other;
+                }
+
+                best(
+                    samples
+                        .iter()
+                        .zip(&hits)
+                        .enumerate()
+                        .filter(|(_, ((category, _), _))| !any_mapped || is_mapped(category))
+                        .map(|(pos, (_, counts))| (pos, counts[form])),
+                )
+                .map_or(Other, |pos| samples[pos].0)
+            })
+            .collect(),
+    )
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PoParser;
+
+    fn map(definition: &str, lang: &str) -> Option<Vec<&'static str>> {
+        let forms = PluralForms::parse(definition, &PoParser::new()).unwrap();
+
+        categories(&forms, &LanguageRange::new(lang).unwrap())
+            .map(|categories| categories.iter().map(PluralCategory::name).collect())
+    }
+
+    #[test]
+    fn test_const_samples() {
+        assert!(
+            SAMPLES.windows(2).all(|w| w[0].0 < w[1].0),
+            "The samples should be sorted by language"
+        );
+
+        for (lang, samples) in SAMPLES {
+            for (category, numbers) in samples {
+                assert!(
+                    numbers
+                        .split(',')
+                        .flat_map(|n| n.split('~'))
+                        .all(|n| n.trim().parse::<usize>().is_ok()),
+                    "Invalid samples of {} for {}",
+                    category,
+                    lang
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_func_name() {
+        for (category, name) in CATEGORY_NAMES {
+            assert_eq!(category.name(), name);
+            assert_eq!(category.to_string(), name);
+            assert_eq!(PluralCategory::from_name(name), Some(category));
+        }
+
+        assert_eq!(PluralCategory::from_name("several"), None);
+    }
+
+    #[test]
+    fn test_func_expand() {
+        assert_eq!(expand("0, 2~4, 100").collect::<Vec<_>>(), vec![0, 2, 3, 4, 100]);
+    }
+
+    #[test]
+    fn test_func_categories() {
+        let standard = |lang: &str| {
+            let forms = PluralForms::for_language(&LanguageRange::new(lang).unwrap()).unwrap();
+
+            map(forms.get_definition(), lang)
+        };
+
+        assert_eq!(standard("en"), Some(vec!["one", "other"]));
+        assert_eq!(standard("fr"), Some(vec!["one", "other"]));
+        assert_eq!(standard("hi"), Some(vec!["one", "other"]));
+        assert_eq!(standard("ja"), Some(vec!["other"]));
+        assert_eq!(standard("ru"), Some(vec!["one", "few", "many"]));
+        assert_eq!(standard("hr"), Some(vec!["one", "few", "other"]));
+        assert_eq!(standard("cs"), Some(vec!["one", "few", "other"]));
+        assert_eq!(standard("lv"), Some(vec!["one", "other", "zero"]));
+        assert_eq!(standard("ga"), Some(vec!["one", "two", "few", "many", "other"]));
+        assert_eq!(standard("ar"), Some(vec!["zero", "one", "two", "few", "many", "other"]));
+        assert_eq!(standard("sl"), Some(vec!["one", "two", "few", "other"]));
+        assert_eq!(standard("pt-BR"), Some(vec!["one", "other"]));
+        assert_eq!(standard("es"), Some(vec!["one", "other"]));
+        assert_eq!(
+            map("nplurals=3; plural=n==1 ? 0 : n==2 ? 1 : 2;", "en"),
+            Some(vec!["one", "other", "other"])
+        );
+        assert_eq!(
+            map("nplurals=3; plural=n<=1 ? 0 : n!=0 && n%1000000==0 ? 1 : 2;", "fr"),
+            Some(vec!["one", "many", "other"])
+        );
+        assert_eq!(
+            map("nplurals=3; plural=n==1 ? 0 : n!=0 && n%1000000==0 ? 1 : 2;", "it"),
+            Some(vec!["one", "many", "other"])
+        );
+        assert_eq!(standard("cy"), Some(vec!["one", "two", "other", "other"]));
+        assert_eq!(map("nplurals=2; plural=n != 1;", "tlh"), None);
+    }
+
+    #[test]
+    fn test_func_category_forms() {
+        let standard = |lang: &str| {
+            let language = LanguageRange::new(lang).unwrap();
+
+            category_forms(&PluralForms::for_language(&language).unwrap(), &language)
+        };
+
+        assert_eq!(standard("fr"), Some(vec![(One, 0), (Many, 1), (Other, 1)]));
+        assert_eq!(standard("es"), Some(vec![(One, 0), (Many, 1), (Other, 1)]));
+        assert_eq!(
+            standard("cy"),
+            Some(vec![(Zero, 2), (One, 0), (Two, 1), (Few, 2), (Many, 2), (Other, 2)])
+        );
+        assert_eq!(standard("lv"), Some(vec![(Zero, 1), (One, 0), (Other, 1)]));
+
+        let forms = PluralForms::parse("nplurals=2; plural=n != 1;", &PoParser::new()).unwrap();
+
+        assert_eq!(category_forms(&forms, &LanguageRange::new("tlh").unwrap()), None);
+    }
+}
+// no-coverage:stop
@@ -1,5 +1,21 @@
-use super::formula::Formula;
-use crate::{error::Error, PoParser};
+use super::{
+    category::{categories, category_forms},
+    formula::Formula,
+    rules::find_rule,
+    PluralCategory,
+};
+use crate::{
+    error::{Error, ErrorKind},
+    PoParser,
+};
+use locale_config::LanguageRange;
+use std::collections::BTreeSet;
+
+/// Numbers on which the plural formula is checked: all numbers up to 1000, then some greater numbers.
+const CHECKED_NUMBERS: [usize; 5] = [1000, 10_000, 100_000, 1_000_000, 1_000_000_000];
+
+/// Highest value of `nplurals`, far above the 6 forms of Arabic, so a bad header doesn't make huge plural messages.
+const MAX_COUNT: usize = 100;
 
 /// Decoded information from the header `Plural-Forms`
 #[derive(Clone, Debug)]
@@ -8,19 +24,31 @@ pub struct PluralForms {
     count: usize,
     definition: String,
     formula_source: String,
+    formula_offset: Option<usize>,
+    count_offset: Option<usize>,
 }
 
 impl PluralForms {
     pub(crate) fn parse(input: &str, parser: &PoParser) -> Result<PluralForms, Error> {
         let values = parser.parse_map(input)?;
+        let offset = |value: &str| value.as_ptr() as usize - input.as_ptr() as usize;
+        let formula_offset = values.get("plural").map(|s| offset(s));
+        let count_offset = values.get("nplurals").map(|s| offset(s));
         let formula_source = values.get("plural").map(|s| s.to_string()).unwrap_or_default();
-        let formula = Formula::parse(&formula_source)?;
+        let formula = Formula::parse_at(&formula_source, formula_offset.unwrap_or_default())?;
         let count: usize = match values.get("nplurals") {
             None => 2,
             Some(s) => match s.parse() {
+                Ok(v) if v > MAX_COUNT => {
+                    let kind = ErrorKind::InvalidPluralFormula { offset: count_offset };
+
+                    return Err(Error::new(kind, format!("‘nplurals’ must be at most {}", MAX_COUNT)));
+                }
                 Ok(v) => v,
                 Err(err) => {
-                    return Err(Error::PluralForms(err.to_string()));
+                    let kind = ErrorKind::InvalidPluralFormula { offset: count_offset };
+
+                    return Err(Error::new(kind, format!("Invalid ‘nplurals’ value: {}", err)).with_source(err));
                 }
             },
         };
@@ -30,15 +58,101 @@ impl PluralForms {
             count,
             definition: input.to_string(),
             formula_source,
+            formula_offset,
+            count_offset,
             // no-coverage:start
         })
         // no-coverage:stop
     }
 
+    /// Get the standard plural forms of a language, from a table like the one of gettext.
+    ///
+    /// The language is searched from its most specific name to the most general one, for instance `pt_BR` then `pt`.
+    pub fn for_language(language: &LanguageRange) -> Option<PluralForms> {
+        find_rule(language).and_then(|definition| PluralForms::parse(definition, &PoParser::new()).ok())
+    }
+
+    /// Get the CLDR category of each form, for a language, or `None` if the categories of the language aren't known.
+    ///
+    /// The formula is evaluated on the CLDR samples of each category, a form getting the category mapped to it by
+    /// [`category_form`](Self::category_form), `other` first.
+    pub fn categories(&self, language: &LanguageRange) -> Option<Vec<PluralCategory>> {
+        categories(self, language)
+    }
+
+    /// Get the form used for a CLDR category, for a language, or `None` if the categories of the language aren't known
+    /// or if no number of the category gives a form.
+    ///
+    /// The form is the one given by most of the CLDR samples of the category.
+    pub fn category_form(&self, category: PluralCategory, language: &LanguageRange) -> Option<usize> {
+        category_forms(self, language)?
+            .into_iter()
+            .find(|(c, _)| *c == category)
+            .map(|(_, form)| form)
+    }
+
     pub fn get_value(&self, count: usize) -> Option<usize> {
         self.formula.execute(count).filter(|v| *v < self.count)
     }
 
+    /// Same as `get_value`, but by walking the tree of the formula instead of running its compiled program.
+    #[doc(hidden)]
+    pub fn get_value_by_tree(&self, count: usize) -> Option<usize> {
+        self.formula.execute_tree(count).filter(|v| *v < self.count)
+    }
+
+    /// Check that the formula gives only the forms below `nplurals`, and that each of these forms is given for some
+    /// number.
+    ///
+    /// The formula is evaluated on all numbers up to 1000, and on some greater numbers. The offset of the error is the
+    /// one of the formula, or of `nplurals` if it is 0, in the definition.
+    pub fn check(&self) -> Result<(), Error> {
+        let kind = ErrorKind::InvalidPluralFormula {
+            offset: self.formula_offset,
+        };
+        let mut used = BTreeSet::new();
+
+        if self.count == 0 {
+            let kind = ErrorKind::InvalidPluralFormula {
+                offset: self.count_offset,
+            };
+
+            return Err(Error::new(kind, "‘nplurals’ must be at least 1"));
+        }
+
+        for n in (0..CHECKED_NUMBERS[0]).chain(CHECKED_NUMBERS) {
+            match self.formula.execute(n) {
+                Some(form) if form < self.count => {
+                    used.insert(form);
+                }
+                Some(form) => {
+                    let message = format!(
+                        "the plural formula gives the form {} for n = {}, but ‘nplurals’ is {}",
+                        form, n, self.count
+                    );
+
+                    return Err(Error::new(kind, message));
+                }
+                None => {
+                    return Err(Error::new(
+                        kind,
+                        format!("the plural formula gives a negative form for n = {}", n),
+                    ));
+                }
+            }
+        }
+
+        let unused: Vec<String> = (0..self.count).filter(|i| !used.contains(i)).map(|i| i.to_string()).collect();
+
+        if unused.is_empty() {
+            Ok(())
+        } else {
+            let message = format!("the plural formula never gives the forms {}", unused.join(", "));
+
+            Err(Error::new(kind, message))
+        }
+    }
+
     pub fn get_count(&self) -> usize {
         self.count
     }
@@ -64,6 +178,8 @@ mod tests {
                 count: 0,
                 formula_source: String::new(),
                 definition: String::new(),
+                formula_offset: None,
+                count_offset: None,
             }
         }
 
@@ -73,6 +189,8 @@ mod tests {
                 count: 200,
                 formula_source: String::from("n-100"),
                 definition: String::from("nplurals=200; plural=n-100"),
+                formula_offset: Some(21),
+                count_offset: Some(9),
             }
         }
 
@@ -154,6 +272,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_func_check() {
+        let check = |definition: &str| {
+            PluralForms::parse(definition, &PoParser::new())
+                .unwrap()
+                .check()
+                .map_err(|err| err.message().to_string())
+        };
+
+        assert_eq!(make_forms(COUNT_CASE1, FORMULA_CASE1).0.check().ok(), Some(()));
+        assert_eq!(check("nplurals=1; plural=0;"), Ok(()));
+        assert_eq!(check("nplurals=2; plural=n != 1;"), Ok(()));
+        assert_eq!(
+            check("nplurals=2; plural=n==1 ? 0 : n<5 ? 1 : 2;"),
+            Err(String::from(
+                "the plural formula gives the form 2 for n = 5, but ‘nplurals’ is 2"
+            ))
+        );
+        assert_eq!(
+            check("nplurals=2; plural=n-1;"),
+            Err(String::from("the plural formula gives a negative form for n = 0"))
+        );
+        assert_eq!(
+            check("nplurals=4; plural=n>1000000 ? 1 : 0;"),
+            Err(String::from("the plural formula never gives the forms 2, 3"))
+        );
+        assert_eq!(
+            PluralForms::parse("nplurals=101; plural=0;", &PoParser::new())
+                .map_err(|err| err.message().to_string())
+                .err(),
+            Some(String::from("‘nplurals’ must be at most 100"))
+        );
+        assert_eq!(
+            check("nplurals=0; plural=0;"),
+            Err(String::from("‘nplurals’ must be at least 1"))
+        );
+        assert!(matches!(
+            PluralForms::for_tests_shift().check().map_err(|err| err.kind()),
+            Err(ErrorKind::InvalidPluralFormula { offset: Some(21) })
+        ));
+    }
+
+    #[test]
+    fn test_func_for_language() {
+        let forms = |lang: &str| PluralForms::for_language(&LanguageRange::new(lang).unwrap());
+
+        assert_eq!(forms("fr").map(|f| f.get_count()), Some(2));
+        assert_eq!(forms("fr").and_then(|f| f.get_value(1)), Some(0));
+        assert_eq!(forms("pt-BR").and_then(|f| f.get_value(0)), Some(0));
+        assert_eq!(forms("pt").and_then(|f| f.get_value(0)), Some(1));
+        assert_eq!(forms("ru").and_then(|f| f.get_value(22)), Some(1));
+        assert_eq!(forms("ar").map(|f| f.get_count()), Some(6));
+        assert_eq!(
+            (0..12).map(|n| forms("ga").and_then(|f| f.get_value(n))).collect::<Vec<_>>(),
+            [4, 0, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4].map(Some)
+        );
+        assert!(forms("tlh").is_none());
+    }
+
     #[test]
     fn test_func_get_count() {
         let forms = make_forms(COUNT_CASE1, FORMULA_CASE1).0;
@@ -208,7 +385,8 @@ mod tests {
         assert_eq!(
             format!("{:?}", copy),
             format!(
-                "PluralForms {{ formula: {:?}, count: 3, definition: {:?}, formula_source: {:?} }}",
+                "PluralForms {{ formula: {:?}, count: 3, definition: {:?}, formula_source: {:?}, formula_offset: \
+                 Some(19), count_offset: Some(9) }}",
                 forms.formula, definition, FORMULA_CASE1,
             ),
         );
@@ -226,5 +404,35 @@ mod tests {
             "The parser should return an error for parsing of `nplurals`"
         );
     }
+
+    #[test]
+    fn test_error_offsets() {
+        let parser = PoParser::new();
+        let offset = |res: Result<(), Error>| match res.map_err(|err| err.kind()) {
+            Err(ErrorKind::InvalidPluralFormula { offset }) => offset,
+            res => panic!("Unexpected result: {:?}", res),
+        };
+
+        assert_eq!(
+            offset(PluralForms::parse("nplurals=wrong; plural=0;", &parser).map(|_| ())),
+            Some(9)
+        );
+        assert_eq!(
+            offset(PluralForms::parse("nplurals=99999999999999; plural=0;", &parser).map(|_| ())),
+            Some(9)
+        );
+        assert_eq!(
+            offset(PluralForms::parse("nplurals=2; plural=n > ;", &parser).map(|_| ())),
+            Some(22)
+        );
+        assert_eq!(
+            offset(PluralForms::parse("nplurals=0; plural=0;", &parser).unwrap().check()),
+            Some(9)
+        );
+        assert_eq!(
+            offset(PluralForms::parse("nplurals=3;  plural=n > 1;", &parser).unwrap().check()),
+            Some(20)
+        );
+    }
 }
 // no-coverage:stop
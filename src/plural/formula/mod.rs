@@ -1,40 +1,70 @@
 mod node;
+mod program;
 
-use crate::error::Error;
+use crate::error::{Error, ErrorKind};
 use lalrpop_util::{lalrpop_mod, ParseError};
+use program::Program;
 
 lalrpop_mod!(formula, "/plural/formula/formula.rs");
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(super) struct Formula {
     expr: node::Node,
+    program: Program,
 }
 
 impl Formula {
-    pub(super) fn parse(input: &str) -> Result<Self, Error> {
+    fn new(expr: node::Node) -> Self {
+        let program = Program::compile(&expr);
+
+        Formula { expr, program }
+    }
+
+    /// Parse a formula found at the byte offset `position` of a text, the offset of an error being in this text.
+    pub(super) fn parse_at(input: &str, position: usize) -> Result<Self, Error> {
+        let start = position + input.len() - input.trim_start().len();
         let input = input.trim();
 
         if input.is_empty() {
-            return Ok(Formula { expr: node::Node::Var });
+            return Ok(Formula::new(node::Node::Var));
         }
 
         let parser = formula::FormulaParser::new();
         let res: Result<node::Node, ParseError<_, _, _>> = parser.parse(input);
 
         match res {
-            Ok(expr) => Ok(Formula { expr }),
-            Err(err) => Err(Error::PluralForms(err.to_string())),
+            Ok(expr) => Ok(Formula::new(expr)),
+            Err(err) => {
+                let offset = match &err {
+                    ParseError::InvalidToken { location } | ParseError::UnrecognizedEof { location, .. } => {
+                        Some(*location)
+                    }
+                    ParseError::UnrecognizedToken { token, .. } | ParseError::ExtraToken { token } => Some(token.0),
+                    ParseError::User { .. } => None,
+                }
+                .map(|offset| offset + start);
+
+                Err(Error::new(ErrorKind::InvalidPluralFormula { offset }, err.to_string())
+                    .with_source(err.map_token(|t| t.to_string())))
+            }
         }
     }
 
     pub(super) fn execute(&self, count: usize) -> Option<usize> {
-        let res = self.expr.execute(count as i64);
+        to_form(self.program.execute(count as i64))
+    }
 
-        if res < 0 {
-            None
-        } else {
-            Some(res as usize)
-        }
+    /// Evaluate the formula by walking its tree, for comparing with the compiled program in the benchmarks.
+    pub(super) fn execute_tree(&self, count: usize) -> Option<usize> {
+        to_form(self.expr.execute(count as i64))
+    }
+}
+
+fn to_form(value: i64) -> Option<usize> {
+    if value < 0 {
+        None
+    } else {
+        Some(value as usize)
     }
 }
 
@@ -45,7 +75,8 @@ mod tests {
         node::{BinOp, Node, UnOp},
         Formula,
     };
-    use std::collections::HashMap;
+    use crate::error::{Error, ErrorKind};
+    use std::{collections::HashMap, error::Error as StdError};
 
     struct TestCase {
         test_name: &'static str,
@@ -333,25 +364,52 @@ mod tests {
         TestCase::make_tests().into_iter().for_each(|t| t.run());
     }
 
+    #[test]
+    fn test_func_parse_with_error() {
+        for (source, offset) in [("n % 10 == ", Some(9)), ("n == 1 n", Some(7)), ("  n # 2", Some(4))] {
+            match Formula::parse(source) {
+                Err(err) => {
+                    assert_eq!(
+                        err.kind(),
+                        ErrorKind::InvalidPluralFormula { offset },
+                        "For `{}`",
+                        source
+                    );
+                    assert!(
+                        err.source().is_some(),
+                        "The error for `{}` should have a source",
+                        source
+                    );
+                }
+                Ok(f) => panic!("Unexpected formula for `{}`: {:?}", source, f),
+            }
+        }
+    }
+
     #[test]
     fn test_struct_formula() {
-        let formula = Formula { expr: Node::Var };
+        let formula = Formula::new(Node::Var);
         let copy = formula.clone();
 
         assert_eq!(copy.expr, formula.expr);
         assert_eq!(copy, formula);
-        assert_eq!(format!("{:?}", formula), String::from("Formula { expr: Var }"));
+        assert_eq!(
+            format!("{:?}", formula),
+            String::from("Formula { expr: Var, program: Program { code: [Var], depth: 1 } }")
+        );
     }
 
     impl Formula {
+        pub(in super::super) fn parse(input: &str) -> Result<Formula, Error> {
+            Formula::parse_at(input, 0)
+        }
+
         pub(in super::super) fn for_tests_empty() -> Formula {
-            Formula { expr: Node::new_num(0) }
+            Formula::new(Node::new_num(0))
         }
 
         pub(in super::super) fn for_tests_shift() -> Formula {
-            Formula {
-                expr: Node::new_binop(BinOp::Sub, Node::Var, Node::new_num(100)),
-            }
+            Formula::new(Node::new_binop(BinOp::Sub, Node::Var, Node::new_num(100)))
         }
     }
 }
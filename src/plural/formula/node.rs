@@ -21,6 +21,47 @@ pub(crate) enum BinOp {
     Gte,
 }
 
+impl UnOp {
+    pub(super) fn apply(self, rhs: i64) -> i64 {
+        match self {
+            UnOp::Not => bool_to_num(rhs == 0),
+            UnOp::Neg => rhs.overflowing_neg().0,
+        }
+    }
+}
+
+impl BinOp {
+    pub(super) fn apply(self, lhs: i64, rhs: i64) -> i64 {
+        match self {
+            BinOp::Add => lhs.overflowing_add(rhs).0,
+            BinOp::Sub => lhs.overflowing_sub(rhs).0,
+            BinOp::Mul => lhs.overflowing_mul(rhs).0,
+            BinOp::Div => {
+                if rhs != 0 {
+                    lhs.overflowing_div(rhs).0
+                } else {
+                    get_infinity(lhs)
+                }
+            }
+            BinOp::Mod => {
+                if rhs != 0 {
+                    lhs.overflowing_rem(rhs).0
+                } else {
+                    lhs
+                }
+            }
+            BinOp::And => bool_to_num((lhs != 0) && (rhs != 0)),
+            BinOp::Or => bool_to_num((lhs != 0) || (rhs != 0)),
+            BinOp::Eq => bool_to_num(lhs == rhs),
+            BinOp::Ne => bool_to_num(lhs != rhs),
+            BinOp::Lt => bool_to_num(lhs < rhs),
+            BinOp::Lte => bool_to_num(lhs <= rhs),
+            BinOp::Gt => bool_to_num(lhs > rhs),
+            BinOp::Gte => bool_to_num(lhs >= rhs),
+        }
+    }
+}
+
 #[inline]
 fn bool_to_num(b: bool) -> i64 {
     if b {
@@ -88,47 +129,19 @@ impl Node {
         // no-coverage:stop
     }
 
+    /// Evaluate the tree directly, which is the reference for the compiled program in the tests and the benchmarks.
     pub(super) fn execute(&self, n: i64) -> i64 {
         match self {
             Node::Var => n,
             Node::Num(v) => *v,
-            Node::UnOp { op, rhs } => match op {
-                UnOp::Not => bool_to_num(rhs.execute(n) == 0),
-                UnOp::Neg => -rhs.execute(n),
-            },
+            Node::UnOp { op, rhs } => op.apply(rhs.execute(n)),
             Node::BinOp { op, lhs, rhs } => {
                 let lhs = lhs.execute(n);
 
                 match op {
-                    BinOp::Add => lhs.overflowing_add(rhs.execute(n)).0,
-                    BinOp::Sub => lhs.overflowing_sub(rhs.execute(n)).0,
-                    BinOp::Mul => lhs.overflowing_mul(rhs.execute(n)).0,
-                    BinOp::Div => {
-                        let rhs = rhs.execute(n);
-
-                        if rhs != 0 {
-                            lhs.overflowing_div(rhs).0
-                        } else {
-                            get_infinity(lhs)
-                        }
-                    }
-                    BinOp::Mod => {
-                        let rhs = rhs.execute(n);
-
-                        if rhs != 0 {
-                            lhs.overflowing_rem(rhs).0
-                        } else {
-                            lhs
-                        }
-                    }
                     BinOp::And => bool_to_num((lhs != 0) && (rhs.execute(n) != 0)),
                     BinOp::Or => bool_to_num((lhs != 0) || (rhs.execute(n) != 0)),
-                    BinOp::Eq => bool_to_num(lhs == rhs.execute(n)),
-                    BinOp::Ne => bool_to_num(lhs != rhs.execute(n)),
-                    BinOp::Lt => bool_to_num(lhs < rhs.execute(n)),
-                    BinOp::Lte => bool_to_num(lhs <= rhs.execute(n)),
-                    BinOp::Gt => bool_to_num(lhs > rhs.execute(n)),
-                    BinOp::Gte => bool_to_num(lhs >= rhs.execute(n)),
+                    _ => op.apply(lhs, rhs.execute(n)),
                 }
             }
             Node::Cond {
@@ -0,0 +1,260 @@
+use super::node::{BinOp, Node, UnOp};
+
+/// Size of the stack on the native stack when a program is run, enough for the usual formulas.
+const STACK_SIZE: usize = 16;
+
+/// Instruction of a compiled formula, for a machine with a stack of numbers.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub(super) enum Instr {
+    /// Push the number `n`.
+    Var,
+
+    /// Push a constant.
+    Num(i64),
+
+    /// Replace the number at the top by the result of the operator.
+    UnOp(UnOp),
+
+    /// Replace the two numbers at the top by the result of the operator.
+    BinOp(BinOp),
+
+    /// Replace the number at the top by the result of the operator, with a constant as right operand.
+    BinOpNum(BinOp, i64),
+
+    /// Pop the number at the top, and go to the instruction if the number is zero.
+    JumpIfZero(usize),
+
+    /// Go to the instruction.
+    Jump(usize),
+}
+
+/// Formula compiled into a flat list of instructions, its constant parts being folded.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(super) struct Program {
+    code: Vec<Instr>,
+    depth: usize,
+}
+
+impl Program {
+    pub(super) fn compile(node: &Node) -> Program {
+        let mut compiler = Compiler {
+            code: vec![],
+            depth: 0,
+            max_depth: 0,
+        };
+
+        compiler.emit(&fold(node));
+
+        Program {
+            code: compiler.code,
+            depth: compiler.max_depth,
+        }
+    }
+
+    pub(super) fn execute(&self, n: i64) -> i64 {
+        if self.depth <= STACK_SIZE {
+            self.run(n, &mut [0; STACK_SIZE])
+        } else {
+            self.run(n, &mut vec![0; self.depth])
+        }
+    }
+
+    fn run(&self, n: i64, stack: &mut [i64]) -> i64 {
+        let (mut top, mut next) = (0, 0);
+
+        while let Some(instr) = self.code.get(next) {
+            next += 1;
+
+            match *instr {
+                Instr::Var => {
+                    stack[top] = n;
+                    top += 1;
+                }
+                Instr::Num(v) => {
+                    stack[top] = v;
+                    top += 1;
+                }
+                Instr::UnOp(op) => stack[top - 1] = op.apply(stack[top - 1]),
+                Instr::BinOp(op) => {
+                    top -= 1;
+                    stack[top - 1] = op.apply(stack[top - 1], stack[top]);
+                }
+                Instr::BinOpNum(op, v) => stack[top - 1] = op.apply(stack[top - 1], v),
+                Instr::JumpIfZero(target) => {
+                    top -= 1;
+                    if stack[top] == 0 {
+                        next = target;
+                    }
+                }
+                Instr::Jump(target) => next = target,
+            }
+        }
+
+        stack[0]
+    }
+}
+
+struct Compiler {
+    code: Vec<Instr>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Compiler {
+    fn emit(&mut self, node: &Node) {
+        match node {
+            Node::Var => self.push(Instr::Var),
+            Node::Num(v) => self.push(Instr::Num(*v)),
+            Node::UnOp { op, rhs } => {
+                self.emit(rhs);
+                self.code.push(Instr::UnOp(*op));
+            }
+            Node::BinOp { op, lhs, rhs } => {
+                self.emit(lhs);
+                if let Node::Num(v) = rhs.as_ref() {
+                    self.code.push(Instr::BinOpNum(*op, *v));
+                } else {
+                    self.emit(rhs);
+                    self.code.push(Instr::BinOp(*op));
+                    self.depth -= 1;
+                }
+            }
+            Node::Cond {
+                test,
+                if_true,
+                if_false,
+            } => {
+                self.emit(test);
+                self.depth -= 1;
+
+                let test_jump = self.code.len();
+
+                self.code.push(Instr::JumpIfZero(0));
+                self.emit(if_true);
+                self.depth -= 1;
+
+                let end_jump = self.code.len();
+
+                self.code.push(Instr::Jump(0));
+                self.code[test_jump] = Instr::JumpIfZero(self.code.len());
+                self.emit(if_false);
+                self.code[end_jump] = Instr::Jump(self.code.len());
+            }
+        }
+    }
+
+    fn push(&mut self, instr: Instr) {
+        self.code.push(instr);
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+    }
+}
+
+/// Fold the constant parts of a formula, like `2 * 3` or a condition with a constant test.
+fn fold(node: &Node) -> Node {
+    match node {
+        Node::Var | Node::Num(_) => node.clone(),
+        Node::UnOp { op, rhs } => match fold(rhs) {
+            Node::Num(v) => Node::Num(op.apply(v)),
+            rhs => Node::new_unop(*op, rhs),
+        },
+        Node::BinOp { op, lhs, rhs } => match (op, fold(lhs), fold(rhs)) {
+            (_, Node::Num(l), Node::Num(r)) => Node::Num(op.apply(l, r)),
+            (BinOp::And, Node::Num(0), _) => Node::Num(0),
+            (BinOp::Or, Node::Num(l), _) if l != 0 => Node::Num(1),
+            (_, lhs, rhs) => Node::new_binop(*op, lhs, rhs),
+        },
+        Node::Cond {
+            test,
+            if_true,
+            if_false,
+        } => match fold(test) {
+            Node::Num(v) => fold(if v != 0 { if_true } else { if_false }),
+            test => Node::new_cond(test, fold(if_true), fold(if_false)),
+        },
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plural::formula::Formula;
+
+    fn compile(source: &str) -> Program {
+        Formula::parse(source).unwrap().program
+    }
+
+    #[test]
+    fn test_func_compile() {
+        let program = compile("n%10==1 && n%100!=11 ? 0 : 1");
+
+        assert_eq!(
+            program.code,
+            vec![
+                Instr::Var,
+                Instr::BinOpNum(BinOp::Mod, 10),
+                Instr::BinOpNum(BinOp::Eq, 1),
+                Instr::Var,
+                Instr::BinOpNum(BinOp::Mod, 100),
+                Instr::BinOpNum(BinOp::Ne, 11),
+                Instr::BinOp(BinOp::And),
+                Instr::JumpIfZero(10),
+                Instr::Num(0),
+                Instr::Jump(11),
+                Instr::Num(1),
+            ]
+        );
+        assert_eq!(program.depth, 2);
+    }
+
+    #[test]
+    fn test_func_compile_with_folding() {
+        let code = |source: &str| compile(source).code;
+
+        assert_eq!(
+            code("(1 + 2) * n"),
+            vec![Instr::Num(3), Instr::Var, Instr::BinOp(BinOp::Mul)]
+        );
+        assert_eq!(code("n > 10 * 10"), vec![Instr::Var, Instr::BinOpNum(BinOp::Gt, 100)]);
+        assert_eq!(code("-(2 - 3)"), vec![Instr::Num(1)]);
+        assert_eq!(code("!(2 > 1) ? n : 2"), vec![Instr::Num(2)]);
+        assert_eq!(code("1 ? n : 2"), vec![Instr::Var]);
+        assert_eq!(code("0 && n"), vec![Instr::Num(0)]);
+        assert_eq!(code("2 || n"), vec![Instr::Num(1)]);
+        assert_eq!(
+            code("1 && n"),
+            vec![Instr::Num(1), Instr::Var, Instr::BinOp(BinOp::And)]
+        );
+        assert_eq!(code("!n"), vec![Instr::Var, Instr::UnOp(UnOp::Not)]);
+    }
+
+    #[test]
+    fn test_func_execute() {
+        let sources = [
+            "n != 1",
+            "n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2",
+            "n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5",
+            "n > 10 ? (n % 10) == 3 ? 10 : n < 100 ? 20 : (!(n > 200) ? -n + 1000 : 1234) : n - 10",
+            "(n / 0) + (n % 0) - (10 / n) * (!n)",
+            "1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (1 + (n)))))))))))))))))",
+        ];
+
+        for source in sources {
+            let formula = Formula::parse(source).unwrap();
+
+            for n in (-20..1100).chain([i64::MIN, i64::MAX]) {
+                assert_eq!(
+                    formula.program.execute(n),
+                    formula.expr.execute(n),
+                    "For `{}` with n = {}",
+                    source,
+                    n
+                );
+            }
+        }
+
+        assert!(compile(sources[5]).depth > STACK_SIZE);
+    }
+}
+// no-coverage:stop
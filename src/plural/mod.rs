@@ -1,5 +1,8 @@
+mod category;
 mod forms;
 mod formula;
+#[allow(clippy::module_inception)]
 mod plural;
+mod rules;
 
-pub use self::{forms::PluralForms, plural::Plural};
+pub use self::{category::PluralCategory, forms::PluralForms, plural::Plural};
@@ -1,4 +1,5 @@
-use super::PluralForms;
+use super::{PluralCategory, PluralForms};
+use locale_config::LanguageRange;
 use std::rc::Rc;
 
 /// Plural set
@@ -11,7 +12,8 @@ pub struct Plural {
 }
 
 impl Plural {
-    pub(crate) fn new(singular: String, plural: String, values: Vec<String>, forms: Option<Rc<PluralForms>>) -> Self {
+    /// Create a plural message, with its translations ordered by the plural forms.
+    pub fn new(singular: String, plural: String, values: Vec<String>, forms: Option<Rc<PluralForms>>) -> Self {
         Self {
             forms,
             singular,
@@ -29,7 +31,7 @@ impl Plural {
     }
 
     pub fn first(&self) -> &str {
-        self.values.iter().next().map(|s| s.as_str()).unwrap_or_default()
+        self.values.first().map(|s| s.as_str()).unwrap_or_default()
     }
 
     pub fn get(&self, count: usize) -> Option<&str> {
@@ -41,6 +43,17 @@ impl Plural {
         })
     }
 
+    /// Get the translation of a CLDR category, like `one` or `few`, for the language of the translations.
+    ///
+    /// The translation is the form used for most numbers of the category (see [`PluralForms::category_form`]).
+    pub fn get_by_category(&self, category: PluralCategory, language: &LanguageRange) -> Option<&str> {
+        self.forms
+            .as_ref()
+            .and_then(|forms| forms.category_form(category, language))
+            .and_then(|index| self.values.get(index))
+            .map(|v| v.as_str())
+    }
+
     pub fn values(&self) -> &Vec<String> {
         &self.values
     }
@@ -52,6 +65,10 @@ impl Plural {
     pub fn get_forms(&self) -> Option<&PluralForms> {
         self.forms.as_ref().map(|f| f.as_ref())
     }
+
+    pub(crate) fn shared_forms(&self) -> Option<Rc<PluralForms>> {
+        self.forms.as_ref().map(Rc::clone)
+    }
 }
 
 // no-coverage:start
@@ -194,5 +211,21 @@ mod tests {
         assert_eq!(plural.get(10), Some(PLURAL_FR));
         assert_eq!(plural.get(100), Some(PLURAL_FR));
     }
+
+    #[test]
+    fn test_func_get_by_category() {
+        let plural = make_plural();
+        let fr = LanguageRange::new("fr").unwrap();
+
+        assert_eq!(plural.get_by_category(PluralCategory::One, &fr), Some(SINGULAR_FR));
+        assert_eq!(plural.get_by_category(PluralCategory::Other, &fr), Some(PLURAL_FR));
+        assert_eq!(plural.get_by_category(PluralCategory::Many, &fr), Some(PLURAL_FR));
+        assert_eq!(plural.get_by_category(PluralCategory::Few, &fr), None);
+        assert_eq!(
+            plural.get_by_category(PluralCategory::One, &LanguageRange::new("tlh").unwrap()),
+            None
+        );
+        assert_eq!(make_blank(vec![]).get_by_category(PluralCategory::One, &fr), None);
+    }
 }
 // no-coverage:stop
@@ -0,0 +1,190 @@
+use crate::header::language_chain;
+use locale_config::LanguageRange;
+
+const ONE_FORM: &str = "nplurals=1; plural=0;";
+const NOT_ONE: &str = "nplurals=2; plural=(n != 1);";
+const GREATER_THAN_ONE: &str = "nplurals=2; plural=(n > 1);";
+const EAST_SLAVIC: &str =
+    "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);";
+const WEST_SLAVIC: &str = "nplurals=3; plural=(n==1) ? 0 : (n>=2 && n<=4) ? 1 : 2;";
+
+/// Standard `Plural-Forms` of the languages, like in the table of gettext, sorted by language.
+const RULES: [(&str, &str); 100] = [
+    ("af", NOT_ONE),
+    ("ak", GREATER_THAN_ONE),
+    ("am", GREATER_THAN_ONE),
+    (
+        "ar",
+        "nplurals=6; plural=(n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5);",
+    ),
+    ("ast", NOT_ONE),
+    ("az", NOT_ONE),
+    ("be", EAST_SLAVIC),
+    ("bg", NOT_ONE),
+    ("bn", NOT_ONE),
+    ("bo", ONE_FORM),
+    ("br", GREATER_THAN_ONE),
+    ("bs", EAST_SLAVIC),
+    ("ca", NOT_ONE),
+    ("cs", WEST_SLAVIC),
+    (
+        "cy",
+        "nplurals=4; plural=(n==1) ? 0 : (n==2) ? 1 : (n != 8 && n != 11) ? 2 : 3;",
+    ),
+    ("da", NOT_ONE),
+    ("de", NOT_ONE),
+    ("dz", ONE_FORM),
+    ("el", NOT_ONE),
+    ("en", NOT_ONE),
+    ("eo", NOT_ONE),
+    ("es", NOT_ONE),
+    ("et", NOT_ONE),
+    ("eu", NOT_ONE),
+    ("fa", GREATER_THAN_ONE),
+    ("fi", NOT_ONE),
+    ("fil", GREATER_THAN_ONE),
+    ("fo", NOT_ONE),
+    ("fr", GREATER_THAN_ONE),
+    ("fur", NOT_ONE),
+    ("fy", NOT_ONE),
+    (
+        "ga",
+        "nplurals=5; plural=n==1 ? 0 : n==2 ? 1 : (n>2 && n<7) ? 2 :(n>6 && n<11) ? 3 : 4;",
+    ),
+    (
+        "gd",
+        "nplurals=4; plural=(n==1 || n==11) ? 0 : (n==2 || n==12) ? 1 : (n > 2 && n < 20) ? 2 : 3;",
+    ),
+    ("gl", NOT_ONE),
+    ("gu", NOT_ONE),
+    ("he", NOT_ONE),
+    ("hi", NOT_ONE),
+    ("hr", EAST_SLAVIC),
+    ("hu", NOT_ONE),
+    ("hy", GREATER_THAN_ONE),
+    ("ia", NOT_ONE),
+    ("id", ONE_FORM),
+    ("is", "nplurals=2; plural=(n%10 != 1 || n%100 == 11);"),
+    ("it", NOT_ONE),
+    ("ja", ONE_FORM),
+    ("jbo", ONE_FORM),
+    ("ka", ONE_FORM),
+    ("kk", NOT_ONE),
+    ("km", ONE_FORM),
+    ("kn", NOT_ONE),
+    ("ko", ONE_FORM),
+    ("ku", NOT_ONE),
+    ("ky", NOT_ONE),
+    ("lb", NOT_ONE),
+    ("ln", GREATER_THAN_ONE),
+    ("lo", ONE_FORM),
+    (
+        "lt",
+        "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && (n%100<10 || n%100>=20) ? 1 : 2);",
+    ),
+    ("lv", "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n != 0 ? 1 : 2);"),
+    ("mg", GREATER_THAN_ONE),
+    ("mi", GREATER_THAN_ONE),
+    ("mk", "nplurals=2; plural=(n%10==1 && n%100!=11 ? 0 : 1);"),
+    ("ml", NOT_ONE),
+    ("mn", NOT_ONE),
+    ("mr", NOT_ONE),
+    ("ms", ONE_FORM),
+    (
+        "mt",
+        "nplurals=4; plural=(n==1 ? 0 : n==0 || (n%100>1 && n%100<11) ? 1 : (n%100>10 && n%100<20) ? 2 : 3);",
+    ),
+    ("my", ONE_FORM),
+    ("nb", NOT_ONE),
+    ("ne", NOT_ONE),
+    ("nl", NOT_ONE),
+    ("nn", NOT_ONE),
+    ("no", NOT_ONE),
+    ("oc", GREATER_THAN_ONE),
+    ("or", NOT_ONE),
+    ("pa", NOT_ONE),
+    (
+        "pl",
+        "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);",
+    ),
+    ("ps", NOT_ONE),
+    ("pt", NOT_ONE),
+    ("pt_BR", GREATER_THAN_ONE),
+    (
+        "ro",
+        "nplurals=3; plural=(n==1 ? 0 : (n==0 || (n%100 > 0 && n%100 < 20)) ? 1 : 2);",
+    ),
+    ("ru", EAST_SLAVIC),
+    ("sk", WEST_SLAVIC),
+    (
+        "sl",
+        "nplurals=4; plural=(n%100==1 ? 0 : n%100==2 ? 1 : n%100==3 || n%100==4 ? 2 : 3);",
+    ),
+    ("sq", NOT_ONE),
+    ("sr", EAST_SLAVIC),
+    ("sv", NOT_ONE),
+    ("sw", NOT_ONE),
+    ("ta", NOT_ONE),
+    ("te", NOT_ONE),
+    ("tg", GREATER_THAN_ONE),
+    ("th", ONE_FORM),
+    ("ti", GREATER_THAN_ONE),
+    ("tk", NOT_ONE),
+    ("tr", NOT_ONE),
+    ("uk", EAST_SLAVIC),
+    ("ur", NOT_ONE),
+    ("uz", GREATER_THAN_ONE),
+    ("vi", ONE_FORM),
+    ("wa", GREATER_THAN_ONE),
+    ("zh", ONE_FORM),
+];
+
+/// Find the standard `Plural-Forms` of a language, from the most specific name of the language to the most general.
+pub(super) fn find_rule(language: &LanguageRange) -> Option<&'static str> {
+    language_chain(language).iter().find_map(|name| {
+        RULES
+            .iter()
+            .find(|(lang, _)| lang.eq_ignore_ascii_case(name))
+            .map(|(_, rule)| *rule)
+    })
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{plural::PluralForms, PoParser};
+
+    #[test]
+    fn test_const_rules() {
+        assert!(
+            RULES.windows(2).all(|w| w[0].0 < w[1].0),
+            "The rules should be sorted by language"
+        );
+    }
+
+    #[test]
+    fn test_const_rules_are_valid() {
+        let parser = PoParser::new();
+
+        for (lang, rule) in RULES {
+            if let Err(err) = PluralForms::parse(rule, &parser).and_then(|forms| forms.check()) {
+                panic!("Invalid plural forms for {}: {}", lang, err.message());
+            }
+        }
+    }
+
+    #[test]
+    fn test_func_find_rule() {
+        let find = |lang: &str| find_rule(&LanguageRange::new(lang).unwrap());
+
+        assert_eq!(find("fr"), Some(GREATER_THAN_ONE));
+        assert_eq!(find("fr-CA"), Some(GREATER_THAN_ONE));
+        assert_eq!(find("pt-BR"), Some(GREATER_THAN_ONE));
+        assert_eq!(find("pt-PT"), Some(NOT_ONE));
+        assert_eq!(find("ja"), Some(ONE_FORM));
+        assert_eq!(find("tlh"), None);
+        assert_eq!(find_rule(&LanguageRange::invariant()), None);
+    }
+}
+// no-coverage:stop
@@ -1,13 +1,21 @@
 use super::{line::PoLine, line_iter::LineIter};
-use crate::{error::Error, unit::Unit};
-use std::{io::Read, iter::Peekable};
+use crate::{
+    error::{Error, ErrorKind},
+    span::Span,
+    unit::Unit,
+};
+use std::io::Read;
 
 #[inline]
-fn fetch_next<R: Read>(reader: &mut Peekable<LineIter<R>>) -> Result<Option<PoLine>, Error> {
+fn fetch_next<R: Read>(reader: &mut LineIter<R>) -> Result<Option<PoLine>, Error> {
     if let Some(Ok(line)) = reader.peek() {
         return Ok(Some(line.clone()));
     }
 
+    if reader.is_next_entry_error() {
+        return Ok(None);
+    }
+
     match reader.next() {
         Some(Err(err)) => Err(err),
         _ => Ok(None),
@@ -15,16 +23,20 @@ fn fetch_next<R: Read>(reader: &mut Peekable<LineIter<R>>) -> Result<Option<PoLi
 }
 
 pub(crate) trait Decoder {
-    fn parse_msg(&mut self, tag: &str, unit: &Unit) -> Result<Option<String>, Error>;
-    fn expected(&mut self, exp: &str) -> Result<(), Error>;
+    /// Parse the message with the tag `tag`, the message is given with its span.
+    fn parse_msg(&mut self, tag: &str, unit: &Unit) -> Result<Option<(String, Span)>, Error>;
+
+    /// Fail with an error of the kind `kind` if the next line is not the end of the entry, the token `exp` being
+    /// expected instead.
+    fn expected(&mut self, kind: ErrorKind, exp: &str) -> Result<(), Error>;
 }
 
-impl<'p, R: Read> Decoder for Peekable<LineIter<'p, R>> {
-    fn parse_msg(&mut self, tag: &str, unit: &Unit) -> Result<Option<String>, Error> {
-        let (prefix, mut string) = match fetch_next(self)? {
+impl<'p, R: Read> Decoder for LineIter<'p, R> {
+    fn parse_msg(&mut self, tag: &str, unit: &Unit) -> Result<Option<(String, Span)>, Error> {
+        let (first, prefix, mut string) = match fetch_next(self)? {
             Some(PoLine::Message(_, p, t, _)) if t == tag && p.starts_with('~') == unit.obsolete => {
                 match self.next().unwrap().unwrap() {
-                    PoLine::Message(_, p, _, s) => (p, s),
+                    PoLine::Message(n, p, _, s) => (n, p, s),
                     _ => {
                         unreachable!();
                     }
@@ -35,14 +47,17 @@ impl<'p, R: Read> Decoder for Peekable<LineIter<'p, R>> {
             }
         };
 
+        let mut last = first;
+
         while let Some(PoLine::Continuation(_, ref p, _)) = fetch_next(self)? {
             if *p != prefix {
                 break;
             }
 
             match self.next().unwrap().unwrap() {
-                PoLine::Continuation(_, _, s) => {
+                PoLine::Continuation(n, _, s) => {
                     string.push_str(&s);
+                    last = n;
                 }
                 _ => {
                     unreachable!();
@@ -50,10 +65,19 @@ impl<'p, R: Read> Decoder for Peekable<LineIter<'p, R>> {
             }
         }
 
-        Ok(Some(string))
+        let span = match (self.line_span(first), self.line_span(last)) {
+            (Some(start), Some(end)) => start.to(&end),
+            _ => Span::default(),
+        };
+
+        Ok(Some((string, span)))
     }
 
-    fn expected(&mut self, exp: &str) -> Result<(), Error> {
+    fn expected(&mut self, kind: ErrorKind, exp: &str) -> Result<(), Error> {
+        if self.peek().is_some() && self.is_next_entry_error() {
+            return Ok(());
+        }
+
         match self.peek() {
             None | Some(Ok(PoLine::Blank)) => Ok(()),
             Some(Err(_)) => {
@@ -63,9 +87,18 @@ impl<'p, R: Read> Decoder for Peekable<LineIter<'p, R>> {
                     unreachable!();
                 }
             }
-            Some(Ok(PoLine::Message(n, p, ..))) => Err(Error::Parse(*n, p.clone(), exp.to_string())),
-            Some(Ok(PoLine::Continuation(n, ..))) => Err(Error::Parse(*n, String::from("\""), exp.to_string())),
-            Some(Ok(PoLine::Comment(n, c, ..))) => Err(Error::Parse(*n, format!("#{}", c), exp.to_string())),
+            Some(Ok(line)) => {
+                let line = line.clone();
+                let span = self.line_span(line.line()).unwrap_or_default();
+                let got = match line {
+                    PoLine::Message(_, p, ..) => p,
+                    PoLine::Continuation(..) => String::from("\""),
+                    PoLine::Comment(_, c, _) => format!("#{}", c),
+                    PoLine::Blank => unreachable!(),
+                };
+
+                Err(Error::parse(kind, span, got, exp))
+            }
         }
     }
 }
@@ -74,10 +107,12 @@ impl<'p, R: Read> Decoder for Peekable<LineIter<'p, R>> {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::{super::PoParser, *};
+    use crate::span::Position;
     use std::collections::{hash_map::Entry, HashMap};
 
     type Str = &'static str;
 
+    #[allow(clippy::enum_variant_names)]
     pub(crate) enum TestAction<T> {
         ActOk(T),
         ActErr(Error),
@@ -157,7 +192,7 @@ pub(crate) mod tests {
     }
 
     impl Decoder for TestDecoder {
-        fn parse_msg(&mut self, tag: &str, _unit: &Unit) -> Result<Option<String>, Error> {
+        fn parse_msg(&mut self, tag: &str, _unit: &Unit) -> Result<Option<(String, Span)>, Error> {
             if let Some(err) = self.error.take() {
                 return Err(err);
             }
@@ -165,17 +200,17 @@ pub(crate) mod tests {
             self.log.push(format!("Message: {}/{}", self.message, tag));
 
             match self.command.as_str() {
-                "@DoError" => self.set_error(Error::Unexpected(210, String::from("From command `@DoError`"))),
+                "@DoError" => self.set_error(Error::new(ErrorKind::Other, "From command `@DoError`").with_line(210)),
                 "@DoInc" => self.inc(),
                 _ => (),
             }
 
             match self.values.as_mut() {
-                None => Ok(Some(self.message.clone())),
+                None => Ok(Some((self.message.clone(), Span::default()))),
                 Some(map) => match map.entry(tag.to_string()) {
                     Entry::Vacant(_) => Ok(None),
                     Entry::Occupied(mut entry) => match entry.get_mut() {
-                        TestAction::ActOk(v) => Ok(Some(v.clone())),
+                        TestAction::ActOk(v) => Ok(Some((v.clone(), Span::default()))),
                         TestAction::ActErr(_) => Err(match entry.remove_entry().1 {
                             TestAction::ActErr(err) => err,
                             _ => unreachable!(),
@@ -192,7 +227,7 @@ pub(crate) mod tests {
             }
         }
 
-        fn expected(&mut self, exp: &str) -> Result<(), Error> {
+        fn expected(&mut self, _kind: ErrorKind, exp: &str) -> Result<(), Error> {
             if exp == self.message {
                 self.log.push(format!("Expected: {}", self.message));
                 if let Some(err) = self.error.take() {
@@ -207,7 +242,7 @@ pub(crate) mod tests {
     #[test]
     fn test_func_fetch_next() {
         let parser = PoParser::new();
-        let mut iter = LineIter::new("msgid \"line 1\"\nmsgstr \"line 2\"".as_bytes(), &parser).peekable();
+        let mut iter = LineIter::new("msgid \"line 1\"\nmsgstr \"line 2\"".as_bytes(), &parser);
 
         match fetch_next(&mut iter) {
             Ok(Some(line)) => match line {
@@ -242,12 +277,12 @@ pub(crate) mod tests {
             r => panic!("Unexpected result: {:?}", r),
         }
 
-        let mut iter = LineIter::new("msgid \"line 1".as_bytes(), &parser).peekable();
+        let mut iter = LineIter::new("msgid \"line 1".as_bytes(), &parser);
 
         match fetch_next(&mut iter) {
             Err(err) => assert_eq!(
                 format!("{:?}", err),
-                String::from("Parse error at line 2, got ‘msgid \"line 1’")
+                String::from("Parse error at line 1, column 1, got ‘msgid \"line 1’")
             ),
             r => panic!("Unexpected result: {:?}", r),
         }
@@ -259,7 +294,7 @@ pub(crate) mod tests {
 
         {
             let text = "#~ msgid \"this\"\nmsgid \"that\"";
-            let mut lines = LineIter::new(text.as_bytes(), &parser).peekable();
+            let mut lines = LineIter::new(text.as_bytes(), &parser);
             let mut unit = Unit::default();
 
             assert_eq!(lines.parse_msg("---", &unit), Ok(None));
@@ -267,7 +302,13 @@ pub(crate) mod tests {
 
             unit = Unit::for_tests_incomplete();
 
-            assert_eq!(lines.parse_msg("msgid", &unit), Ok(Some(String::from("this"))));
+            assert_eq!(
+                lines.parse_msg("msgid", &unit),
+                Ok(Some((
+                    String::from("this"),
+                    Span::new(Position::new(0, 1, 1), Position::new(15, 1, 16))
+                )))
+            );
 
             assert_eq!(lines.parse_msg("---", &unit), Ok(None));
             assert_eq!(lines.parse_msg("msgid", &unit), Ok(None));
@@ -275,30 +316,39 @@ pub(crate) mod tests {
 
         {
             let text = "msgid \"this\"\n\" is\"\n\" good\"";
-            let mut lines = LineIter::new(text.as_bytes(), &parser).peekable();
+            let mut lines = LineIter::new(text.as_bytes(), &parser);
             let unit = Unit::default();
 
-            assert_eq!(lines.parse_msg("msgid", &unit), Ok(Some(String::from("this is good"))));
+            assert_eq!(
+                lines.parse_msg("msgid", &unit),
+                Ok(Some((
+                    String::from("this is good"),
+                    Span::new(Position::new(0, 1, 1), Position::new(26, 3, 8))
+                )))
+            );
         }
 
         {
             let text = "msgid \"this";
-            let mut lines = LineIter::new(text.as_bytes(), &parser).peekable();
+            let mut lines = LineIter::new(text.as_bytes(), &parser);
             let unit = Unit::default();
 
             match lines.parse_msg("msgid", &unit) {
-                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 2, got ‘msgid \"this’"),
+                Err(err) => assert_eq!(
+                    format!("{:?}", err),
+                    "Parse error at line 1, column 1, got ‘msgid \"this’"
+                ),
                 v => panic!("Unexpected result for the first error: {:?}", v),
             }
         }
 
         {
             let text = "msgid \"this\"\n\" is bad";
-            let mut lines = LineIter::new(text.as_bytes(), &parser).peekable();
+            let mut lines = LineIter::new(text.as_bytes(), &parser);
             let unit = Unit::default();
 
             match lines.parse_msg("msgid", &unit) {
-                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 3, got ‘\" is bad’"),
+                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 2, column 1, got ‘\" is bad’"),
                 v => panic!("Unexpected result for the second error: {:?}", v),
             }
         }
@@ -310,31 +360,34 @@ pub(crate) mod tests {
 
         {
             let text = "   ";
-            let mut lines = LineIter::new(text.as_bytes(), &parser).peekable();
+            let mut lines = LineIter::new(text.as_bytes(), &parser);
 
-            assert_eq!(lines.expected(""), Ok(()));
+            assert_eq!(lines.expected(ErrorKind::Parse, ""), Ok(()));
 
             lines.next();
-            assert_eq!(lines.expected(""), Ok(()));
+            assert_eq!(lines.expected(ErrorKind::Parse, ""), Ok(()));
         }
 
         {
             let text = "---";
-            let mut lines = LineIter::new(text.as_bytes(), &parser).peekable();
+            let mut lines = LineIter::new(text.as_bytes(), &parser);
 
-            match lines.expected("") {
-                Err(err) => assert_eq!(format!("{:?}", err), String::from("Parse error at line 2, got ‘---’")),
+            match lines.expected(ErrorKind::Parse, "") {
+                Err(err) => assert_eq!(
+                    format!("{:?}", err),
+                    String::from("Parse error at line 1, column 1, got ‘---’")
+                ),
                 r => panic!("Unexpected result: {:?}", r),
             }
         }
 
         {
             let text = "# this is a test\nmsgid \"hello,\"\n\"it's me\"";
-            let mut lines = LineIter::new(text.as_bytes(), &parser).peekable();
+            let mut lines = LineIter::new(text.as_bytes(), &parser);
 
-            match lines.expected("here-1") {
+            match lines.expected(ErrorKind::Parse, "here-1") {
                 Err(err) => {
-                    let msg = String::from("Parse error at line 1 expected ‘here-1’, got ‘# ’");
+                    let msg = String::from("Parse error at line 1, column 1 expected ‘here-1’, got ‘# ’");
 
                     assert_eq!(format!("{:?}", err), msg);
                 }
@@ -342,9 +395,9 @@ pub(crate) mod tests {
             }
 
             lines.next();
-            match lines.expected("here-2") {
+            match lines.expected(ErrorKind::Parse, "here-2") {
                 Err(err) => {
-                    let msg = String::from("Parse error at line 2 expected ‘here-2’");
+                    let msg = String::from("Parse error at line 2, column 1 expected ‘here-2’");
 
                     assert_eq!(format!("{:?}", err), msg);
                 }
@@ -352,9 +405,9 @@ pub(crate) mod tests {
             }
 
             lines.next();
-            match lines.expected("here-3") {
+            match lines.expected(ErrorKind::Parse, "here-3") {
                 Err(err) => {
-                    let msg = String::from("Parse error at line 3 expected ‘here-3’, got ‘\"’");
+                    let msg = String::from("Parse error at line 3, column 1 expected ‘here-3’, got ‘\"’");
 
                     assert_eq!(format!("{:?}", err), msg);
                 }
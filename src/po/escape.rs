@@ -0,0 +1,48 @@
+/// Escape a string so it can be written between double quotes in a PO file.
+///
+/// It is the reverse operation of the [`Unescaper`](super::unescape::Unescaper).
+pub(super) fn escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            '\x07' => res.push_str("\\a"),
+            '\x08' => res.push_str("\\b"),
+            '\x0b' => res.push_str("\\v"),
+            '\x0c' => res.push_str("\\f"),
+            _ => res.push(ch),
+        }
+    }
+
+    res
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::{super::unescape::Unescaper, *};
+    use encoding_rs::UTF_8;
+
+    #[test]
+    fn test_func_escape() {
+        assert_eq!(escape("Hello\nworld\r\n\t!"), String::from(r"Hello\nworld\r\n\t!"));
+        assert_eq!(escape("Sub\"\tstring"), String::from(r#"Sub\"\tstring"#));
+        assert_eq!(escape("My\\Path: \tValue"), String::from(r"My\\Path: \tValue"));
+        assert_eq!(escape("Déjà vu"), String::from("Déjà vu"));
+        assert_eq!(escape("\x07\x08\x0b\x0c"), String::from(r"\a\b\v\f"));
+    }
+
+    #[test]
+    fn test_reverse() {
+        let unesc = Unescaper::new();
+        let text = "Some \"quoted\" text\\\nwith\ttabs\r\n\x07\x0c";
+
+        assert_eq!(unesc.unescape(&escape(text), UTF_8), Ok(String::from(text)));
+    }
+}
+// no-coverage:stop
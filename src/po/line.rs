@@ -1,5 +1,6 @@
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub(super) enum PoLine {
+    #[default]
     Blank,
 
     // (line number, kind (translator is space), content of the comment)
@@ -23,12 +24,6 @@ impl PoLine {
     }
 }
 
-impl Default for PoLine {
-    fn default() -> Self {
-        Self::Blank
-    }
-}
-
 // no-coverage:start
 #[cfg(test)]
 mod tests {
@@ -1,52 +1,295 @@
 use super::{line::PoLine, PoParser};
-use crate::error::Error;
-use std::io::{BufRead, BufReader, Lines, Read};
+use crate::{
+    charset::{decode, find_encoding, parse_charset},
+    error::{Error, ErrorKind},
+    span::{Position, Span},
+};
+use encoding_rs::{Encoding, UTF_8};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Error as IoError, Read},
+};
 
 pub(super) struct LineIter<'p, R: Read> {
     n: usize,
-    inner: Option<Lines<BufReader<R>>>,
+    offset: usize,
+    line_offset: usize,
+    spans: VecDeque<(usize, Span)>,
+    inner: Option<BufReader<R>>,
     parser: &'p PoParser,
+    peeked: Option<Option<Result<PoLine, Error>>>,
+    source: Option<Vec<(usize, String)>>,
+    pending: VecDeque<Result<Vec<u8>, IoError>>,
+    encoding: &'static Encoding,
+    error: Option<Error>,
+    after_blank: bool,
+    blank_seen: bool,
 }
 
 impl<'p, R: Read> LineIter<'p, R> {
     pub(super) fn new(r: R, parser: &'p PoParser) -> Self {
-        Self {
+        let mut res = Self {
             n: 1,
-            inner: Some(BufReader::new(r).lines()),
+            offset: 0,
+            line_offset: 0,
+            spans: VecDeque::new(),
+            inner: Some(BufReader::new(r)),
             parser,
+            peeked: None,
+            source: if parser.is_lossless() { Some(vec![]) } else { None },
+            pending: VecDeque::new(),
+            encoding: UTF_8,
+            error: None,
+            after_blank: false,
+            blank_seen: false,
+        };
+
+        res.detect_encoding();
+        res
+    }
+
+    /// Returns a reference to the next line without consuming it.
+    pub(super) fn peek(&mut self) -> Option<&Result<PoLine, Error>> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_next());
         }
+
+        self.peeked.as_ref().and_then(Option::as_ref)
     }
-}
 
-impl<'p, R: Read> Iterator for LineIter<'p, R> {
-    type Item = Result<PoLine, Error>;
+    /// Take the original text of the lines read before the line `end` (all lines read if `None`).
+    ///
+    /// The text is only kept in lossless mode, otherwise `None` is returned.
+    pub(super) fn take_source(&mut self, end: Option<usize>) -> Option<String> {
+        self.source.as_mut().map(|lines| {
+            let count = end.map_or(lines.len(), |end| lines.iter().take_while(|(n, _)| *n < end).count());
 
-    fn next(&mut self) -> Option<Result<PoLine, Error>> {
-        while let Some(reader) = self.inner.as_mut() {
-            let n = self.n;
-            let line = match reader.next() {
-                Some(Ok(s)) => s,
-                Some(Err(e)) => {
-                    self.inner = None;
-
-                    return Some(Err(Error::Io(n, e)));
-                    // no-coverage:start
+            lines.drain(..count).map(|(_, line)| line).collect()
+        })
+    }
+
+    /// Returns the span of the text of the line `n`, without the surrounding spaces.
+    ///
+    /// Spans are only kept for the lines which are not blank, up to a call to [`forget_spans`](Self::forget_spans).
+    pub(super) fn line_span(&self, n: usize) -> Option<Span> {
+        self.spans
+            .iter()
+            .rev()
+            .find(|(line, _)| *line == n)
+            .map(|(_, span)| *span)
+    }
+
+    /// Forget the spans of the lines before the line `start`.
+    pub(super) fn forget_spans(&mut self, start: usize) {
+        while self.spans.front().is_some_and(|(n, _)| *n < start) {
+            self.spans.pop_front();
+        }
+    }
+
+    /// Position of the character at the byte index `idx` in the line `n`, which begins at the offset `offset` of the
+    /// stream. The byte offset is counted in the encoding of the stream.
+    fn position_at(&self, n: usize, offset: usize, line: &str, idx: usize) -> Position {
+        let prefix = &line[..idx];
+        let len = if self.encoding == UTF_8 {
+            idx
+        } else {
+            self.encoding.encode(prefix).0.len()
+        };
+
+        Position::new(offset + len, n, prefix.chars().count() + 1)
+    }
+
+    /// Read ahead the lines of the first entry, and use the charset declared in the `Content-Type` property if this
+    /// entry is the header. Lines are decoded as UTF-8 when there is no header.
+    fn detect_encoding(&mut self) {
+        let mut is_header = None;
+        let mut charset = None;
+
+        while let Some(bytes) = self.read_inner() {
+            let bytes = match bytes {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    self.pending.push_back(Err(err));
+                    break;
                 }
-                // no-coverage:stop
-                None => {
-                    return None;
+            };
+
+            let text = String::from_utf8_lossy(&bytes);
+            let line = text.trim();
+
+            if line.is_empty() && is_header.is_some() {
+                self.pending.push_back(Ok(bytes));
+                break;
+            }
+
+            if !(line.is_empty() || line.starts_with('#') || is_header.is_some()) {
+                is_header = Some(line.strip_prefix("msgid").map(str::trim) == Some("\"\""));
+            }
+
+            if let Some(pos) = text.find("Content-Type:") {
+                charset = parse_charset(&text[pos..]).map(|c| (self.pending.len() + 1, c.to_string()));
+            }
+
+            self.pending.push_back(Ok(bytes));
+        }
+
+        if let (Some(true), Some((n, charset))) = (is_header, charset) {
+            match find_encoding(&charset) {
+                Ok(encoding) => self.encoding = encoding,
+                Err(msg) => self.error = Some(Error::new(ErrorKind::Encoding, msg).with_line(n)),
+            }
+        }
+    }
+
+    fn read_raw(&mut self) -> Option<Result<Vec<u8>, IoError>> {
+        match self.pending.pop_front() {
+            Some(bytes) => Some(bytes),
+            None => self.read_inner(),
+        }
+    }
+
+    fn read_inner(&mut self) -> Option<Result<Vec<u8>, IoError>> {
+        let reader = self.inner.as_mut()?;
+        let mut bytes = vec![];
+
+        match reader.read_until(b'\n', &mut bytes) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(bytes)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Returns whether the next line is an error which belongs to the next entry, in lenient mode.
+    ///
+    /// The current entry then ends before this line, the error is given when reading the next entry.
+    pub(super) fn is_next_entry_error(&self) -> bool {
+        self.parser.is_lenient() && self.after_blank && matches!(self.peeked, Some(Some(Err(_))))
+    }
+
+    /// Skip the lines up to the next blank line, for resuming the reading after an error in lenient mode.
+    ///
+    /// Nothing is skipped if the next line already begins a new entry, unless this entry is the one in error, which
+    /// begins at the line `start`.
+    pub(super) fn skip_entry(&mut self, start: Option<usize>) {
+        match self.peeked.take() {
+            Some(Some(Ok(line))) if self.after_blank && start != Some(line.line()) => {
+                self.peeked = Some(Some(Ok(line)));
+
+                return;
+            }
+            Some(None) => {
+                self.peeked = Some(None);
+
+                return;
+            }
+            _ => (),
+        }
+
+        while let Some(line) = self.next_line() {
+            if let Ok((_, line)) = line {
+                if line.trim().is_empty() {
+                    self.blank_seen = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn stop(&mut self, err: Error) -> Option<Result<PoLine, Error>> {
+        self.inner = None;
+        self.pending.clear();
+
+        Some(Err(err))
+    }
+
+    /// Returns an error, the reading is stopped unless the parser is lenient.
+    fn fail(&mut self, err: Error) -> Option<Result<PoLine, Error>> {
+        if self.parser.is_lenient() {
+            Some(Err(err))
+        } else {
+            self.stop(err)
+        }
+    }
+
+    /// Read and decode the next line, with its number.
+    fn next_line(&mut self) -> Option<Result<(usize, String), Error>> {
+        let n = self.n;
+        let line = match self.read_raw()? {
+            Ok(bytes) => {
+                self.line_offset = self.offset;
+                self.offset += bytes.len();
+
+                decode(self.encoding, &bytes).map(String::from)
+            }
+            Err(e) => {
+                self.inner = None;
+                self.pending.clear();
+
+                return Some(Err(Error::io(n, e)));
+            }
+        };
+
+        self.n += 1;
+
+        Some(match line {
+            Ok(line) => {
+                let start = line.len() - line.trim_start().len();
+
+                if start < line.len() {
+                    let end = line.trim_end().len();
+                    let span = Span::new(
+                        self.position_at(n, self.line_offset, &line, start),
+                        self.position_at(n, self.line_offset, &line, end),
+                    );
+
+                    self.spans.push_back((n, span));
+                }
+
+                if let Some(source) = self.source.as_mut() {
+                    source.push((n, line.clone()));
+                }
+
+                Ok((n, line))
+            }
+            Err(e) => Err(Error::io(n, e)),
+        })
+    }
+
+    fn read_next(&mut self) -> Option<Result<PoLine, Error>> {
+        if let Some(err) = self.error.take() {
+            return self.stop(err);
+        }
+
+        while let Some(line) = self.next_line() {
+            self.after_blank = self.blank_seen;
+            self.blank_seen = false;
+
+            let (n, line) = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    return self.fail(err);
                 }
             };
 
-            self.n += 1;
+            let content = line.strip_suffix('\n').unwrap_or(&line);
+            let content = content.strip_suffix('\r').unwrap_or(content);
 
-            match self.parser.parse_line(&line, n) {
-                Ok(PoLine::Blank) => (),
+            match self.parser.parse_line(content, n, self.encoding) {
+                Ok(PoLine::Blank) => self.blank_seen = true,
                 Ok(p) => return Some(Ok(p)),
-                Err(()) => {
-                    self.inner = None;
+                Err(Some((idx, msg))) => {
+                    let end = content[(idx + 1)..].chars().next().map_or(0, char::len_utf8) + idx + 1;
+                    let span = Span::new(
+                        self.position_at(n, self.line_offset, content, idx),
+                        self.position_at(n, self.line_offset, content, end),
+                    );
+
+                    return self.fail(Error::new(ErrorKind::BadEscape, msg).with_span(span));
+                }
+                Err(None) => {
+                    let span = self.line_span(n).unwrap_or_default();
 
-                    return Some(Err(Error::Parse(self.n, line, String::new())));
+                    return self.fail(Error::parse(ErrorKind::Parse, span, content, ""));
                 }
             }
         }
@@ -55,6 +298,17 @@ impl<'p, R: Read> Iterator for LineIter<'p, R> {
     }
 }
 
+impl<'p, R: Read> Iterator for LineIter<'p, R> {
+    type Item = Result<PoLine, Error>;
+
+    fn next(&mut self) -> Option<Result<PoLine, Error>> {
+        match self.peeked.take() {
+            Some(v) => v,
+            None => self.read_next(),
+        }
+    }
+}
+
 // no-coverage:start
 #[cfg(test)]
 mod tests {
@@ -184,7 +438,7 @@ mod tests {
             Some(Err(err)) => {
                 assert_eq!(
                     format!("{:?}", err),
-                    "Parse error at line 5, got ‘            msgstr \"Line 2’"
+                    "Parse error at line 4, column 13, got ‘            msgstr \"Line 2’"
                 );
             }
             v => panic!("Unexpected result for the third line: {:?}", v),
@@ -195,6 +449,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn with_escape_error() {
+        let lines = "msgid \"Line\\t1\"\nmsgstr \"Line\\d2\"\n";
+        let parser = PoParser::new();
+        let mut iter = LineIter::new(lines.as_bytes(), &parser);
+
+        match iter.next() {
+            Some(Ok(PoLine::Message(_, _, _, string))) => assert_eq!(string, "Line\t1"),
+            v => panic!("Unexpected result for the first line: {:?}", v),
+        }
+
+        match iter.next() {
+            Some(Err(err)) => assert_eq!(
+                format!("{:?}", err),
+                "Syntax error at line 2, column 13: Invalid escape sequence ‘\\d’"
+            ),
+            v => panic!("Unexpected result for the second line: {:?}", v),
+        }
+
+        if let Some(v) = iter.next() {
+            panic!("Unexpected result for the third line: {:?}", v);
+        }
+    }
+
+    #[test]
+    fn test_func_skip_entry() {
+        let lines = "msgid \"a\"\nbad\nmsgstr \"b\"\n\nbad\n\nmsgid \"c\"\nmsgstr \"d\"";
+        let mut parser = PoParser::new();
+
+        parser.set_lenient(true);
+
+        let mut iter = LineIter::new(lines.as_bytes(), &parser);
+
+        assert_eq!(iter.next().map(|r| r.map(|l| l.line()).ok()), Some(Some(1)));
+        assert!(
+            matches!(iter.next(), Some(Err(_))),
+            "The second line should be an error"
+        );
+        assert!(!iter.is_next_entry_error(), "The error is in the current entry");
+
+        iter.skip_entry(Some(1));
+        assert!(matches!(iter.peek(), Some(Err(_))), "The fifth line should be an error");
+        assert!(iter.is_next_entry_error(), "The error is in the next entry");
+
+        iter.skip_entry(None);
+        assert_eq!(iter.peek().map(|r| r.as_ref().map(PoLine::line).ok()), Some(Some(7)));
+
+        iter.skip_entry(Some(5));
+        assert_eq!(iter.peek().map(|r| r.as_ref().map(PoLine::line).ok()), Some(Some(7)));
+
+        iter.skip_entry(Some(7));
+        assert!(iter.next().is_none(), "There should be no more line");
+    }
+
     #[test]
     fn with_io_error() {
         let input = b"ABC\x32\x80\x32";
@@ -209,5 +517,104 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_func_detect_encoding() {
+        let lines = b"\
+            # \xa3\xf3d\xbc\n\
+            msgid \"\"\n\
+            msgstr \"\"\n\
+            \"Content-Type: text/plain; charset=ISO-8859-2\\n\"\n\
+            \n\
+            msgid \"\xbf\"\
+        ";
+        let parser = PoParser::new();
+        let iter = LineIter::new(&lines[..], &parser);
+        let strings: Vec<_> = iter
+            .map(|line| match line {
+                Ok(PoLine::Comment(_, _, s) | PoLine::Message(.., s) | PoLine::Continuation(_, _, s)) => s,
+                r => panic!("Unexpected line: {:?}", r),
+            })
+            .collect();
+
+        assert_eq!(
+            strings,
+            vec!["Łódź", "", "", "Content-Type: text/plain; charset=ISO-8859-2\n", "ż"]
+        );
+
+        let lines = b"msgid \"Content-Type: charset=ISO-8859-2\"\nmsgstr \"\xbf\"";
+        let mut iter = LineIter::new(&lines[..], &parser);
+
+        iter.next();
+        match iter.next() {
+            Some(Err(err)) => {
+                assert_eq!(err.kind(), ErrorKind::Encoding);
+                assert_eq!(err.line(), 2);
+                assert_eq!(err.message(), "Invalid UTF-8 sequence");
+            }
+            v => panic!("Unexpected result without header: {:?}", v),
+        }
+
+        let lines = b"msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-16\\n\"\n";
+        let mut iter = LineIter::new(&lines[..], &parser);
+
+        match iter.next() {
+            Some(Err(err)) => assert_eq!(
+                format!("{:?}", err),
+                "Encoding error at line 3: Unsupported charset ‘UTF-16’"
+            ),
+            v => panic!("Unexpected result for an unsupported charset: {:?}", v),
+        }
+
+        assert!(iter.next().is_none(), "There should be no more line");
+    }
+
+    #[test]
+    fn test_func_peek() {
+        let lines = "msgid \"Line 1\"\n\nmsgstr \"Line 2\"";
+        let parser = PoParser::new();
+        let mut iter = LineIter::new(lines.as_bytes(), &parser);
+
+        assert_eq!(iter.peek().map(|r| r.as_ref().map(PoLine::line).ok()), Some(Some(1)));
+        assert_eq!(iter.peek().map(|r| r.as_ref().map(PoLine::line).ok()), Some(Some(1)));
+        assert_eq!(iter.next().map(|r| r.map(|l| l.line()).ok()), Some(Some(1)));
+        assert_eq!(iter.peek().map(|r| r.as_ref().map(PoLine::line).ok()), Some(Some(3)));
+        assert_eq!(iter.next().map(|r| r.map(|l| l.line()).ok()), Some(Some(3)));
+        assert!(iter.peek().is_none(), "There should be no more line");
+        assert!(iter.next().is_none(), "There should be no more line");
+    }
+
+    #[test]
+    fn test_func_take_source() {
+        let lines = "# Comment\r\nmsgid \"Line 1\"\n\n  \nmsgstr \"Line 2\"";
+        let mut parser = PoParser::new();
+
+        {
+            let mut iter = LineIter::new(lines.as_bytes(), &parser);
+
+            while iter.next().is_some() {}
+            assert_eq!(iter.take_source(None), None);
+        }
+
+        parser.set_lossless(true);
+
+        let mut iter = LineIter::new(lines.as_bytes(), &parser);
+
+        match iter.next() {
+            Some(Ok(PoLine::Comment(1, ' ', content))) => assert_eq!(content, "Comment"),
+            v => panic!("Unexpected result for the first line: {:?}", v),
+        }
+
+        iter.next();
+        iter.peek();
+
+        assert_eq!(iter.take_source(Some(2)), Some(String::from("# Comment\r\n")));
+        assert_eq!(
+            iter.take_source(Some(5)),
+            Some(String::from("msgid \"Line 1\"\n\n  \n"))
+        );
+        assert_eq!(iter.take_source(None), Some(String::from("msgstr \"Line 2\"")));
+        assert_eq!(iter.take_source(None), Some(String::new()));
+    }
 }
 // no-coverage:stop
@@ -1,6 +1,6 @@
 use super::Decoder;
 use crate::{
-    error::Error,
+    error::{Error, ErrorKind},
     plural::{Plural, PluralForms},
     unit::Unit,
     Message,
@@ -43,7 +43,7 @@ impl<'r, D: Decoder> MessageExtractor<'r, D> {
         let msgid = self.parse_msg("msgid")?;
 
         if (!first) && msgid.is_none() {
-            self.expected("msgid")?;
+            self.expected(ErrorKind::MissingMsgid, "msgid")?;
 
             return Ok(None);
         }
@@ -86,7 +86,7 @@ impl<'r, D: Decoder> MessageExtractor<'r, D> {
                     }
 
                     return Ok(if values.is_empty() {
-                        self.expected("msgstr[0]")?;
+                        self.expected(ErrorKind::MissingMsgstr, "msgstr[0]")?;
 
                         None
                     } else {
@@ -102,7 +102,7 @@ impl<'r, D: Decoder> MessageExtractor<'r, D> {
                 text: if text.is_empty() { None } else { Some(text) },
             }),
             None => {
-                self.expected("msgstr")?;
+                self.expected(ErrorKind::MissingMsgstr, "msgstr")?;
 
                 None
             }
@@ -123,11 +123,15 @@ impl<'r, D: Decoder> MessageExtractor<'r, D> {
     }
 
     fn parse_msg(&mut self, tag: &str) -> Result<Option<String>, Error> {
-        self.decoder.parse_msg(tag, &self.unit)
+        Ok(self.decoder.parse_msg(tag, &self.unit)?.map(|(string, span)| {
+            self.unit.field_spans.insert(tag.to_string(), span);
+
+            string
+        }))
     }
 
-    fn expected(&mut self, exp: &str) -> Result<(), Error> {
-        self.decoder.expected(exp)
+    fn expected(&mut self, kind: ErrorKind, exp: &str) -> Result<(), Error> {
+        self.decoder.expected(kind, exp)
     }
 
     fn plural_forms(&self) -> Option<Rc<PluralForms>> {
@@ -142,8 +146,8 @@ mod tests {
         super::decoder::tests::{TestAction::*, TestDecoder},
         *,
     };
+    use crate::location::Location;
 
-    use crate::error::Error;
 
     impl<'r, D: Decoder> MessageExtractor<'r, D> {
         pub(crate) fn for_tests_zero(decoder: &'r mut D) -> Self {
@@ -181,7 +185,7 @@ mod tests {
 
     #[test]
     fn test_func_parse_message_fields_with_errors_on_new_message() {
-        let err = Error::Unexpected(123, String::from("Error"));
+        let err = Error::new(ErrorKind::Other, "Error").with_line(123);
         let err_msg = format!("{:?}", err);
         let mut decoder = TestDecoder::with_values([("msgid", ActOk("Something")), ("msgid_plural", ActErr(err))]);
 
@@ -195,7 +199,7 @@ mod tests {
 
     #[test]
     fn test_func_parse_message_fields_with_errors_on_expected() {
-        let err = Error::Unexpected(123, String::from("Error"));
+        let err = Error::new(ErrorKind::Other, "Error").with_line(123);
         let err_msg = format!("{:?}", err);
         let mut decoder = TestDecoder::with_values([("msgid", ActDelayed(err))]);
 
@@ -211,11 +215,11 @@ mod tests {
 
     #[test]
     fn test_func_parse_message_fields_with_errors_on_parse_msg() {
-        let err_msg = format!("{:?}", Error::Unexpected(123, String::from("Error")));
+        let err_msg = format!("{:?}", Error::new(ErrorKind::Other, "Error").with_line(123));
         let mut decoder = TestDecoder::new();
 
         {
-            let err = Error::Unexpected(123, String::from("Error"));
+            let err = Error::new(ErrorKind::Other, "Error").with_line(123);
 
             decoder.push_values([("|msgctxt", ActErr(err))]);
 
@@ -228,7 +232,7 @@ mod tests {
         }
 
         {
-            let err = Error::Unexpected(123, String::from("Error"));
+            let err = Error::new(ErrorKind::Other, "Error").with_line(123);
 
             decoder.push_values([("|msgid", ActErr(err))]);
 
@@ -241,7 +245,7 @@ mod tests {
         }
 
         {
-            let err = Error::Unexpected(123, String::from("Error"));
+            let err = Error::new(ErrorKind::Other, "Error").with_line(123);
 
             decoder.push_values([("|msgid", ActOk("Something")), ("|msgid_plural", ActErr(err))]);
 
@@ -254,7 +258,7 @@ mod tests {
         }
 
         {
-            let err = Error::Unexpected(123, String::from("Error"));
+            let err = Error::new(ErrorKind::Other, "Error").with_line(123);
 
             decoder.push_values([("msgctxt", ActErr(err))]);
 
@@ -267,7 +271,7 @@ mod tests {
         }
 
         {
-            let err = Error::Unexpected(123, String::from("Error"));
+            let err = Error::new(ErrorKind::Other, "Error").with_line(123);
 
             decoder.push_values([("msgid", ActErr(err))]);
 
@@ -280,7 +284,7 @@ mod tests {
         }
 
         {
-            let err = Error::Unexpected(123, String::from("Error"));
+            let err = Error::new(ErrorKind::Other, "Error").with_line(123);
 
             decoder.push_values([("msgid", ActOk("Something")), ("msgid_plural", ActErr(err))]);
 
@@ -428,9 +432,9 @@ mod tests {
                 assert_eq!(
                     msg.unit.locations,
                     vec![
-                        String::from("EmptyFile1:11"),
-                        String::from("EmptyFile2:22"),
-                        String::from("EmptyFile3:33"),
+                        Location::new("EmptyFile1", Some(11)),
+                        Location::new("EmptyFile2", Some(22)),
+                        Location::new("EmptyFile3", Some(33)),
                     ]
                 );
 
@@ -466,7 +470,7 @@ mod tests {
 
         let estr = "An error";
 
-        msg.decoder.set_error(Error::Unexpected(123, estr.to_string()));
+        msg.decoder.set_error(Error::new(ErrorKind::Other, estr).with_line(123));
         match msg.parse_msg("tag4") {
             Err(err) => {
                 assert_eq!(format!("{}", err), format!("Unexpected error at line 123: {}", estr));
@@ -480,7 +484,7 @@ mod tests {
         let mut decoder = TestDecoder::new();
         let mut msg = MessageExtractor::for_tests_zero(&mut decoder);
 
-        match msg.expected("---") {
+        match msg.expected(ErrorKind::Parse, "---") {
             Ok(()) => assert!(msg.decoder.log().is_empty(), "Decoder log should be empty"),
             r => panic!("Bad result for first call of `expected`: {:?}", r),
         }
@@ -488,7 +492,7 @@ mod tests {
         let mut log = vec![String::from("Expected: message-1")];
 
         msg.decoder.inc();
-        match msg.expected("message-1") {
+        match msg.expected(ErrorKind::Parse, "message-1") {
             Ok(()) => assert_eq!(msg.decoder.log(), &log),
             r => panic!("Bad result for second call of `expected`: {:?}", r),
         }
@@ -497,8 +501,8 @@ mod tests {
 
         log.push(String::from("Expected: message-2"));
         msg.decoder.inc();
-        msg.decoder.set_error(Error::Unexpected(123, estr.to_string()));
-        match msg.expected("message-2") {
+        msg.decoder.set_error(Error::new(ErrorKind::Other, estr).with_line(123));
+        match msg.expected(ErrorKind::Parse, "message-2") {
             Err(err) => {
                 assert_eq!(format!("{}", err), format!("Unexpected error at line 123: {}", estr));
                 assert_eq!(msg.decoder.log(), &log);
@@ -614,7 +618,7 @@ mod tests {
     #[test]
     fn test_func_new_message_for_error_from_singular() {
         let mut decoder = TestDecoder::new();
-        let err = Error::Unexpected(123, String::from("But this is expected"));
+        let err = Error::new(ErrorKind::Other, "But this is expected").with_line(123);
         let err_msg = format!("{:?}", err);
 
         decoder.set_error(err);
@@ -630,7 +634,7 @@ mod tests {
     #[test]
     fn test_func_new_message_for_error_from_plural_on_parse() {
         let mut decoder = TestDecoder::new();
-        let err = Error::Unexpected(123, String::from("But this is expected"));
+        let err = Error::new(ErrorKind::Other, "But this is expected").with_line(123);
         let err_msg = format!("{:?}", err);
 
         decoder.set_error(err);
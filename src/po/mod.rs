@@ -11,13 +11,15 @@
 //! [tt]: http://toolkit.translatehouse.org/
 
 mod decoder;
+mod escape;
 mod line;
 mod line_iter;
 mod message_extractor;
 mod parser;
 mod reader;
 mod unescape;
+mod writer;
 
-pub use self::{parser::PoParser, reader::PoReader};
+pub use self::{parser::PoParser, reader::PoReader, writer::PoWriter};
 
 pub(super) use self::{decoder::Decoder, message_extractor::MessageExtractor};
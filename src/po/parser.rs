@@ -1,5 +1,6 @@
 use super::{line::PoLine, reader::PoReader, unescape::Unescaper};
-use crate::error::Error;
+use crate::error::{Error, ErrorKind};
+use encoding_rs::Encoding;
 use regex::Regex;
 use std::{collections::HashMap, io::Read};
 
@@ -14,6 +15,8 @@ pub struct PoParser {
     message_re: Regex,
     comment_re: Regex,
     unescaper: Unescaper,
+    lossless: bool,
+    lenient: bool,
 }
 
 impl PoParser {
@@ -27,12 +30,42 @@ impl PoParser {
             .unwrap(),
             comment_re: Regex::new(r#"^\s*#(.)?\s*(.*)$"#).unwrap(),
             unescaper: Unescaper::new(),
+            lossless: false,
+            lenient: false,
             // no-coverage:start
         }
         // no-coverage:stop
     }
 
-    pub fn parse<R: Read>(&self, reader: R) -> Result<PoReader<R>, Error> {
+    /// Enable or disable the lossless mode.
+    ///
+    /// In this mode, the readers keep the original text of each unit and of the header, including blank lines,
+    /// comment order and line splits of strings. A [`PoWriter`](super::PoWriter) writes back this text as is for
+    /// units which have not been modified, so an unchanged catalogue is written byte for byte.
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
+    /// Returns whether the lossless mode is enabled.
+    pub fn is_lossless(&self) -> bool {
+        self.lossless
+    }
+
+    /// Enable or disable the lenient mode.
+    ///
+    /// In this mode, the readers do not stop on the first error: the entry in error is skipped up to the next blank
+    /// line, the error is recorded as a diagnostic, and the reading goes on with the next entries. Only I/O errors
+    /// stop the reading. Diagnostics are given by [`PoReader::diagnostics`](super::PoReader::diagnostics).
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Returns whether the lenient mode is enabled.
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    pub fn parse<R: Read>(&self, reader: R) -> Result<PoReader<'_, R>, Error> {
         PoReader::new(reader, self)
     }
 
@@ -44,17 +77,32 @@ impl PoParser {
                 .filter_map(|c| c.get(1).and_then(|v1| c.get(2).map(|v2| (v1.as_str(), v2.as_str()))))
                 .collect())
         } else {
-            Err(Error::Unexpected(0, format!("Bad value list definition: `{}`", text)))
+            Err(Error::new(
+                ErrorKind::Other,
+                format!("Bad value list definition: `{}`", text),
+            ))
         }
     }
 
-    pub(super) fn parse_line(&self, line: &str, n: usize) -> Result<PoLine, ()> {
+    /// Parse a line, the error is `None` if the line has no known syntax.
+    ///
+    /// Strings are unescaped, an invalid escape sequence is an error with its byte offset in the line.
+    pub(super) fn parse_line(
+        &self,
+        line: &str,
+        n: usize,
+        encoding: &'static Encoding,
+    ) -> Result<PoLine, Option<(usize, String)>> {
         if !line.contains(|c: char| !c.is_whitespace()) {
             Ok(PoLine::Blank)
         } else if let Some(c) = self.message_re.captures(line) {
-            let string = self
-                .unescaper
-                .unescape(c.get(3).map(|m| m.as_str()).unwrap_or_default());
+            let string = match c.get(3) {
+                Some(m) => self
+                    .unescaper
+                    .unescape(m.as_str(), encoding)
+                    .map_err(|(offset, msg)| Some((m.start() + offset, msg)))?,
+                None => String::new(),
+            };
             let flags = c.get(1).map(|x| x.as_str().to_string()).unwrap_or_default();
 
             Ok(match c.get(2) {
@@ -70,7 +118,7 @@ impl PoParser {
                 }
             })
         } else {
-            self.comment_re.captures(line).map_or(Err(()), |c| {
+            self.comment_re.captures(line).map_or(Err(None), |c| {
                 Ok(PoLine::Comment(
                     n,
                     c.get(1).and_then(|m| m.as_str().chars().next()).unwrap_or(' '),
@@ -81,20 +129,27 @@ impl PoParser {
     }
 }
 
+impl Default for PoParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // no-coverage:start
 #[cfg(test)]
 mod tests {
     use super::*;
+    use encoding_rs::UTF_8;
 
     struct TestCase {
         source: &'static str,
-        target: Result<PoLine, ()>,
+        target: Result<PoLine, Option<(usize, String)>>,
     }
 
     impl TestCase {
         fn test(&self, parser: &PoParser) {
             assert_eq!(
-                parser.parse_line(self.source, 123),
+                parser.parse_line(self.source, 123, UTF_8),
                 self.target,
                 "Error for source: `{}`",
                 self.source
@@ -102,6 +157,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_func_set_lossless() {
+        let mut parser = PoParser::new();
+
+        assert!(!parser.is_lossless(), "Parser should not be lossless by default");
+
+        parser.set_lossless(true);
+        assert!(parser.is_lossless(), "Parser should be lossless");
+    }
+
+    #[test]
+    fn test_func_set_lenient() {
+        let mut parser = PoParser::new();
+
+        assert!(!parser.is_lenient(), "Parser should not be lenient by default");
+
+        parser.set_lenient(true);
+        assert!(parser.is_lenient(), "Parser should be lenient");
+    }
+
     #[test]
     fn test_func_parse_map() {
         let parser = PoParser::new();
@@ -128,23 +203,23 @@ mod tests {
         let cases = vec![
             TestCase {
                 source: "---",
-                target: Err(()),
+                target: Err(None),
             },
             TestCase {
                 source: "\"--",
-                target: Err(()),
+                target: Err(None),
             },
             TestCase {
                 source: "msgid \"--",
-                target: Err(()),
+                target: Err(None),
             },
             TestCase {
                 source: "msgxx \"--\"",
-                target: Err(()),
+                target: Err(None),
             },
             TestCase {
                 source: "-# Something",
-                target: Err(()),
+                target: Err(None),
             },
             TestCase {
                 source: "",
@@ -188,6 +263,19 @@ mod tests {
                     String::from("path: xx\\yy"),
                 )),
             },
+            TestCase {
+                source: r#"msgid "a\ab\x41\101""#,
+                target: Ok(PoLine::Message(
+                    123,
+                    String::new(),
+                    String::from("msgid"),
+                    String::from("a\x07bAA"),
+                )),
+            },
+            TestCase {
+                source: r#"  msgstr "Déjà\z""#,
+                target: Err(Some((16, String::from("Invalid escape sequence ‘\\z’")))),
+            },
             TestCase {
                 source: "#, My comment",
                 target: Ok(PoLine::Comment(123, ',', String::from("My comment"))),
@@ -1,26 +1,36 @@
 use super::{line::PoLine, line_iter::LineIter, parser::PoParser, MessageExtractor as Extractor};
 use crate::{
-    comment::Comment, error::Error, note::Note, plural::PluralForms, unit::Unit, CatalogueReader, Origin, State,
+    comment::Comment,
+    error::{Error, ErrorKind},
+    flag::Flag,
+    header::{parse_language, parse_properties},
+    location::Location,
+    note::Note,
+    plural::PluralForms,
+    unit::Unit,
+    CatalogueReader, Origin, State,
 };
 
 use locale_config::LanguageRange;
-use std::{
-    collections::HashMap,
-    io::Read,
-    iter::Peekable,
-    mem::{replace, swap},
-    rc::Rc,
-};
+use std::{collections::HashMap, io::Read, mem::swap, rc::Rc};
 
 /// Object for reading PO streams
 ///
 /// An iterator is implemented for reading each unit of translation in the PO stream.
+///
+/// With a lenient parser, entries in error are skipped and only valid units are given by the iterator, the errors
+/// are then given by [`diagnostics`](Self::diagnostics). The iterator should be borrowed (with
+/// [`by_ref`](Iterator::by_ref) for instance) to get them at the end.
 pub struct PoReader<'p, R: Read> {
-    lines: Peekable<LineIter<'p, R>>,
+    lines: LineIter<'p, R>,
+    lenient: bool,
+    diagnostics: Vec<Error>,
+    unit_start: Option<usize>,
     next_unit: Option<Result<Unit, Error>>,
     header_notes: Vec<Note>,
     header_comments: Vec<Comment>,
     header_properties: HashMap<String, String>,
+    header_source: Option<String>,
     target_language: LanguageRange<'static>,
     plural_forms: Option<Rc<PluralForms>>,
 }
@@ -28,11 +38,15 @@ pub struct PoReader<'p, R: Read> {
 impl<'p, R: Read> PoReader<'p, R> {
     pub(super) fn new(reader: R, parser: &'p PoParser) -> Result<PoReader<'p, R>, Error> {
         let mut res = PoReader {
-            lines: LineIter::new(reader, parser).peekable(),
+            lines: LineIter::new(reader, parser),
+            lenient: parser.is_lenient(),
+            diagnostics: vec![],
+            unit_start: None,
             next_unit: None,
             header_notes: vec![],
             header_comments: vec![],
             header_properties: HashMap::new(),
+            header_source: None,
             target_language: LanguageRange::invariant(),
             plural_forms: None,
             // no-coverage:start
@@ -53,13 +67,25 @@ impl<'p, R: Read> PoReader<'p, R> {
 
         res.next_unit = next_unit;
         if has_header {
-            res.parse_po_header(parser)?;
+            if let Err(err) = res.parse_po_header(parser) {
+                if !res.lenient {
+                    return Err(err);
+                }
+
+                res.diagnostics.push(err);
+            }
+
             res.next_unit = res.next_unit(false);
         }
 
         Ok(res)
     }
 
+    /// Returns the errors met while reading with a lenient parser, in the order of the stream.
+    pub fn diagnostics(&self) -> &Vec<Error> {
+        &self.diagnostics
+    }
+
     fn read_line(&mut self) -> Result<Option<(usize, bool)>, Error> {
         match self.lines.peek() {
             // end if no unit (possibly after comments)
@@ -67,7 +93,7 @@ impl<'p, R: Read> PoReader<'p, R> {
 
             // error
             Some(Err(_)) => {
-                if let Some(Err(err)) = replace(&mut self.next_unit, None) {
+                if let Some(Err(err)) = self.next_unit.take() {
                     Err(err)
                 } else if let Some(Err(err)) = self.lines.next() {
                     Err(err)
@@ -85,21 +111,23 @@ impl<'p, R: Read> PoReader<'p, R> {
     }
 
     fn parse_comments(&mut self, unit: &mut Unit) -> Result<(), Error> {
-        while let Some(Ok(PoLine::Comment(..))) = self.lines.peek() {
+        while let Some(Ok(PoLine::Comment(n, ..))) = self.lines.peek() {
+            let n = *n;
+
+            unit.comment_spans.extend(self.lines.line_span(n));
+
             match self.lines.next() {
                 Some(Ok(PoLine::Comment(_, ',', s))) => {
-                    for flag in s.split(',').map(str::trim) {
-                        unit.flags.insert(flag.to_string());
-
-                        match flag {
-                            "fuzzy" => unit.state = State::NeedsWork,
-                            _ => (), // TODO: Implement other flags (do we need any?)
+                    for flag in s.split(',').filter(|f| !f.trim().is_empty()).map(Flag::parse) {
+                        if flag == Flag::Fuzzy {
+                            unit.state = State::NeedsWork;
                         }
+
+                        unit.flags.insert(flag);
                     }
                 }
                 Some(Ok(PoLine::Comment(_, ':', s))) => {
-                    unit.locations
-                        .extend(s.split(char::is_whitespace).filter(|x| !x.is_empty()).map(From::from));
+                    unit.locations.extend(Location::parse_list(&s));
                 }
                 Some(Ok(PoLine::Comment(_, '.', value))) => {
                     unit.notes.push(Note::new(Origin::Developer, value));
@@ -135,6 +163,15 @@ impl<'p, R: Read> PoReader<'p, R> {
     fn read_unit(&mut self, first: bool) -> Result<Option<Unit>, Error> {
         let mut unit = Unit::default();
 
+        self.unit_start = match self.lines.peek() {
+            Some(Ok(line)) => Some(line.line()),
+            _ => None,
+        };
+
+        if let Some(start) = self.unit_start {
+            self.lines.forget_spans(start);
+        }
+
         self.parse_comments(&mut unit)?;
 
         let line = match self.read_line()? {
@@ -154,8 +191,27 @@ impl<'p, R: Read> PoReader<'p, R> {
             }
         };
 
+        let end = match self.lines.peek() {
+            Some(Ok(next)) => Some(next.line()),
+            _ => None,
+        };
+
+        unit.source = self.lines.take_source(end);
+        unit.span = match (
+            self.unit_start.and_then(|n| self.lines.line_span(n)),
+            unit.field_spans.values().max_by_key(|s| s.end()),
+        ) {
+            (Some(start), Some(last)) => Some(start.to(last)),
+            _ => None,
+        };
+
         if (!first) && unit.message.is_empty() {
-            Err(Error::Unexpected(line, String::from("Source should not be empty")))
+            let msg = String::from("Source should not be empty");
+
+            Err(match unit.field_span("msgid") {
+                Some(span) => Error::new(ErrorKind::EmptyMsgid, msg).with_span(*span),
+                None => Error::new(ErrorKind::EmptyMsgid, msg).with_line(line),
+            })
         } else {
             if unit.state == State::Empty && !unit.message.is_blank() {
                 // translation is non-empty and state was not set yet, then it is final
@@ -167,45 +223,39 @@ impl<'p, R: Read> PoReader<'p, R> {
     }
 
     fn next_unit(&mut self, first: bool) -> Option<Result<Unit, Error>> {
-        match self.read_unit(first) {
-            Ok(None) => None,
-            Ok(Some(u)) => Some(Ok(u)),
-            Err(e) => Some(Err(e)),
+        loop {
+            match self.read_unit(first) {
+                Ok(None) => return None,
+                Ok(Some(u)) => return Some(Ok(u)),
+                Err(e) if self.lenient => {
+                    self.diagnostics.push(e);
+                    self.lines.skip_entry(self.unit_start);
+                }
+                Err(e) => return Some(Err(e)),
+            }
         }
     }
 
     fn parse_po_header(&mut self, parser: &PoParser) -> Result<(), Error> {
-        if let Some(Ok(ref u)) = self.next_unit {
-            let mut header_properties: HashMap<String, Vec<String>> = HashMap::new();
-
-            for line in u.message.get_text().split('\n') {
-                if let Some(n) = line.find(':') {
-                    let key = line[..n].trim();
-                    let val = line[(n + 1)..].trim();
-
-                    header_properties
-                        .entry(key.to_owned())
-                        .or_default()
-                        .push(val.to_owned());
-                }
-            }
-
-            self.header_properties
-                .extend(header_properties.into_iter().map(|(k, l)| (k, l.join(" "))));
+        if let Some(Ok(ref mut u)) = self.next_unit {
+            self.header_properties.extend(parse_properties(u.message.get_text()));
             self.header_notes.extend_from_slice(&u.notes);
             self.header_comments.extend_from_slice(&u.comments);
+            self.header_source = u.source.take();
 
             if let Some(lang) = self.header_properties.get("Language") {
-                self.target_language = LanguageRange::new(lang)
-                    .map(LanguageRange::into_static)
-                    .or_else(|_| LanguageRange::from_unix(lang))
-                    .unwrap_or_else(|_| LanguageRange::invariant());
+                self.target_language = parse_language(lang);
             }
 
-            if let Some(forms) = self.header_properties.get("Plural-Forms") {
-                if !forms.is_empty() {
-                    self.plural_forms.replace(Rc::new(PluralForms::parse(&forms, parser)?));
+            match self
+                .header_properties
+                .get("Plural-Forms")
+                .filter(|forms| !forms.is_empty())
+            {
+                Some(forms) => {
+                    self.plural_forms.replace(Rc::new(PluralForms::parse(forms, parser)?));
                 }
+                None => self.plural_forms = PluralForms::for_language(&self.target_language).map(Rc::new),
             }
         }
 
@@ -219,7 +269,7 @@ impl<'p, R: Read> Iterator for PoReader<'p, R> {
     fn next(&mut self) -> Option<Self::Item> {
         match self.next_unit {
             None => None,
-            Some(Err(_)) => replace(&mut self.next_unit, None),
+            Some(Err(_)) => self.next_unit.take(),
             _ => {
                 let mut res = self.next_unit(false);
 
@@ -246,13 +296,18 @@ impl<'p, R: Read> CatalogueReader for PoReader<'p, R> {
     fn header_properties(&self) -> &HashMap<String, String> {
         &self.header_properties
     }
+
+    fn header_source(&self) -> Option<&str> {
+        self.header_source.as_deref()
+    }
 }
 
 // no-coverage:start
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
-    use crate::Message;
+    use crate::{flag::FormatLanguage, span::Span, Message};
     use std::collections::HashSet;
 
     fn make_source() -> &'static str {
@@ -274,7 +329,7 @@ mod tests {
         "
     }
 
-    fn make_reader<R: Read>(reader: R, parser: &PoParser) -> PoReader<R> {
+    fn make_reader<R: Read>(reader: R, parser: &PoParser) -> PoReader<'_, R> {
         let mut unit = Unit::default();
 
         unit.message = Message::Simple {
@@ -291,7 +346,10 @@ mod tests {
         };
 
         PoReader {
-            lines: LineIter::new(reader, parser).peekable(),
+            lines: LineIter::new(reader, parser),
+            lenient: false,
+            diagnostics: vec![],
+            unit_start: None,
             next_unit: Some(Ok(unit)),
             header_notes: vec![
                 Note::new(Origin::Translator, String::from("You")),
@@ -302,6 +360,7 @@ mod tests {
                 Comment::new('=', String::from("Comment 2")),
             ],
             header_properties: HashMap::new(),
+            header_source: None,
             target_language: LanguageRange::invariant(),
             plural_forms: None,
         }
@@ -442,7 +501,7 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
                 v => panic!("Unexpected result for first line: {:?}", v),
             }
 
-            if let None = reader.lines.next() {
+            if reader.lines.next().is_none() {
                 panic!("Unexpected None as first result of next");
             }
 
@@ -454,7 +513,7 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
                 v => panic!("Unexpected result for second line: {:?}", v),
             }
 
-            if let None = reader.lines.next() {
+            if reader.lines.next().is_none() {
                 panic!("Unexpected None as second result of next");
             }
 
@@ -469,7 +528,10 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             let mut reader = make_reader(source.as_bytes(), &parser);
 
             match reader.read_line() {
-                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 2, got ‘msgid \"my-error’"),
+                Err(err) => assert_eq!(
+                    format!("{:?}", err),
+                    "Parse error at line 1, column 1, got ‘msgid \"my-error’"
+                ),
                 v => panic!("Unexpected result for the first error case: {:?}", v),
             }
         }
@@ -478,7 +540,7 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             let source = "msgid \"my-error";
             let mut reader = make_reader(source.as_bytes(), &parser);
 
-            reader.next_unit = Some(Err(Error::Unexpected(123, String::from("An error"))));
+            reader.next_unit = Some(Err(Error::new(ErrorKind::Other, "An error").with_line(123)));
             match reader.read_line() {
                 Err(err) => assert_eq!(format!("{:?}", err), "Unexpected error at line 123: An error"),
                 v => panic!("Unexpected result for the second error case: {:?}", v),
@@ -496,7 +558,10 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             let mut unit = Unit::default();
 
             match reader.parse_comments(&mut unit) {
-                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 2, got ‘msgid \"my-error’"),
+                Err(err) => assert_eq!(
+                    format!("{:?}", err),
+                    "Parse error at line 1, column 1, got ‘msgid \"my-error’"
+                ),
                 v => panic!("Unexpected result for the first error line: {:?}", v),
             }
 
@@ -523,7 +588,10 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             let mut unit = Unit::default();
 
             match reader.parse_comments(&mut unit) {
-                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 3, got ‘msgid \"my-error’"),
+                Err(err) => assert_eq!(
+                    format!("{:?}", err),
+                    "Parse error at line 2, column 1, got ‘msgid \"my-error’"
+                ),
                 v => panic!("Unexpected result for the third error line: {:?}", v),
             }
 
@@ -578,14 +646,14 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
 
                     assert_eq!(
                         unit.locations,
-                        (1..=4).map(|i| format!("Location:{}", i)).collect::<Vec<_>>(),
+                        (1..=4).map(|i| Location::new("Location", Some(i))).collect::<Vec<_>>(),
                     );
 
                     assert_eq!(
                         unit.flags,
                         (1..=4)
-                            .map(|i| format!("flag{}", i))
-                            .chain(vec![String::from("fuzzy")])
+                            .map(|i| Flag::Other(format!("flag{}", i)))
+                            .chain(vec![Flag::Fuzzy])
                             .collect::<HashSet<_>>()
                     );
 
@@ -594,6 +662,20 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
                 v => panic!("Unexpected result for single line: {:?}", v),
             }
         }
+
+        {
+            let source = "#, fuzzy,\n#, , c-format ,,\n";
+            let mut reader = make_reader(source.as_bytes(), &parser);
+            let mut unit = Unit::default();
+
+            match reader.parse_comments(&mut unit) {
+                Ok(()) => assert_eq!(
+                    unit.flags,
+                    HashSet::from([Flag::Fuzzy, Flag::Format(FormatLanguage::C)])
+                ),
+                v => panic!("Unexpected result for empty flags: {:?}", v),
+            }
+        }
     }
 
     #[test]
@@ -615,7 +697,7 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             let mut reader = make_reader(source.as_bytes(), &parser);
 
             match reader.parse_unit(Unit::default(), false) {
-                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 2, got ‘msgid \"’"),
+                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 1, column 1, got ‘msgid \"’"),
                 Ok(v) => panic!("Unexpected result for bad `msgid`: {:?}", v),
             }
         }
@@ -667,13 +749,13 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
                     unit.locations,
                     ["File1:1", "File1:2", "File2:1", "File2:2",]
                         .into_iter()
-                        .map(str::to_string)
+                        .map(Location::parse)
                         .collect::<Vec<_>>()
                 );
 
                 assert_eq!(
                     unit.flags,
-                    ["flag1", "flag2", "fuzzy"].into_iter().map(str::to_string).collect()
+                    ["flag1", "flag2", "fuzzy"].into_iter().map(Flag::parse).collect()
                 );
                 assert_eq!(
                     unit.notes,
@@ -708,7 +790,7 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             let mut reader = make_reader(source.as_bytes(), &parser);
 
             match reader.read_unit(false) {
-                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 2, got ‘msgid \"’"),
+                Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 1, column 1, got ‘msgid \"’"),
                 Ok(r) => panic!("Unexpected result for the error test on parse comment: {:?}", r),
             }
         }
@@ -740,7 +822,7 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             match reader.read_unit(false) {
                 Err(err) => assert_eq!(
                     format!("{:?}", err),
-                    "Unexpected error at line 1: Source should not be empty"
+                    "Syntax error at line 1, column 1: Source should not be empty"
                 ),
                 Ok(r) => panic!("Unexpected result for the test on empty messages: {:?}", r),
             }
@@ -762,7 +844,7 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
         }
 
         match reader.next_unit(false) {
-            Some(Err(err)) => assert_eq!(format!("{:?}", err), "Parse error at line 5, got ‘msgstr \"’"),
+            Some(Err(err)) => assert_eq!(format!("{:?}", err), "Parse error at line 4, column 1, got ‘msgstr \"’"),
             v => panic!("Unexpected result for test on error: {:?}", v),
         }
 
@@ -779,7 +861,10 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
         let reader = PoReader::new(source.as_bytes(), &parser);
 
         match reader {
-            Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 2, got ‘msgid: \"--’"),
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Parse error at line 1, column 1, got ‘msgid: \"--’"
+            ),
             Ok(v) => panic!(
                 "Unexpected result: forms={:?}, notes={:?}, headers={:?}, next={:?}",
                 v.plural_forms, v.header_notes, v.header_properties, v.next_unit,
@@ -834,10 +919,11 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
                         .collect::<HashMap<_, _>>()
                 );
 
-                match reader.plural_forms {
-                    None => (),
-                    Some(forms) => panic!("Unexpected forms: {:?}", forms),
-                }
+                assert_eq!(
+                    reader.plural_forms.as_ref().map(|f| f.get_definition()),
+                    Some("nplurals=2; plural=(n > 1);"),
+                    "The forms of the language should be used without `Plural-Forms` header"
+                );
             }
             Err(err) => panic!("Unexpected error: {:?}", err),
         }
@@ -883,7 +969,9 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
                 }
 
                 match reader.next() {
-                    Some(Err(err)) => assert_eq!(format!("{:?}", err), "Parse error at line 6, got ‘msgid \"’"),
+                    Some(Err(err)) => {
+                        assert_eq!(format!("{:?}", err), "Parse error at line 5, column 1, got ‘msgid \"’")
+                    }
                     r => panic!("Unexpected result after the second call of `next()`: {:?}", r),
                 }
 
@@ -895,5 +983,144 @@ Expected one of "(", "-", "n" or r#"[0-9]+"#"##,
             Err(err) => panic!("Unexpected error: {:?}", err),
         }
     }
+
+    #[test]
+    fn test_trait_iterator_lenient() {
+        let source = "\
+            msgid \"\"\n\
+            msgstr \"Plural-Forms: nplurals=2; plural=n+;\\n\"\n\
+            \n\
+            msgid \"One\"\n\
+            msgstr \"Un\"\n\
+            \n\
+            msgid \"Bad\\q\"\n\
+            msgstr \"Mauvais\"\n\
+            \n\
+            msgid \"Two\"\n\
+            msgstr \"Deux\"\n\
+            \n\
+            garbage line\n\
+            msgid \"Three\"\n\
+            msgstr \"Trois\"\n\
+            \n\
+            msgstr \"Orphan\"\n\
+            \n\
+            msgid \"Four\"\n\
+            msgstr \"Quatre\"\n\
+        ";
+
+        let mut parser = PoParser::new();
+
+        parser.set_lenient(true);
+
+        let mut reader = PoReader::new(source.as_bytes(), &parser).unwrap();
+        let units: Vec<_> = reader
+            .by_ref()
+            .map(|u| u.map(|u| u.message.get_id().to_string()))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(units, vec!["One", "Two", "Four"]);
+        assert_eq!(
+            reader
+                .diagnostics()
+                .iter()
+                .map(|e| format!("{:?}", e))
+                .collect::<Vec<_>>(),
+            vec![
+                "Error in plurals forms: Unrecognized EOF found at 2\n\
+                 Expected one of \"(\", \"-\", \"n\" or r#\"[0-9]+\"#",
+                "Syntax error at line 7, column 11: Invalid escape sequence ‘\\q’",
+                "Parse error at line 13, column 1, got ‘garbage line’",
+                "Parse error at line 17, column 1 expected ‘msgid’",
+            ]
+        );
+        assert_eq!(
+            reader.diagnostics().iter().map(Error::kind).collect::<Vec<_>>(),
+            vec![
+                ErrorKind::InvalidPluralFormula { offset: Some(21) },
+                ErrorKind::BadEscape,
+                ErrorKind::Parse,
+                ErrorKind::MissingMsgid,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trait_iterator_spans() {
+        let source = "msgid \"\"\nmsgstr \"\"\n\n# Note\n#: file.rs:12\n  msgid \"Été\"\nmsgstr \"\"\n\"Summer\"\n";
+        let parser = PoParser::new();
+
+        match PoReader::new(source.as_bytes(), &parser) {
+            Ok(mut reader) => match reader.next() {
+                Some(Ok(unit)) => {
+                    let span = |s: &Span| {
+                        (
+                            s.start().offset(),
+                            s.start().line(),
+                            s.start().column(),
+                            s.end().offset(),
+                        )
+                    };
+                    let text = |s: &Span| &source[s.start().offset()..s.end().offset()];
+
+                    assert_eq!(unit.span().map(span), Some((20, 4, 1, 75)));
+                    assert_eq!(unit.field_span("msgid").map(span), Some((43, 6, 3, 56)));
+                    assert_eq!(unit.field_span("msgid").map(text), Some("msgid \"Été\""));
+                    assert_eq!(unit.field_span("msgstr").map(text), Some("msgstr \"\"\n\"Summer\""));
+                    assert_eq!(
+                        unit.field_span("msgstr").map(|s| (s.end().line(), s.end().column())),
+                        Some((8, 9))
+                    );
+                    assert_eq!(unit.field_span("msgctxt"), None);
+                    assert_eq!(
+                        unit.comment_spans().iter().map(text).collect::<Vec<_>>(),
+                        vec!["# Note", "#: file.rs:12"]
+                    );
+                }
+                r => panic!("Unexpected result: {:?}", r),
+            },
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_trait_iterator_lossless() {
+        let source = make_source();
+        let mut parser = PoParser::new();
+
+        match PoReader::new(source.as_bytes(), &parser) {
+            Ok(mut reader) => {
+                assert_eq!(reader.header_source(), None);
+
+                match reader.next() {
+                    Some(Ok(unit)) => assert_eq!(unit.source(), None),
+                    r => panic!("Unexpected result in normal mode: {:?}", r),
+                }
+            }
+            Err(err) => panic!("Unexpected error in normal mode: {:?}", err),
+        }
+
+        parser.set_lossless(true);
+
+        match PoReader::new(source.as_bytes(), &parser) {
+            Ok(mut reader) => {
+                let (header, entry) = source.split_at(source.find("msgid \"Hello").unwrap());
+
+                assert_eq!(reader.header_source(), Some(header));
+
+                match reader.next() {
+                    Some(Ok(unit)) => assert_eq!(unit.source(), Some(entry)),
+                    r => panic!("Unexpected result in lossless mode: {:?}", r),
+                }
+
+                match reader.next() {
+                    None => (),
+                    Some(r) => panic!("Unexpected result at the end in lossless mode: {:?}", r),
+                }
+            }
+            Err(err) => panic!("Unexpected error in lossless mode: {:?}", err),
+        }
+    }
 }
 // no-coverage:stop
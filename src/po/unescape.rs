@@ -1,48 +1,120 @@
-use regex::{Captures, Regex};
+use crate::charset::decode;
+use encoding_rs::Encoding;
+use std::{iter::Peekable, str::CharIndices};
 
-pub(super) struct Unescaper {
-    re: Regex,
-    table: [Option<&'static str>; 42],
-}
+/// Unescaper of the strings of PO files, with the escape sequences of the C language as gettext accepts them.
+///
+/// Octal (`\NNN`) and hexadecimal (`\xHH`) sequences are bytes, a run of them is decoded with the encoding of the
+/// catalogue.
+pub(super) struct Unescaper;
 
 impl Unescaper {
-    #[rustfmt::skip]
     pub(super) fn new() -> Unescaper {
-        Unescaper {
-            re: Regex::new(r#"\\([rtn"\\])"#).unwrap(),
-            table: [
-                Some("\""), None,       None, None,       None, None,       None,
-                None,       None,       None, None,       None, None,       None,
-                None,       None,       None, None,       None, None,       None,
-                None,       None,       None, None,       None, None,       None,
-                None,       Some("\\"), None, None,       None, None,       None,
-                None,       None,       None, Some("\n"), None, Some("\r"), Some("\t"),
-            ]
+        Unescaper
+    }
+
+    fn replace_char(&self, ch: char) -> Option<char> {
+        match ch {
+            'a' => Some('\x07'),
+            'b' => Some('\x08'),
+            'f' => Some('\x0c'),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            'v' => Some('\x0b'),
+            '"' | '\'' | '?' | '\\' => Some(ch),
+            _ => None,
+        }
+    }
+
+    /// Read the digits of a numeric escape sequence after its first digit, returns `None` if the value does not fit
+    /// in a byte.
+    fn read_byte(chars: &mut Peekable<CharIndices>, radix: u32, first: u32, max_len: usize) -> Option<u8> {
+        let mut value = first;
+
+        for _ in 1..max_len {
+            match chars.peek().and_then(|(_, c)| c.to_digit(radix)) {
+                Some(digit) => {
+                    value = value.saturating_mul(radix).saturating_add(digit);
+                    chars.next();
+                }
+                None => break,
+            }
         }
+
+        u8::try_from(value).ok()
     }
 
-    fn replace_char(&self, ch: char) -> Option<&'static str> {
-        let idx = ch as u32;
+    /// Unescape a string, the error gives the byte offset of the invalid escape sequence in the string.
+    pub(super) fn unescape(&self, text: &str, encoding: &'static Encoding) -> Result<String, (usize, String)> {
+        let mut res = String::with_capacity(text.len());
+        let mut bytes: Vec<u8> = vec![];
+        let mut bytes_start = 0;
+        let mut chars = text.char_indices().peekable();
 
-        if ((idx & 1) == 0) && (34 <= idx) && (idx <= 116) {
-            let index = ((idx - 34) / 2) as usize;
+        while let Some((pos, ch)) = chars.next() {
+            if ch != '\\' {
+                Self::flush_bytes(&mut res, &mut bytes, bytes_start, encoding)?;
+                res.push(ch);
+                continue;
+            }
 
-            self.table[index]
-        } else {
-            None
+            let byte = match chars.next() {
+                None => {
+                    return Err((pos, String::from("Incomplete escape sequence at the end of the string")));
+                }
+                Some((_, 'x')) => match chars.next().and_then(|(_, c)| c.to_digit(16)) {
+                    Some(digit) => Self::read_byte(&mut chars, 16, digit, usize::MAX),
+                    _ => {
+                        return Err((pos, String::from("Missing hexadecimal digits in escape sequence ‘\\x’")));
+                    }
+                },
+                Some((_, c)) if c.is_digit(8) => Self::read_byte(&mut chars, 8, c.to_digit(8).unwrap_or_default(), 3),
+                Some((_, c)) => match self.replace_char(c) {
+                    Some(r) => {
+                        Self::flush_bytes(&mut res, &mut bytes, bytes_start, encoding)?;
+                        res.push(r);
+                        continue;
+                    }
+                    None => {
+                        return Err((pos, format!("Invalid escape sequence ‘\\{}’", c)));
+                    }
+                },
+            };
+
+            match byte {
+                Some(byte) => {
+                    if bytes.is_empty() {
+                        bytes_start = pos;
+                    }
+
+                    bytes.push(byte);
+                }
+                None => {
+                    let end = chars.peek().map_or(text.len(), |(end, _)| *end);
+
+                    return Err((pos, format!("Escape sequence ‘{}’ out of range", &text[pos..end])));
+                }
+            }
         }
+
+        Self::flush_bytes(&mut res, &mut bytes, bytes_start, encoding)?;
+
+        Ok(res)
     }
 
-    pub(super) fn unescape(&self, text: &str) -> String {
-        self.re
-            .replace_all(text, |d: &Captures| -> String {
-                d.get(1)
-                    .map(|m| m.as_str())
-                    .map(|key| key.chars().next().and_then(|ch| self.replace_char(ch)).unwrap_or(key))
-                    .unwrap_or_default()
-                    .to_string()
-            })
-            .to_string()
+    fn flush_bytes(
+        res: &mut String,
+        bytes: &mut Vec<u8>,
+        start: usize,
+        encoding: &'static Encoding,
+    ) -> Result<(), (usize, String)> {
+        if !bytes.is_empty() {
+            res.push_str(&decode(encoding, bytes).map_err(|err| (start, err.to_string()))?);
+            bytes.clear();
+        }
+
+        Ok(())
     }
 }
 
@@ -50,6 +122,7 @@ impl Unescaper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use encoding_rs::{ISO_8859_2, UTF_8};
 
     #[test]
     fn test_func_replace_char() {
@@ -60,11 +133,15 @@ mod tests {
         assert_eq!(unesc.replace_char('C'), None);
         assert_eq!(unesc.replace_char('D'), None);
         assert_eq!(unesc.replace_char('E'), None);
-        assert_eq!(unesc.replace_char('"'), Some("\""));
-        assert_eq!(unesc.replace_char('\\'), Some("\\"));
-        assert_eq!(unesc.replace_char('n'), Some("\n"));
-        assert_eq!(unesc.replace_char('r'), Some("\r"));
-        assert_eq!(unesc.replace_char('t'), Some("\t"));
+        assert_eq!(unesc.replace_char('"'), Some('"'));
+        assert_eq!(unesc.replace_char('\\'), Some('\\'));
+        assert_eq!(unesc.replace_char('n'), Some('\n'));
+        assert_eq!(unesc.replace_char('r'), Some('\r'));
+        assert_eq!(unesc.replace_char('t'), Some('\t'));
+        assert_eq!(unesc.replace_char('a'), Some('\x07'));
+        assert_eq!(unesc.replace_char('b'), Some('\x08'));
+        assert_eq!(unesc.replace_char('f'), Some('\x0c'));
+        assert_eq!(unesc.replace_char('v'), Some('\x0b'));
     }
 
     #[test]
@@ -72,11 +149,51 @@ mod tests {
         let unesc = Unescaper::new();
 
         assert_eq!(
-            unesc.unescape(r"Hello\nworld\r\n\t!"),
-            String::from("Hello\nworld\r\n\t!")
+            unesc.unescape(r"Hello\nworld\r\n\t!", UTF_8),
+            Ok(String::from("Hello\nworld\r\n\t!"))
         );
-        assert_eq!(unesc.unescape(r#"Sub\"\tstring"#), String::from("Sub\"\tstring"));
-        assert_eq!(unesc.unescape(r"My\\Path: \tValue"), String::from("My\\Path: \tValue"));
+        assert_eq!(
+            unesc.unescape(r#"Sub\"\tstring"#, UTF_8),
+            Ok(String::from("Sub\"\tstring"))
+        );
+        assert_eq!(
+            unesc.unescape(r"My\\Path: \tValue", UTF_8),
+            Ok(String::from("My\\Path: \tValue"))
+        );
+        assert_eq!(
+            unesc.unescape(r"\a\b\f\v\'\?", UTF_8),
+            Ok(String::from("\x07\x08\x0c\x0b'?"))
+        );
+        assert_eq!(
+            unesc.unescape(r"\101\1022\x41\x4a\x4B!", UTF_8),
+            Ok(String::from("AB2AJK!"))
+        );
+        assert_eq!(
+            unesc.unescape(r"Caf\xc3\xa9 \303\251t\xc3\xa9", UTF_8),
+            Ok(String::from("Café été"))
+        );
+        assert_eq!(unesc.unescape(r"\xbf\363\263w", ISO_8859_2), Ok(String::from("żółw")));
+    }
+
+    #[test]
+    fn test_func_unescape_with_error() {
+        let unesc = Unescaper::new();
+
+        for (text, expected) in [
+            (r"ab\q", (2, "Invalid escape sequence ‘\\q’")),
+            (r"ab\", (2, "Incomplete escape sequence at the end of the string")),
+            (r"ab\xg", (2, "Missing hexadecimal digits in escape sequence ‘\\x’")),
+            (r"ab\x100c", (2, "Escape sequence ‘\\x100c’ out of range")),
+            (r"ab\777", (2, "Escape sequence ‘\\777’ out of range")),
+            (r"a\xc3 b", (1, "Invalid UTF-8 sequence")),
+        ] {
+            assert_eq!(
+                unesc.unescape(text, UTF_8),
+                Err((expected.0, String::from(expected.1))),
+                "Text: {}",
+                text
+            );
+        }
     }
 }
 // no-coverage:stop
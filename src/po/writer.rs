@@ -0,0 +1,696 @@
+use super::escape::escape;
+use crate::{
+    charset::{encode, find_encoding, parse_charset},
+    comment::Comment,
+    error::{Error, ErrorKind},
+    flag::Flag,
+    header::{format_properties, ordered_properties},
+    location::Location,
+    note::Note,
+    unit::Unit,
+    CatalogueReader, Message, Origin, State,
+};
+use encoding_rs::{Encoding, UTF_8};
+use std::{
+    borrow::Borrow,
+    io::{Error as IoError, Write},
+};
+
+/// Default width of the written lines, the same as in gettext tools.
+const DEFAULT_LINE_WIDTH: usize = 79;
+
+#[inline]
+fn width(s: &str) -> usize {
+    s.chars().count()
+}
+
+fn wrap(text: &str, width: usize) -> Vec<&str> {
+    if width == 0 || self::width(text) <= width {
+        return vec![text];
+    }
+
+    let mut res = vec![];
+    let (mut start, mut end, mut len) = (0, 0, 0);
+
+    for word in text.split_inclusive(' ') {
+        let word_len = self::width(word);
+
+        if len > 0 && len + word_len > width {
+            res.push(&text[start..end]);
+            start = end;
+            len = 0;
+        }
+
+        end += word.len();
+        len += word_len;
+    }
+
+    res.push(&text[start..end]);
+    res
+}
+
+/// Object for writing PO streams
+///
+/// The header entry should be written first, then each unit of translation. The text is encoded with the charset
+/// declared in the `Content-Type` property of the header, or in UTF-8 if there is none.
+pub struct PoWriter<W: Write> {
+    writer: W,
+    encoding: &'static Encoding,
+    line: usize,
+    line_width: usize,
+    has_entries: bool,
+    separated: bool,
+    open_line: bool,
+}
+
+impl<W: Write> PoWriter<W> {
+    pub fn new(writer: W) -> PoWriter<W> {
+        PoWriter {
+            writer,
+            encoding: UTF_8,
+            line: 0,
+            line_width: DEFAULT_LINE_WIDTH,
+            has_entries: false,
+            separated: false,
+            open_line: false,
+        }
+    }
+
+    /// Set the maximal width of the lines, long strings are wrapped on spaces.
+    ///
+    /// A zero width disables the wrapping, strings are then only split after new lines.
+    pub fn set_line_width(&mut self, width: usize) {
+        self.line_width = width;
+    }
+
+    /// Write the header entry with its notes, comments and properties (in the given order).
+    pub fn write_header<K, V, P>(&mut self, notes: &[Note], comments: &[Comment], properties: P) -> Result<(), Error>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+        P: IntoIterator<Item = (K, V)>, {
+        let properties: Vec<(K, V)> = properties.into_iter().collect();
+
+        if let Some((_, content_type)) = properties.iter().find(|(k, _)| k.as_ref() == "Content-Type") {
+            self.use_content_type(content_type.as_ref())?;
+        }
+
+        let text = format_properties(properties);
+
+        self.begin_entry()?;
+        self.write_notes(notes, comments)?;
+        self.write_string("", "msgid", "")?;
+        self.write_string("", "msgstr", &text)
+    }
+
+    /// Write a unit of translation.
+    ///
+    /// If the unit has kept its original text (lossless mode), this text is written as is.
+    pub fn write_unit(&mut self, unit: &Unit) -> Result<(), Error> {
+        if let Some(source) = &unit.source {
+            return self.write_source(source);
+        }
+
+        let (prefix, prev_prefix) = if unit.obsolete { ("#~ ", "#~| ") } else { ("", "#| ") };
+
+        self.begin_entry()?;
+        self.write_notes(&unit.notes, &unit.comments)?;
+        self.write_locations(&unit.locations)?;
+        self.write_flags(unit)?;
+
+        if let Some(context) = &unit.prev_context {
+            self.write_string(prev_prefix, "msgctxt", context)?;
+        }
+
+        if !unit.prev_message.is_empty() {
+            self.write_string(prev_prefix, "msgid", unit.prev_message.get_id())?;
+
+            if let Some(plural) = unit.prev_message.get_plural_id() {
+                self.write_string(prev_prefix, "msgid_plural", plural)?;
+            }
+        }
+
+        if let Some(context) = &unit.context {
+            self.write_string(prefix, "msgctxt", context)?;
+        }
+
+        match &unit.message {
+            Message::Simple { id, text } => {
+                self.write_string(prefix, "msgid", id)?;
+                self.write_string(prefix, "msgstr", text.as_deref().unwrap_or_default())
+            }
+            Message::Plural(plural) => {
+                self.write_string(prefix, "msgid", plural.singular())?;
+                self.write_string(prefix, "msgid_plural", plural.plural())?;
+
+                if plural.values().is_empty() {
+                    let count = plural.get_forms().map_or(2, |f| f.get_count()).max(1);
+
+                    for i in 0..count {
+                        self.write_string(prefix, &format!("msgstr[{}]", i), "")?;
+                    }
+                } else {
+                    for (i, value) in plural.values().iter().enumerate() {
+                        self.write_string(prefix, &format!("msgstr[{}]", i), value)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Write all units given by an iterator.
+    pub fn write_units<I>(&mut self, units: I) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Unit>, {
+        for unit in units {
+            self.write_unit(unit.borrow())?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a whole catalogue, the header then all units.
+    ///
+    /// Header properties are written in the usual order of gettext tools.
+    pub fn write_catalogue<C: CatalogueReader>(&mut self, catalogue: C) -> Result<(), Error> {
+        let properties: Vec<(String, String)> = ordered_properties(catalogue.header_properties())
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        if let Some(source) = catalogue.header_source() {
+            if let Some(content_type) = catalogue.header_properties().get("Content-Type") {
+                self.use_content_type(content_type)?;
+            }
+
+            self.write_source(source)?;
+        } else if !(properties.is_empty()
+            && catalogue.header_notes().is_empty()
+            && catalogue.header_comments().is_empty())
+        {
+            let notes = catalogue.header_notes().clone();
+            let comments = catalogue.header_comments().clone();
+
+            self.write_header(&notes, &comments, properties)?;
+        }
+
+        for unit in catalogue {
+            self.write_unit(&unit?)?;
+        }
+
+        self.writer.flush().map_err(|err| Error::io(0, err))
+    }
+
+    /// Get back the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn use_content_type(&mut self, content_type: &str) -> Result<(), Error> {
+        if let Some(charset) = parse_charset(content_type) {
+            self.encoding =
+                find_encoding(charset).map_err(|msg| Error::new(ErrorKind::Encoding, msg).with_line(self.line))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_text(&mut self, text: &str) -> Result<(), IoError> {
+        let bytes = encode(self.encoding, text)?;
+
+        self.writer.write_all(&bytes)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), Error> {
+        self.line += 1;
+        self.write_text(&format!("{}\n", line))
+            .map_err(|err| Error::io(self.line, err))
+    }
+
+    fn begin_entry(&mut self) -> Result<(), Error> {
+        if self.open_line {
+            self.open_line = false;
+            self.line += 1;
+            self.writer.write_all(b"\n").map_err(|err| Error::io(self.line, err))?;
+        }
+
+        if self.has_entries && !self.separated {
+            self.write_line("")?;
+        }
+
+        self.has_entries = true;
+        self.separated = false;
+
+        Ok(())
+    }
+
+    fn write_source(&mut self, source: &str) -> Result<(), Error> {
+        self.begin_entry()?;
+        self.write_text(source).map_err(|err| Error::io(self.line + 1, err))?;
+
+        self.line += source.matches('\n').count();
+        self.open_line = !(source.is_empty() || source.ends_with('\n'));
+        self.separated = source.lines().last().is_some_and(|line| line.trim().is_empty());
+
+        Ok(())
+    }
+
+    fn write_comment(&mut self, kind: char, content: &str) -> Result<(), Error> {
+        let head = if kind == ' ' {
+            String::from("#")
+        } else {
+            format!("#{}", kind)
+        };
+
+        for line in content.split('\n') {
+            if line.is_empty() {
+                self.write_line(&head)?;
+            } else {
+                self.write_line(&format!("{} {}", head, line))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_notes(&mut self, notes: &[Note], comments: &[Comment]) -> Result<(), Error> {
+        for note in notes.iter().filter(|n| *n.origin() == Origin::Translator) {
+            self.write_comment(' ', note.value())?;
+        }
+
+        for comment in comments {
+            self.write_comment(comment.kind(), comment.comment())?;
+        }
+
+        for note in notes.iter().filter(|n| *n.origin() == Origin::Developer) {
+            self.write_comment('.', note.value())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_locations(&mut self, locations: &[Location]) -> Result<(), Error> {
+        let mut line = String::new();
+
+        for location in locations.iter().map(Location::to_string) {
+            if !line.is_empty() && self.line_width > 0 && width(&line) + width(&location) + 1 > self.line_width {
+                self.write_line(&line)?;
+                line.clear();
+            }
+
+            if line.is_empty() {
+                line.push_str("#:");
+            }
+
+            line.push(' ');
+            line.push_str(&location);
+        }
+
+        if line.is_empty() {
+            Ok(())
+        } else {
+            self.write_line(&line)
+        }
+    }
+
+    fn write_flags(&mut self, unit: &Unit) -> Result<(), Error> {
+        let mut flags: Vec<String> = unit
+            .flags
+            .iter()
+            .filter(|f| **f != Flag::Fuzzy)
+            .map(Flag::to_string)
+            .collect();
+
+        flags.sort_unstable();
+        if unit.state == State::NeedsWork {
+            flags.insert(0, Flag::Fuzzy.to_string());
+        }
+
+        if flags.is_empty() {
+            Ok(())
+        } else {
+            self.write_line(&format!("#, {}", flags.join(", ")))
+        }
+    }
+
+    fn write_string(&mut self, prefix: &str, keyword: &str, text: &str) -> Result<(), Error> {
+        let segments: Vec<String> = text.split_inclusive('\n').map(escape).collect();
+        let head = format!("{}{} ", prefix, keyword);
+
+        if segments.len() <= 1 {
+            let value = segments.first().map(String::as_str).unwrap_or_default();
+
+            if self.line_width == 0 || width(&head) + width(value) + 2 <= self.line_width {
+                return self.write_line(&format!("{}\"{}\"", head, value));
+            }
+        }
+
+        let available = if self.line_width == 0 {
+            0
+        } else {
+            self.line_width.saturating_sub(width(prefix) + 2).max(1)
+        };
+
+        self.write_line(&format!("{}\"\"", head))?;
+        for segment in segments.iter() {
+            for chunk in wrap(segment, available) {
+                self.write_line(&format!("{}\"{}\"", prefix, chunk))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
+mod tests {
+    use super::*;
+    use crate::{plural::Plural, PoParser};
+
+    fn write<F: FnOnce(&mut PoWriter<Vec<u8>>) -> Result<(), Error>>(f: F) -> String {
+        let mut writer = PoWriter::new(vec![]);
+
+        f(&mut writer).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_func_wrap() {
+        assert_eq!(wrap("Some text", 0), vec!["Some text"]);
+        assert_eq!(wrap("Some text", 9), vec!["Some text"]);
+        assert_eq!(wrap("Some text to wrap", 10), vec!["Some text ", "to wrap"]);
+        assert_eq!(wrap("A verylongword", 5), vec!["A ", "verylongword"]);
+    }
+
+    #[test]
+    fn test_func_write_header() {
+        let notes = vec![
+            Note::new(Origin::Developer, String::from("Generated")),
+            Note::new(Origin::Translator, String::from("SOME DESCRIPTIVE TITLE.")),
+        ];
+
+        let comments = vec![Comment::new('$', String::from("Custom"))];
+        let output = write(|w| w.write_header(&notes, &comments, [("Language", "fr"), ("MIME-Version", "1.0")]));
+
+        assert_eq!(
+            output,
+            "\
+                # SOME DESCRIPTIVE TITLE.\n\
+                #$ Custom\n\
+                #. Generated\n\
+                msgid \"\"\n\
+                msgstr \"\"\n\
+                \"Language: fr\\n\"\n\
+                \"MIME-Version: 1.0\\n\"\n\
+            "
+        );
+    }
+
+    #[test]
+    fn test_func_write_unit_normal() {
+        let output = write(|w| w.write_unit(&Unit::for_tests_normal()));
+
+        assert_eq!(
+            output,
+            "\
+                # translator note 1\n\
+                # translator note 2\n\
+                #X Comment 1\n\
+                #X Comment 2\n\
+                #X Comment 3\n\
+                #. developper note 1\n\
+                #. developper note 2\n\
+                #: File1:12 File2:34 File3:56\n\
+                #, flag1, flag2, flag3\n\
+                #| msgctxt \"prev-context\"\n\
+                #| msgid \"prev-message\"\n\
+                msgctxt \"context\"\n\
+                msgid \"message\"\n\
+                msgstr \"text\"\n\
+            "
+        );
+    }
+
+    #[test]
+    fn test_func_write_unit_obsolete() {
+        let mut unit = Unit::for_tests_incomplete();
+
+        unit.prev_message = Message::Simple {
+            id: String::from("previous"),
+            text: None,
+        };
+
+        let output = write(|w| w.write_unit(&unit));
+
+        assert_eq!(
+            output,
+            "\
+                #, fuzzy, flag1, flag2, flag3\n\
+                #~| msgid \"previous\"\n\
+                #~ msgctxt \"incomplete context\"\n\
+                #~ msgid \"incomplete message\"\n\
+                #~ msgstr \"incomplete text\"\n\
+            "
+        );
+    }
+
+    #[test]
+    fn test_func_write_unit_plural() {
+        let mut unit = Unit::default();
+
+        unit.message = Message::Plural(Plural::new(
+            String::from("One file"),
+            String::from("Several files"),
+            vec![String::from("Un fichier"), String::from("Des fichiers")],
+            None,
+        ));
+
+        let output = write(|w| w.write_unit(&unit));
+
+        assert_eq!(
+            output,
+            "\
+                msgid \"One file\"\n\
+                msgid_plural \"Several files\"\n\
+                msgstr[0] \"Un fichier\"\n\
+                msgstr[1] \"Des fichiers\"\n\
+            "
+        );
+
+        unit.message = Message::Plural(Plural::new(String::from("a"), String::from("b"), vec![], None));
+
+        let output = write(|w| w.write_unit(&unit));
+
+        assert_eq!(
+            output,
+            "msgid \"a\"\nmsgid_plural \"b\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_func_write_units() {
+        let units = vec![Unit::for_tests_incomplete(), Unit::for_tests_incomplete()];
+        let output = write(|w| w.write_units(&units));
+
+        assert_eq!(output.matches("#~ msgid \"incomplete message\"\n").count(), 2);
+        assert_eq!(output.matches("\n\n").count(), 1);
+    }
+
+    #[test]
+    fn test_func_write_string() {
+        let output = write(|w| {
+            w.set_line_width(30);
+            w.write_string("", "msgid", "A long message which must be wrapped")?;
+            w.write_string("#~ ", "msgstr", "First line\nSecond line\n")?;
+            w.write_string("", "msgstr", "Final\n")
+        });
+
+        assert_eq!(
+            output,
+            "\
+                msgid \"\"\n\
+                \"A long message which must \"\n\
+                \"be wrapped\"\n\
+                #~ msgstr \"\"\n\
+                #~ \"First line\\n\"\n\
+                #~ \"Second line\\n\"\n\
+                msgstr \"Final\\n\"\n\
+            "
+        );
+
+        let output = write(|w| {
+            w.set_line_width(0);
+            w.write_string("", "msgid", "A long message which must not be wrapped")
+        });
+
+        assert_eq!(output, "msgid \"A long message which must not be wrapped\"\n");
+    }
+
+    #[test]
+    fn test_func_write_catalogue() {
+        let source = "\
+            # Header note\n\
+            msgid \"\"\n\
+            msgstr \"\"\n\
+            \"Plural-Forms: nplurals=2; plural=(n > 1);\\n\"\n\
+            \"Language: fr\\n\"\n\
+            \n\
+            #: main.c:12\n\
+            #, fuzzy\n\
+            msgid \"Hello\"\n\
+            msgstr \"Salut\"\n\
+            \n\
+            msgid \"One file\"\n\
+            msgid_plural \"Several files\"\n\
+            msgstr[0] \"Un fichier\"\n\
+            msgstr[1] \"Des fichiers\"\n\
+        ";
+
+        let parser = PoParser::new();
+        let output = write(|w| w.write_catalogue(parser.parse(source.as_bytes())?));
+
+        assert_eq!(
+            output,
+            "\
+                # Header note\n\
+                msgid \"\"\n\
+                msgstr \"\"\n\
+                \"Language: fr\\n\"\n\
+                \"Plural-Forms: nplurals=2; plural=(n > 1);\\n\"\n\
+                \n\
+                #: main.c:12\n\
+                #, fuzzy\n\
+                msgid \"Hello\"\n\
+                msgstr \"Salut\"\n\
+                \n\
+                msgid \"One file\"\n\
+                msgid_plural \"Several files\"\n\
+                msgstr[0] \"Un fichier\"\n\
+                msgstr[1] \"Des fichiers\"\n\
+            "
+        );
+    }
+
+    #[test]
+    fn test_func_write_catalogue_lossless() {
+        let source = "\
+            \n\
+            #, fuzzy\n\
+            # Header note\n\
+            msgid \"\"\n\
+            msgstr \"Language: fr\\n\"\n\
+            \n\
+            \n\
+            #. Developer note\r\n\
+            # Translator note\r\n\
+            msgid \"\"\r\n\
+            \"Hello\"\r\n\
+            msgstr \"Sa\"\r\n\
+            \"lut\"\r\n\
+            \n\
+            #~ msgid \"Old\"\n\
+            #~ msgstr \"Vieux\"\
+        ";
+
+        let mut parser = PoParser::new();
+
+        parser.set_lossless(true);
+
+        let output = write(|w| w.write_catalogue(parser.parse(source.as_bytes())?));
+
+        assert_eq!(output, source);
+    }
+
+    #[test]
+    fn test_func_write_unit_lossless() {
+        let mut unit = Unit::for_tests_incomplete();
+
+        unit.source = Some(String::from("#~ msgid \"raw\"\n#~ msgstr \"brut\""));
+
+        let units = vec![unit.clone(), Unit::for_tests_incomplete(), unit];
+        let output = write(|w| w.write_units(&units));
+        let rendered = write(|w| w.write_unit(&Unit::for_tests_incomplete()));
+        let raw = "#~ msgid \"raw\"\n#~ msgstr \"brut\"";
+
+        assert_eq!(output, format!("{raw}\n\n{rendered}\n{raw}"));
+    }
+
+    #[test]
+    fn test_func_write_with_charset() {
+        let mut unit = Unit::default();
+        let mut writer = PoWriter::new(vec![]);
+
+        unit.message = Message::Simple {
+            id: String::from("Turtle"),
+            text: Some(String::from("Żółw")),
+        };
+
+        writer
+            .write_header(&[], &[], [("Content-Type", "text/plain; charset=ISO-8859-2")])
+            .unwrap();
+        writer.write_unit(&unit).unwrap();
+
+        let output = writer.into_inner();
+
+        assert!(
+            output.ends_with(b"msgid \"Turtle\"\nmsgstr \"\xaf\xf3\xb3w\"\n"),
+            "Bad output: {:?}",
+            String::from_utf8_lossy(&output)
+        );
+
+        let mut parser = PoParser::new();
+
+        parser.set_lossless(true);
+
+        let output = {
+            let mut writer = PoWriter::new(vec![]);
+
+            writer.write_catalogue(parser.parse(&output[..]).unwrap()).unwrap();
+            writer.into_inner()
+        };
+
+        assert!(output.ends_with(b"msgstr \"\xaf\xf3\xb3w\"\n"), "Bad lossless output");
+
+        let mut writer = PoWriter::new(vec![]);
+
+        writer
+            .write_header(&[], &[], [("Content-Type", "text/plain; charset=ASCII")])
+            .unwrap();
+
+        match writer.write_unit(&unit) {
+            Err(err) => {
+                assert_eq!(err.kind(), ErrorKind::Encoding);
+                assert_eq!(err.line(), 5);
+                assert_eq!(err.message(), "Character not representable in windows-1252");
+            }
+            r => panic!("Unexpected result for an unmappable character: {:?}", r),
+        }
+
+        match writer.write_header(&[], &[], [("Content-Type", "text/plain; charset=UTF-7")]) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Encoding error at line 5: Unsupported charset ‘UTF-7’"
+            ),
+            r => panic!("Unexpected result for an unsupported charset: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_func_write_catalogue_with_error() {
+        let parser = PoParser::new();
+        let mut writer = PoWriter::new(vec![]);
+        let source = "msgid \"a\"\nmsgstr \"b\"\n\nmsgid \"c\"\nmsgstr \"d\"\n\nmsgid \"e";
+        let reader = parser.parse(source.as_bytes()).unwrap();
+
+        match writer.write_catalogue(reader) {
+            Err(err) => assert_eq!(format!("{:?}", err), "Parse error at line 7, column 1, got ‘msgid \"e’"),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,360 @@
+//! Search of the units with a message similar to a text.
+
+use crate::{unit::Unit, Catalog};
+use std::collections::HashMap;
+
+/// Bigram of characters, the texts are padded with a NUL character at both ends.
+type Gram = [char; 2];
+
+/// Below this threshold, a text can be similar to a message without sharing any bigram with it.
+const FULL_SCAN_THRESHOLD: f64 = 0.5;
+
+/// Similarity of two texts, from 0 (nothing in common) to 1 (same texts), from the edit distance of their characters.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let len = a.len().max(b.len());
+
+    if len == 0 {
+        1.0
+    } else {
+        1.0 - (edit_distance(&a, &b) as f64) / (len as f64)
+    }
+}
+
+/// Levenshtein distance of two texts.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let value = (diagonal + usize::from(ca != cb)).min(row[j] + 1).min(row[j + 1] + 1);
+
+            diagonal = row[j + 1];
+            row[j + 1] = value;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Count the bigrams of a text.
+fn grams(text: &[char]) -> HashMap<Gram, usize> {
+    let mut res = HashMap::new();
+    let mut prev = '\0';
+
+    for c in text.iter().copied().chain(Some('\0')) {
+        *res.entry([prev, c]).or_insert(0) += 1;
+        prev = c;
+    }
+
+    res
+}
+
+/// Unit found by a similarity index, with the similarity of its message.
+#[derive(Clone, Copy, Debug)]
+pub struct Candidate<'c> {
+    unit: &'c Unit,
+    score: f64,
+}
+
+impl<'c> Candidate<'c> {
+    /// The unit found.
+    pub fn unit(&self) -> &'c Unit {
+        self.unit
+    }
+
+    /// The similarity of the message of the unit with the searched text, from 0 to 1.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+/// Index of the messages of units, for finding the messages similar to a text.
+///
+/// The similarity is given by the edit distance of the texts (see [`similarity`]). The bigrams of the messages are
+/// indexed, so that only the messages sharing enough bigrams with the text are compared to it, without missing any
+/// message above the threshold when the threshold is greater than 0.5.
+///
+/// Example:
+/// ```rust
+/// use poreader::{similarity::SimilarityIndex, Catalog, PoParser};
+///
+/// let source = "msgid \"Open the file\"\nmsgstr \"Ouvrir le fichier\"\n";
+/// let parser = PoParser::new();
+/// let catalog = Catalog::from_reader(parser.parse(source.as_bytes()).unwrap()).unwrap();
+/// let index = SimilarityIndex::new(&catalog);
+///
+/// assert_eq!(
+///     index
+///         .best("Open the files", 0.6)
+///         .map(|c| c.unit().message().get_id()),
+///     Some("Open the file")
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SimilarityIndex<'c> {
+    units: Vec<(&'c Unit, Vec<char>)>,
+    postings: HashMap<Gram, Vec<(usize, usize)>>,
+}
+
+impl<'c> SimilarityIndex<'c> {
+    /// Index the messages of all units of a catalogue.
+    pub fn new(catalog: &'c Catalog) -> SimilarityIndex<'c> {
+        Self::from_units(catalog.iter())
+    }
+
+    /// Index the messages of some units.
+    pub fn from_units<I: IntoIterator<Item = &'c Unit>>(units: I) -> SimilarityIndex<'c> {
+        let mut postings: HashMap<Gram, Vec<(usize, usize)>> = HashMap::new();
+        let units: Vec<(&Unit, Vec<char>)> = units
+            .into_iter()
+            .map(|unit| (unit, unit.message().get_id().chars().collect()))
+            .collect();
+
+        for (pos, (_, text)) in units.iter().enumerate() {
+            for (gram, count) in grams(text) {
+                postings.entry(gram).or_default().push((pos, count));
+            }
+        }
+
+        SimilarityIndex { units, postings }
+    }
+
+    /// Number of indexed units.
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Returns whether the index has no unit.
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Find the units with a message similar to a text, with a similarity greater or equal to the threshold.
+    ///
+    /// The candidates are sorted by decreasing similarity, then in the order of the units.
+    pub fn find(&self, text: &str, threshold: f64) -> Vec<Candidate<'c>> {
+        let text: Vec<char> = text.chars().collect();
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+
+        for (gram, count) in grams(&text) {
+            for (pos, unit_count) in self.postings.get(&gram).into_iter().flatten() {
+                *shared.entry(*pos).or_insert(0) += count.min(*unit_count);
+            }
+        }
+
+        let mut positions: Vec<usize> = if threshold <= FULL_SCAN_THRESHOLD {
+            (0..self.units.len()).collect()
+        } else {
+            shared.keys().copied().collect()
+        };
+
+        positions.sort_unstable();
+
+        let mut res: Vec<Candidate<'c>> = positions
+            .into_iter()
+            .filter_map(|pos| {
+                let (unit, message) = &self.units[pos];
+                let len = text.len().max(message.len());
+                let max_distance = ((1.0 - threshold) * (len as f64) + 1e-9).floor().max(0.0) as usize;
+
+                // Each edit changes 2 bigrams at most, and the texts have `len + 1` bigrams.
+                if text.len().abs_diff(message.len()) > max_distance
+                    || shared.get(&pos).copied().unwrap_or_default() + 2 * max_distance < len + 1
+                {
+                    return None;
+                }
+
+                let distance = edit_distance(&text, message);
+                let score = if len == 0 {
+                    1.0
+                } else {
+                    1.0 - (distance as f64) / (len as f64)
+                };
+
+                Some(Candidate { unit, score }).filter(|_| distance <= max_distance && score >= threshold)
+            })
+            .collect();
+
+        res.sort_by(|a, b| b.score.total_cmp(&a.score));
+        res
+    }
+
+    /// Find the unit with the message the most similar to a text, if its similarity is greater or equal to the
+    /// threshold.
+    pub fn best(&self, text: &str, threshold: f64) -> Option<Candidate<'c>> {
+        self.find(text, threshold).into_iter().next()
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PoParser;
+
+    const SOURCE: &str = r#"msgid "Open the file"
+msgstr "Ouvrir le fichier"
+
+msgid "Open a file"
+msgstr "Ouvrir un fichier"
+
+msgid "Close the window"
+msgstr "Fermer la fenêtre"
+
+msgctxt "menu"
+msgid "Open the file"
+msgstr "Ouvrir le fichier…"
+
+msgid ""
+"A long message which is not similar"
+msgstr "Un long message"
+"#;
+
+    fn make_catalog() -> Catalog {
+        let parser = PoParser::new();
+
+        Catalog::from_reader(parser.parse(SOURCE.as_bytes()).unwrap()).unwrap()
+    }
+
+    fn found(index: &SimilarityIndex, text: &str, threshold: f64) -> Vec<(Option<String>, String, f64)> {
+        index
+            .find(text, threshold)
+            .into_iter()
+            .map(|c| {
+                let unit = c.unit();
+
+                (
+                    unit.context().map(String::from),
+                    unit.message().get_id().to_string(),
+                    c.score(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_func_similarity() {
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abc", "abc"), 1.0);
+        assert_eq!(similarity("abc", ""), 0.0);
+        assert_eq!(similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0);
+        assert_eq!(similarity("été", "ete"), 1.0 - 2.0 / 3.0);
+    }
+
+    #[test]
+    fn test_func_grams() {
+        let text: Vec<char> = "abab".chars().collect();
+        let grams = grams(&text);
+
+        assert_eq!(grams.values().sum::<usize>(), 5);
+        assert_eq!(grams.get(&['a', 'b']), Some(&2));
+        assert_eq!(grams.get(&['\0', 'a']), Some(&1));
+        assert_eq!(grams.get(&['b', '\0']), Some(&1));
+    }
+
+    #[test]
+    fn test_func_new() {
+        let catalog = make_catalog();
+        let index = SimilarityIndex::new(&catalog);
+
+        assert_eq!(index.len(), 5);
+        assert!(!index.is_empty());
+        assert!(SimilarityIndex::from_units(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_func_find() {
+        let catalog = make_catalog();
+        let index = SimilarityIndex::new(&catalog);
+        let score = 1.0 - 1.0 / 14.0;
+
+        assert_eq!(
+            found(&index, "Open the files", 0.6),
+            vec![
+                (None, String::from("Open the file"), score),
+                (Some(String::from("menu")), String::from("Open the file"), score),
+                (None, String::from("Open a file"), 1.0 - 4.0 / 14.0),
+            ]
+        );
+        assert_eq!(found(&index, "Open the files", 0.8).len(), 2);
+        assert!(found(&index, "Something else", 0.6).is_empty());
+        assert_eq!(found(&index, "Close the window", 1.0).len(), 1);
+    }
+
+    #[test]
+    fn test_func_find_with_low_threshold() {
+        let catalog = make_catalog();
+        let index = SimilarityIndex::new(&catalog);
+
+        assert_eq!(found(&index, "xyz", 0.0).len(), 5);
+        assert_eq!(
+            found(&index, "Opan thi fele", 0.5).first().map(|c| c.1.as_str()),
+            Some("Open the file")
+        );
+    }
+
+    #[test]
+    fn test_func_find_is_exhaustive() {
+        let texts = [
+            "abcdefghij",
+            "abXdeXghXj",
+            "XbcXefXhiX",
+            "aXcXeXgXiX",
+            "abcdefghijkl",
+            "jihgfedcba",
+            "a",
+            "",
+        ];
+        let units: Vec<Unit> = texts
+            .iter()
+            .map(|t| Unit {
+                message: crate::Message::Simple {
+                    id: t.to_string(),
+                    text: None,
+                },
+                ..Unit::default()
+            })
+            .collect();
+
+        let index = SimilarityIndex::from_units(&units);
+
+        for query in texts.iter().chain(&["abcdefXhij", "b", "XXXXXXXXXX"]) {
+            for threshold in [0.0, 0.3, 0.5, 0.55, 0.6, 0.7, 0.9, 1.0] {
+                let expected: Vec<&str> = texts
+                    .iter()
+                    .copied()
+                    .filter(|t| similarity(query, t) >= threshold)
+                    .collect();
+
+                let mut got: Vec<&str> = index
+                    .find(query, threshold)
+                    .into_iter()
+                    .map(|c| c.unit().message().get_id())
+                    .collect();
+
+                got.sort_by_key(|t| texts.iter().position(|x| x == t));
+                assert_eq!(got, expected, "Query {:?} with threshold {}", query, threshold);
+            }
+        }
+    }
+
+    #[test]
+    fn test_func_best() {
+        let catalog = make_catalog();
+        let index = SimilarityIndex::new(&catalog);
+
+        assert_eq!(
+            index
+                .best("Close the windows", 0.6)
+                .map(|c| c.unit().message().get_text()),
+            Some("Fermer la fenêtre")
+        );
+        assert!(index.best("Nothing", 0.6).is_none());
+    }
+}
+// no-coverage:stop
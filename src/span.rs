@@ -0,0 +1,110 @@
+//! Positions of the texts in a source stream.
+
+/// Position of a character in a source stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    pub(crate) fn new(offset: usize, line: usize, column: usize) -> Position {
+        Position { offset, line, column }
+    }
+
+    /// Byte offset from the beginning of the stream.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Line number, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column number in characters, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// Span of a text in a source stream, the end position is excluded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    start: Position,
+    end: Position,
+}
+
+impl Span {
+    pub(crate) fn new(start: Position, end: Position) -> Span {
+        Span { start, end }
+    }
+
+    /// Position of the first character.
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Position just after the last character.
+    pub fn end(&self) -> Position {
+        self.end
+    }
+
+    /// Length of the text in bytes.
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    /// Returns whether the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.end.offset == self.start.offset
+    }
+
+    /// Returns the span which goes from the start of this span to the end of the other one.
+    pub(crate) fn to(&self, other: &Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_span() -> Span {
+        Span::new(Position::new(10, 2, 3), Position::new(15, 2, 8))
+    }
+
+    #[test]
+    fn test_func_position() {
+        let pos = Position::new(10, 2, 3);
+
+        assert_eq!(pos.offset(), 10);
+        assert_eq!(pos.line(), 2);
+        assert_eq!(pos.column(), 3);
+        assert!(pos < Position::new(11, 2, 4));
+    }
+
+    #[test]
+    fn test_func_span() {
+        let span = make_span();
+
+        assert_eq!(span.start(), Position::new(10, 2, 3));
+        assert_eq!(span.end(), Position::new(15, 2, 8));
+        assert_eq!(span.len(), 5);
+        assert!(!span.is_empty());
+        assert!(Span::default().is_empty());
+    }
+
+    #[test]
+    fn test_func_to() {
+        let other = Span::new(Position::new(20, 3, 1), Position::new(30, 3, 11));
+
+        assert_eq!(
+            make_span().to(&other),
+            Span::new(Position::new(10, 2, 3), Position::new(30, 3, 11))
+        );
+    }
+}
+// no-coverage:stop
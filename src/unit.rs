@@ -1,5 +1,15 @@
-use super::{comment::Comment, note::Note, Message, State};
-use std::collections::HashSet;
+use super::{
+    comment::Comment,
+    flag::{Flag, FormatLanguage},
+    location::Location,
+    note::Note,
+    span::Span,
+    Message, State,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+};
 
 /// Elementary unit of translation.
 ///
@@ -16,24 +26,104 @@ use std::collections::HashSet;
 ///  - Previous source and context if the target is automatic suggestion from fuzzy matching.
 ///  - Obsolete flag, indicating the unit is not currently in use.
 ///  - Comments (which are not notes, locations and flags).
+///  - Spans of the entry and of its fields in the source stream, for units read from a PO stream.
+///
+/// The messages are plain text, except for the units read from XLIFF 2.x with inline codes, whose messages are XML
+/// markup (see [`is_markup`](Self::is_markup)).
 #[derive(Clone, Debug, Default)]
 pub struct Unit {
     pub(super) context: Option<String>,
     pub(super) message: Message,
     pub(super) prev_context: Option<String>,
     pub(super) prev_message: Message,
-    pub(super) flags: HashSet<String>,
+    pub(super) flags: HashSet<Flag>,
     pub(super) notes: Vec<Note>,
-    pub(super) locations: Vec<String>,
+    pub(super) locations: Vec<Location>,
     pub(super) comments: Vec<Comment>,
     pub(super) state: State,
     pub(super) obsolete: bool,
+    pub(super) markup: bool,
+    pub(super) source: Option<String>,
+    pub(super) span: Option<Span>,
+    pub(super) field_spans: HashMap<String, Span>,
+    pub(super) comment_spans: Vec<Span>,
 }
 
 impl Unit {
+    /// Create a unit for a message, its state being final if the message has a translation, else empty.
+    pub fn new(context: Option<String>, message: Message) -> Unit {
+        let state = if message.is_blank() { State::Empty } else { State::Final };
+
+        Unit {
+            context,
+            message,
+            state,
+            ..Unit::default()
+        }
+    }
+
+    /// Set the context string.
+    pub fn set_context(&mut self, context: Option<String>) {
+        self.context = context;
+        self.edited();
+    }
+
+    /// Set the message, with its translation.
+    pub fn set_message(&mut self, message: Message) {
+        self.message = message;
+        self.edited();
+    }
+
+    /// Set the flags, the state gives the flag `fuzzy` when the unit is written.
+    pub fn set_flags(&mut self, flags: HashSet<Flag>) {
+        self.flags = flags;
+        self.edited();
+    }
+
+    /// Set the notes.
+    pub fn set_notes(&mut self, notes: Vec<Note>) {
+        self.notes = notes;
+        self.edited();
+    }
+
+    /// Set the locations.
+    pub fn set_locations(&mut self, locations: Vec<Location>) {
+        self.locations = locations;
+        self.edited();
+    }
+
+    /// Set the custom comments.
+    pub fn set_comments(&mut self, comments: Vec<Comment>) {
+        self.comments = comments;
+        self.edited();
+    }
+
+    /// Set the state.
+    pub fn set_state(&mut self, state: State) {
+        self.state = state;
+        self.edited();
+    }
+
+    /// Set whether the unit is obsolete.
+    pub fn set_obsolete(&mut self, obsolete: bool) {
+        self.obsolete = obsolete;
+        self.edited();
+    }
+
+    /// Set whether the messages are XML markup.
+    pub fn set_markup(&mut self, markup: bool) {
+        self.markup = markup;
+        self.edited();
+    }
+
+    /// Forget the original text of an edited unit, so the unit is written from its fields.
+    fn edited(&mut self) {
+        self.source = None;
+    }
+
     /// Get the context string.
     pub fn context(&self) -> Option<&str> {
-        self.context.as_ref().map(String::as_str)
+        self.context.as_deref()
     }
 
     /// Get the message string.
@@ -43,7 +133,7 @@ impl Unit {
 
     /// Get the previous context (in fuzzy units).
     pub fn prev_context(&self) -> Option<&str> {
-        self.prev_context.as_ref().map(String::as_str)
+        self.prev_context.as_deref()
     }
 
     /// Get the previous message (in fuzzy units).
@@ -52,8 +142,31 @@ impl Unit {
     }
 
     /// Get the flags
-    pub fn flags(&self) -> &HashSet<String> {
-        return &self.flags;
+    pub fn flags(&self) -> &HashSet<Flag> {
+        &self.flags
+    }
+
+    /// Get the languages of the format strings in the message, from the flags `<lang>-format`, sorted by name.
+    pub fn format_languages(&self) -> Vec<FormatLanguage> {
+        let mut res: Vec<FormatLanguage> = self
+            .flags
+            .iter()
+            .filter_map(|flag| match flag {
+                Flag::Format(lang) => Some(*lang),
+                _ => None,
+            })
+            .collect();
+
+        res.sort_unstable_by_key(FormatLanguage::name);
+        res
+    }
+
+    /// Get the range of the numbers given to the plural message, from the flag `range: min..max`.
+    pub fn range(&self) -> Option<RangeInclusive<u64>> {
+        self.flags.iter().find_map(|flag| match flag {
+            Flag::Range(range) => Some(range.clone()),
+            _ => None,
+        })
     }
 
     /// Get the notes/comments.
@@ -62,7 +175,7 @@ impl Unit {
     }
 
     /// Get locations.
-    pub fn locations(&self) -> &Vec<String> {
+    pub fn locations(&self) -> &Vec<Location> {
         &self.locations
     }
 
@@ -85,19 +198,50 @@ impl Unit {
     pub fn is_obsolete(&self) -> bool {
         self.obsolete
     }
+
+    /// Returns whether the messages are XML markup, with their text escaped and the inline codes `<ph>` and `<pc>` as
+    /// tags, like the units read from XLIFF 2.x with inline codes. The messages are plain text otherwise.
+    pub fn is_markup(&self) -> bool {
+        self.markup
+    }
+
+    /// Get the original text of the unit, only kept when the catalogue is read in lossless mode.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Get the span of the whole entry in the source stream, from its first comment to its last string.
+    pub fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+
+    /// Get the span of a field in the source stream, from its keyword to the end of its last string.
+    ///
+    /// Fields are named with their keyword (`msgctxt`, `msgid`, `msgid_plural`, `msgstr` or `msgstr[n]`), the fields
+    /// of the previous message are prefixed by `|` (like `|msgid`).
+    pub fn field_span(&self, field: &str) -> Option<&Span> {
+        self.field_spans.get(field)
+    }
+
+    /// Get the spans of the comment lines of the entry (notes, locations, flags and comments), in the order of the
+    /// source stream.
+    pub fn comment_spans(&self) -> &Vec<Span> {
+        &self.comment_spans
+    }
 }
 
 // no-coverage:start
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
-    use crate::Origin;
+    use crate::{span::Position, Origin};
 
     impl Unit {
         pub(crate) fn for_tests_empty() -> Self {
             let mut res = Self::default();
 
-            res.flags = (1..=3).map(|i| format!("empty-flag-{}", i)).collect();
+            res.flags = (1..=3).map(|i| Flag::Other(format!("empty-flag-{}", i))).collect();
             res.notes = (1..=4)
                 .map(|i| Note::new(Origin::Translator, format!("empty translator note {}", i)))
                 .collect();
@@ -105,7 +249,7 @@ mod tests {
             res.locations = vec![11, 22, 33]
                 .into_iter()
                 .enumerate()
-                .map(|(i, n)| format!("EmptyFile{}:{}", i + 1, n))
+                .map(|(i, n)| Location::new(format!("EmptyFile{}", i + 1), Some(n)))
                 .collect();
 
             res.state = State::Empty;
@@ -127,21 +271,17 @@ mod tests {
                 text: Some(String::from("text")),
             };
 
-            res.flags = (1..=3).map(|i| format!("flag{}", i)).collect();
+            res.flags = (1..=3).map(|i| Flag::Other(format!("flag{}", i))).collect();
             res.comments = (1..=3).map(|i| Comment::new('X', format!("Comment {}", i))).collect();
             res.notes = (1..=2)
                 .map(|i| Note::new(Origin::Translator, format!("translator note {}", i)))
-                .chain(
-                    (1..=2)
-                        .into_iter()
-                        .map(|i| Note::new(Origin::Developer, format!("developper note {}", i))),
-                )
+                .chain((1..=2).map(|i| Note::new(Origin::Developer, format!("developper note {}", i))))
                 .collect();
 
             res.locations = vec![12, 34, 56]
                 .into_iter()
                 .enumerate()
-                .map(|(i, n)| format!("File{}:{}", i + 1, n))
+                .map(|(i, n)| Location::new(format!("File{}", i + 1), Some(n)))
                 .collect();
 
             res.state = State::Final;
@@ -157,7 +297,7 @@ mod tests {
                 text: Some(String::from("incomplete text")),
             };
 
-            res.flags = (1..=3).map(|i| format!("flag{}", i)).collect();
+            res.flags = (1..=3).map(|i| Flag::Other(format!("flag{}", i))).collect();
             res.obsolete = true;
             res.state = State::NeedsWork;
 
@@ -182,6 +322,58 @@ mod tests {
 
     impl Eq for Unit {}
 
+    #[test]
+    fn test_func_new() {
+        let message = |text: Option<&str>| Message::Simple {
+            id: String::from("message"),
+            text: text.map(String::from),
+        };
+        let unit = Unit::new(Some(String::from("context")), message(Some("texte")));
+
+        assert_eq!(unit.context(), Some("context"));
+        assert_eq!(unit.message(), &message(Some("texte")));
+        assert_eq!(unit.state(), State::Final);
+        assert!(!unit.is_obsolete(), "A new unit should not be obsolete");
+        assert!(unit.flags().is_empty(), "A new unit should have no flag");
+        assert_eq!(Unit::new(None, message(None)).state(), State::Empty);
+    }
+
+    #[test]
+    fn test_func_setters() {
+        let mut unit = Unit::for_tests_normal();
+        let message = Message::Simple {
+            id: String::from("other"),
+            text: Some(String::from("autre")),
+        };
+        let flags: HashSet<Flag> = [Flag::Format(FormatLanguage::C)].into_iter().collect();
+        let notes = vec![Note::new(Origin::Developer, String::from("note"))];
+        let locations = vec![Location::new("main.rs", Some(3))];
+        let comments = vec![Comment::new(' ', String::from("comment"))];
+
+        unit.source = Some(String::from("msgid \"message\"\nmsgstr \"text\"\n"));
+        unit.set_context(None);
+        assert_eq!(unit.source(), None);
+
+        unit.set_message(message.clone());
+        unit.set_flags(flags.clone());
+        unit.set_notes(notes.clone());
+        unit.set_locations(locations.clone());
+        unit.set_comments(comments.clone());
+        unit.set_state(State::NeedsWork);
+        unit.set_obsolete(true);
+        unit.set_markup(true);
+
+        assert_eq!(unit.context(), None);
+        assert_eq!(unit.message(), &message);
+        assert_eq!(unit.flags(), &flags);
+        assert_eq!(unit.notes(), &notes);
+        assert_eq!(unit.locations(), &locations);
+        assert_eq!(unit.comments(), &comments);
+        assert_eq!(unit.state(), State::NeedsWork);
+        assert!(unit.is_obsolete(), "The unit should be obsolete");
+        assert!(unit.is_markup(), "The messages should be markup");
+    }
+
     #[test]
     fn test_func_context() {
         let empty = Unit::for_tests_empty();
@@ -234,7 +426,32 @@ mod tests {
         assert!(Unit::default().flags().is_empty(), "Empty unit should have no flag");
         assert!(!flags.is_empty(), "Normal unit should have flags");
         assert_eq!(flags.len(), 3);
-        assert!(flags.contains("flag1"));
+        assert!(flags.contains(&Flag::Other(String::from("flag1"))));
+    }
+
+    #[test]
+    fn test_func_format_languages() {
+        let mut unit = Unit::for_tests_normal();
+
+        assert!(unit.format_languages().is_empty());
+
+        unit.flags.extend(
+            ["python-format", "no-rust-format", "c-format", "possible-sh-format"]
+                .into_iter()
+                .map(Flag::parse),
+        );
+
+        assert_eq!(unit.format_languages(), vec![FormatLanguage::C, FormatLanguage::Python]);
+    }
+
+    #[test]
+    fn test_func_range() {
+        let mut unit = Unit::for_tests_normal();
+
+        assert_eq!(unit.range(), None);
+
+        unit.flags.insert(Flag::parse("range: 1..12"));
+        assert_eq!(unit.range(), Some(1..=12));
     }
 
     #[test]
@@ -246,7 +463,7 @@ mod tests {
         assert!(!notes.is_empty(), "Normal unit should have notes");
         assert_eq!(notes.len(), 4);
 
-        if let Some(note) = notes.into_iter().next() {
+        if let Some(note) = notes.iter().next() {
             assert_eq!(note.value(), "translator note 1");
         } else {
             panic!("Normal unit should hanve at least one note");
@@ -264,9 +481,9 @@ mod tests {
         assert_eq!(
             locations,
             &vec![
-                String::from("File1:12"),
-                String::from("File2:34"),
-                String::from("File3:56"),
+                Location::new("File1", Some(12)),
+                Location::new("File2", Some(34)),
+                Location::new("File3", Some(56)),
             ]
         );
     }
@@ -339,6 +556,34 @@ mod tests {
         assert!(!unit.obsolete, "Empty unit should not be obsolete");
     }
 
+    #[test]
+    fn test_func_source() {
+        let mut unit = Unit::for_tests_normal();
+
+        assert_eq!(unit.source(), None);
+
+        unit.source = Some(String::from("msgid \"a\"\nmsgstr \"b\"\n"));
+        assert_eq!(unit.source(), Some("msgid \"a\"\nmsgstr \"b\"\n"));
+    }
+
+    #[test]
+    fn test_func_spans() {
+        let mut unit = Unit::for_tests_normal();
+        let span = Span::new(Position::new(10, 2, 1), Position::new(20, 2, 11));
+
+        assert_eq!(unit.span(), None);
+        assert_eq!(unit.field_span("msgid"), None);
+        assert!(unit.comment_spans().is_empty());
+
+        unit.span = Some(span);
+        unit.field_spans.insert(String::from("msgid"), span);
+        unit.comment_spans.push(span);
+        assert_eq!(unit.span(), Some(&span));
+        assert_eq!(unit.field_span("msgid"), Some(&span));
+        assert_eq!(unit.field_span("msgstr"), None);
+        assert_eq!(unit.comment_spans(), &vec![span]);
+    }
+
     #[test]
     fn test_trait_debug() {
         assert_eq!(
@@ -354,7 +599,12 @@ mod tests {
                     locations: [], \
                     comments: [], \
                     state: Empty, \
-                    obsolete: false \
+                    obsolete: false, \
+                    markup: false, \
+                    source: None, \
+                    span: None, \
+                    field_spans: {}, \
+                    comment_spans: [] \
                 }"
             ),
         )
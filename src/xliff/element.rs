@@ -0,0 +1,288 @@
+use crate::error::{Error, ErrorKind};
+use quick_xml::{
+    escape::{escape, partial_escape},
+    events::Event,
+    Reader,
+};
+use std::io::Read;
+
+/// Node in the content of an XML element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Node {
+    Element(Element),
+    Text(String),
+}
+
+/// Minimal XML element, namespaces prefixes are removed from the element names.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Element {
+    name: String,
+    line: usize,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    /// Read a whole XML document and returns its root element.
+    pub(super) fn parse<R: Read>(mut reader: R) -> Result<Element, Error> {
+        let mut data = vec![];
+
+        reader.read_to_end(&mut data).map_err(|err| Error::io(0, err))?;
+
+        let line_at = |pos: usize| 1 + data[..pos.min(data.len())].iter().filter(|b| **b == b'\n').count();
+        let mut xml = Reader::from_reader(&data[..]);
+        let mut stack: Vec<Element> = vec![];
+        let mut buf = vec![];
+
+        xml.expand_empty_elements(true);
+        loop {
+            let pos = xml.buffer_position();
+            let event = xml.read_event_into(&mut buf).map_err(|err| {
+                Error::new(ErrorKind::Other, err.to_string())
+                    .with_line(line_at(xml.buffer_position()))
+                    .with_source(err)
+            })?;
+
+            let xml_error = |err: quick_xml::Error| {
+                Error::new(ErrorKind::Other, err.to_string())
+                    .with_line(line_at(pos))
+                    .with_source(err)
+            };
+
+            match event {
+                Event::Start(start) => {
+                    let mut attributes = vec![];
+
+                    for attr in start.attributes() {
+                        let attr = attr.map_err(|err| xml_error(err.into()))?;
+                        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+
+                        attributes.push((key, attr.unescape_value().map_err(xml_error)?.to_string()));
+                    }
+
+                    stack.push(Element {
+                        name: String::from_utf8_lossy(start.local_name().as_ref()).to_string(),
+                        line: line_at(pos),
+                        attributes,
+                        children: vec![],
+                    });
+                }
+                Event::End(_) => {
+                    let element = stack
+                        .pop()
+                        .ok_or_else(|| Error::new(ErrorKind::Other, "Unexpected end").with_line(line_at(pos)))?;
+
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(Node::Element(element)),
+                        None => {
+                            return Ok(element);
+                        }
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.push_text(&text.unescape().map_err(xml_error)?);
+                    }
+                }
+                Event::CData(text) => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.push_text(&String::from_utf8_lossy(&text.into_inner()));
+                    }
+                }
+                Event::Eof => {
+                    let msg = if stack.is_empty() {
+                        "No root element"
+                    } else {
+                        "Unexpected end of document"
+                    };
+
+                    return Err(Error::new(ErrorKind::Other, msg).with_line(line_at(pos)));
+                }
+                _ => (),
+            }
+
+            buf.clear();
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        match self.children.last_mut() {
+            Some(Node::Text(prev)) => prev.push_str(text),
+            _ => self.children.push(Node::Text(text.to_string())),
+        }
+    }
+
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(super) fn line(&self) -> usize {
+        self.line
+    }
+
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Iterate on the child elements.
+    pub(super) fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// Iterate on the child elements with a given name.
+    pub(super) fn elements_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |e| e.name == name)
+    }
+
+    /// Get the first child element with a given name.
+    pub(super) fn element(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    /// Get the text content, with the text of the inner elements.
+    pub(super) fn text(&self) -> String {
+        let mut res = String::new();
+
+        for node in self.children.iter() {
+            match node {
+                Node::Text(text) => res.push_str(text),
+                Node::Element(element) => res.push_str(&element.text()),
+            }
+        }
+
+        res
+    }
+
+    /// Whether an inner element, at any depth, has one of the given names.
+    pub(super) fn contains(&self, names: &[&str]) -> bool {
+        self.elements().any(|e| names.contains(&e.name()) || e.contains(names))
+    }
+
+    /// Get the content as XML markup, with the tags of the elements with the given names and the text escaped.
+    ///
+    /// The tags of the other elements are removed, like in [`text`](Self::text).
+    pub(super) fn content(&self, opaque: &[&str]) -> String {
+        let mut res = String::new();
+
+        for node in self.children.iter() {
+            match node {
+                Node::Text(text) => res.push_str(&partial_escape(text)),
+                Node::Element(element) if opaque.contains(&element.name()) => {
+                    res.push('<');
+                    res.push_str(&element.name);
+                    for (k, v) in element.attributes.iter() {
+                        res.push_str(&format!(" {}=\"{}\"", k, escape(v)));
+                    }
+
+                    if element.children.is_empty() {
+                        res.push_str("/>");
+                    } else {
+                        res.push('>');
+                        res.push_str(&element.content(opaque));
+                        res.push_str(&format!("</{}>", element.name));
+                    }
+                }
+                Node::Element(element) => res.push_str(&element.content(opaque)),
+            }
+        }
+
+        res
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SOURCE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Comment -->
+<doc xmlns:x="urn:test" version="1.2">
+  <x:item id="a &amp; b">Some <b>bold</b> text<![CDATA[ & <raw>]]></x:item>
+  <empty/>
+  <item id="2">&lt;escaped&gt;</item>
+</doc>
+"#;
+
+    #[test]
+    fn test_func_parse() {
+        let root = Element::parse(SOURCE.as_bytes()).unwrap();
+
+        assert_eq!(root.name(), "doc");
+        assert_eq!(root.line(), 3);
+        assert_eq!(root.attribute("version"), Some("1.2"));
+        assert_eq!(root.attribute("xmlns:x"), Some("urn:test"));
+        assert_eq!(root.attribute("id"), None);
+        assert_eq!(
+            root.elements().map(Element::name).collect::<Vec<_>>(),
+            vec!["item", "empty", "item"]
+        );
+
+        let item = root.element("item").unwrap();
+
+        assert_eq!(item.line(), 4);
+        assert_eq!(item.attribute("id"), Some("a & b"));
+        assert_eq!(item.children.len(), 3);
+        assert_eq!(item.text(), "Some bold text & <raw>");
+
+        let empty = root.element("empty").unwrap();
+
+        assert!(empty.children.is_empty(), "The element should be empty");
+        assert_eq!(empty.text(), "");
+
+        let items: Vec<_> = root.elements_named("item").map(Element::text).collect();
+
+        assert_eq!(
+            items,
+            vec![String::from("Some bold text & <raw>"), String::from("<escaped>")]
+        );
+        assert!(
+            root.element("missing").is_none(),
+            "There should be no element `missing`"
+        );
+    }
+
+    #[test]
+    fn test_func_content() {
+        let source = r#"<s>Hello <ph id="1" disp="&lt;br&gt;"/> <pc id="2">big <b>&amp;</b> <ph id="3"/></pc>!</s>"#;
+        let root = Element::parse(source.as_bytes()).unwrap();
+
+        assert_eq!(root.text(), "Hello  big & !");
+        assert_eq!(
+            root.content(&["ph", "pc"]),
+            r#"Hello <ph id="1" disp="&lt;br&gt;"/> <pc id="2">big &amp; <ph id="3"/></pc>!"#
+        );
+        assert!(root.contains(&["ph"]), "The element should contain a placeholder");
+        assert!(!root.contains(&["sc"]), "The element should not contain any `sc` element");
+    }
+
+    #[test]
+    fn test_func_parse_with_error() {
+        match Element::parse("<doc>\n<item></other>\n</doc>".as_bytes()) {
+            Err(err) if err.line() == 2 => (),
+            r => panic!("Unexpected result for mismatched tags: {:?}", r),
+        }
+
+        match Element::parse("<!-- Nothing -->".as_bytes()) {
+            Err(err) => assert_eq!(format!("{:?}", err), "Unexpected error at line 1: No root element"),
+            r => panic!("Unexpected result for an empty document: {:?}", r),
+        }
+
+        match Element::parse("<doc>\n<item>".as_bytes()) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 2: Unexpected end of document"
+            ),
+            r => panic!("Unexpected result for an unclosed element: {:?}", r),
+        }
+
+        match Element::parse(r#"<doc a="1" a="2"/>"#.as_bytes()) {
+            Err(err) if err.line() == 1 => (),
+            r => panic!("Unexpected result for a duplicated attribute: {:?}", r),
+        }
+    }
+}
+// no-coverage:stop
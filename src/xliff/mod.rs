@@ -0,0 +1,24 @@
+//! Handling of [XML Localisation Interchange File Format][XLIFF]
+//!
+//! This format is an OASIS open standard, more generic than the PO format. Versions 1.2 and 2.x
+//! are read, and documents are written in version 2.0.
+//!
+//! Units are mapped the way [translate-toolkit][tt] converts PO files to XLIFF 1.2: plural variants
+//! of a message are in a group with the restype `x-gettext-plurals`, and locations are in context
+//! groups with the purpose `location`. In XLIFF 2.x, plural variants are in a group with the type
+//! `x-gettext:plurals`, and the context and the locations are in notes with the categories
+//! `x-gettext-msgctxt` and `location`. The messages of the XLIFF 2.x units with inline codes
+//! (`<ph>` and `<pc>`) are XML markup, with the inline codes as tags and the text escaped, and
+//! they are written back as is. The other messages are plain text, escaped entirely when they are
+//! written.
+//!
+//! [XLIFF]: https://www.oasis-open.org/committees/xliff/
+//! [tt]: http://toolkit.translatehouse.org/
+
+mod element;
+mod reader;
+mod v1;
+mod v2;
+mod writer;
+
+pub use self::{reader::XliffReader, writer::XliffWriter};
@@ -0,0 +1,390 @@
+use super::{element::Element, v1, v2};
+use crate::{
+    comment::Comment,
+    error::{Error, ErrorKind},
+    header::parse_language,
+    note::Note,
+    unit::Unit,
+    CatalogueReader,
+};
+
+use locale_config::LanguageRange;
+use std::{collections::HashMap, io::Read, vec::IntoIter};
+
+/// Object for reading XLIFF documents, in versions 1.2 and 2.x
+///
+/// The whole document is loaded when the reader is created, then an iterator is implemented for reading each unit of
+/// translation. Units of all `<file>` elements are read, the notes of the header are the ones of the first file.
+pub struct XliffReader {
+    units: IntoIter<Unit>,
+    header_notes: Vec<Note>,
+    header_comments: Vec<Comment>,
+    header_properties: HashMap<String, String>,
+    target_language: LanguageRange<'static>,
+}
+
+impl XliffReader {
+    /// Read a XLIFF document, the version is given by the root element.
+    pub fn new<R: Read>(reader: R) -> Result<XliffReader, Error> {
+        let root = Element::parse(reader)?;
+
+        if root.name() != "xliff" {
+            return Err(Error::new(
+                ErrorKind::Other,
+                format!("Expected ‘xliff’ element, got ‘{}’", root.name()),
+            )
+            .with_line(root.line()));
+        }
+
+        let is_v2 = match root.attribute("version") {
+            Some(version) if version.starts_with("1.") => false,
+            Some(version) if version.starts_with("2.") => true,
+            version => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("Unsupported XLIFF version ‘{}’", version.unwrap_or_default()),
+                )
+                .with_line(root.line()));
+            }
+        };
+
+        let mut res = XliffReader {
+            units: vec![].into_iter(),
+            header_notes: vec![],
+            header_comments: vec![],
+            header_properties: HashMap::new(),
+            target_language: LanguageRange::invariant(),
+        };
+
+        let mut units = vec![];
+        let mut language = root.attribute("trgLang").filter(|_| is_v2);
+
+        for (i, file) in root.elements_named("file").enumerate() {
+            if i == 0 {
+                if is_v2 {
+                    res.header_notes = v2::read_file_notes(file);
+                } else {
+                    res.header_notes = v1::read_header_notes(file);
+                    language = file.attribute("target-language");
+                }
+            }
+
+            if is_v2 {
+                v2::read_file(file, &mut units)?;
+            } else {
+                v1::read_file(file, &mut units)?;
+            }
+        }
+
+        if let Some(lang) = language {
+            res.header_properties.insert(String::from("Language"), lang.to_string());
+            res.target_language = parse_language(lang);
+        }
+
+        res.units = units.into_iter();
+
+        Ok(res)
+    }
+}
+
+impl Iterator for XliffReader {
+    type Item = Result<Unit, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.units.next().map(Ok)
+    }
+}
+
+impl CatalogueReader for XliffReader {
+    fn target_language(&self) -> &LanguageRange<'static> {
+        &self.target_language
+    }
+
+    fn header_notes(&self) -> &Vec<Note> {
+        &self.header_notes
+    }
+
+    fn header_comments(&self) -> &Vec<Comment> {
+        &self.header_comments
+    }
+
+    fn header_properties(&self) -> &HashMap<String, String> {
+        &self.header_properties
+    }
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{location::Location, Message, Origin, State};
+
+    static SOURCE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+  <file original="messages.po" source-language="en" target-language="fr" datatype="po">
+    <header>
+      <note from="translator">Header note</note>
+    </header>
+    <body>
+      <trans-unit id="1">
+        <source>Hello</source>
+        <target state="translated">Salut</target>
+        <note from="developer">Greeting</note>
+        <note from="translator">Informal</note>
+        <context-group name="po-reference" purpose="location">
+          <context context-type="sourcefile">main.c</context>
+          <context context-type="linenumber">12</context>
+        </context-group>
+        <context-group purpose="location">
+          <context context-type="sourcefile">other.c</context>
+        </context-group>
+      </trans-unit>
+      <group id="g1">
+        <trans-unit id="2" approved="no">
+          <source>File</source>
+          <target>Fichier</target>
+          <context-group name="po-entry" purpose="information">
+            <context context-type="x-gettext-msgctxt">Menu</context>
+          </context-group>
+        </trans-unit>
+        <trans-unit id="3">
+          <source>Untranslated</source>
+        </trans-unit>
+      </group>
+      <group id="4" restype="x-gettext-plurals">
+        <note from="developer">Files count</note>
+        <trans-unit id="4[0]">
+          <source>One file</source>
+          <target state="needs-review-translation">Un fichier</target>
+        </trans-unit>
+        <trans-unit id="4[1]">
+          <source>Several files</source>
+          <target state="needs-review-translation">Des fichiers</target>
+        </trans-unit>
+      </group>
+    </body>
+  </file>
+  <file original="other.po" source-language="en" target-language="de" datatype="po">
+    <body>
+      <trans-unit id="5">
+        <source>Bye</source>
+        <target state="new"></target>
+      </trans-unit>
+    </body>
+  </file>
+</xliff>
+"#;
+
+    static SOURCE_V2: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.1" srcLang="en" trgLang="pt-BR">
+  <file id="f1">
+    <notes>
+      <note category="translator">File note</note>
+    </notes>
+    <unit id="u1">
+      <notes>
+        <note category="x-gettext-msgctxt">Menu</note>
+        <note category="location">main.c:12</note>
+        <note category="developer">Greeting</note>
+      </notes>
+      <segment state="final">
+        <source>Hello <ph id="1"/></source>
+        <target>Ol&#225; <ph id="1"/></target>
+      </segment>
+    </unit>
+    <group id="g1">
+      <unit id="u2">
+        <segment>
+          <source>Untranslated</source>
+        </segment>
+      </unit>
+    </group>
+    <group id="u3" type="x-gettext:plurals">
+      <unit id="u3-0">
+        <segment state="initial"><source>One file</source><target>Um arquivo</target></segment>
+      </unit>
+      <unit id="u3-1">
+        <segment state="initial"><source>Several files</source><target>Arquivos</target></segment>
+      </unit>
+    </group>
+  </file>
+</xliff>
+"#;
+
+    #[test]
+    fn test_func_new() {
+        let reader = XliffReader::new(SOURCE.as_bytes()).unwrap();
+
+        assert_eq!(reader.target_language().as_ref(), "fr");
+        assert_eq!(
+            reader.header_notes(),
+            &vec![Note::new(Origin::Translator, String::from("Header note"))]
+        );
+
+        assert!(reader.header_comments().is_empty(), "There should be no comment");
+        assert_eq!(
+            reader.header_properties(),
+            &[("Language", "fr")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        );
+    }
+
+    #[test]
+    fn test_func_new_with_error() {
+        match XliffReader::new("<xliff version=\"3.0\"/>".as_bytes()) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 1: Unsupported XLIFF version ‘3.0’"
+            ),
+            r => panic!("Unexpected result for a bad version: {:?}", r.err()),
+        }
+
+        match XliffReader::new("<xliff version=\"2.0\"><file><unit id=\"1\"/></file></xliff>".as_bytes()) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 1: Missing ‘segment’ element in ‘unit’"
+            ),
+            r => panic!("Unexpected result for a missing segment: {:?}", r.err()),
+        }
+
+        match XliffReader::new("<html/>".as_bytes()) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 1: Expected ‘xliff’ element, got ‘html’"
+            ),
+            r => panic!("Unexpected result for a bad root: {:?}", r.err()),
+        }
+
+        let source = "<xliff version=\"1.2\"><file><body>\n<trans-unit id=\"1\"/></body></file></xliff>";
+
+        match XliffReader::new(source.as_bytes()) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 2: Missing ‘source’ element in ‘trans-unit’"
+            ),
+            r => panic!("Unexpected result for a missing source: {:?}", r.err()),
+        }
+
+        let source =
+            "<xliff version=\"1.2\"><file><body>\n<group restype=\"x-gettext-plurals\"/></body></file></xliff>";
+
+        match XliffReader::new(source.as_bytes()) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 2: Missing ‘trans-unit’ element in plural group"
+            ),
+            r => panic!("Unexpected result for an empty plural group: {:?}", r.err()),
+        }
+    }
+
+    #[test]
+    fn test_trait_iterator() {
+        let units = XliffReader::new(SOURCE.as_bytes())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(units.len(), 5);
+
+        assert_eq!(units[0].context(), None);
+        assert_eq!(units[0].message().get_id(), "Hello");
+        assert_eq!(units[0].message().get_text(), "Salut");
+        assert_eq!(units[0].state(), State::Final);
+        assert_eq!(
+            units[0].locations(),
+            &vec![Location::new("main.c", Some(12)), Location::new("other.c", None)]
+        );
+        assert_eq!(
+            units[0].notes(),
+            &vec![
+                Note::new(Origin::Developer, String::from("Greeting")),
+                Note::new(Origin::Translator, String::from("Informal")),
+            ]
+        );
+
+        assert_eq!(units[1].context(), Some("Menu"));
+        assert_eq!(units[1].message().get_text(), "Fichier");
+        assert_eq!(units[1].state(), State::NeedsWork);
+
+        assert_eq!(
+            units[2].message(),
+            &Message::Simple {
+                id: String::from("Untranslated"),
+                text: None,
+            }
+        );
+        assert_eq!(units[2].state(), State::Empty);
+
+        match units[3].message().plural() {
+            Some(plural) => {
+                assert_eq!(plural.singular(), "One file");
+                assert_eq!(plural.plural(), "Several files");
+                assert_eq!(
+                    plural.values(),
+                    &vec![String::from("Un fichier"), String::from("Des fichiers")]
+                );
+            }
+            None => panic!("Plural message expected, got {:?}", units[3].message()),
+        }
+
+        assert_eq!(units[3].state(), State::NeedsWork);
+        assert_eq!(
+            units[3].notes(),
+            &vec![Note::new(Origin::Developer, String::from("Files count"))]
+        );
+
+        assert_eq!(units[4].message().get_id(), "Bye");
+        assert_eq!(units[4].state(), State::Empty);
+    }
+
+    #[test]
+    fn test_trait_iterator_for_v2() {
+        let reader = XliffReader::new(SOURCE_V2.as_bytes()).unwrap();
+
+        assert_eq!(reader.target_language().as_ref(), "pt-BR");
+        assert_eq!(
+            reader.header_notes(),
+            &vec![Note::new(Origin::Translator, String::from("File note"))]
+        );
+
+        let units = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(units.len(), 3);
+
+        assert_eq!(units[0].context(), Some("Menu"));
+        assert_eq!(units[0].message().get_id(), r#"Hello <ph id="1"/>"#);
+        assert_eq!(units[0].message().get_text(), r#"Olá <ph id="1"/>"#);
+        assert_eq!(units[0].state(), State::Final);
+        assert_eq!(units[0].locations(), &vec![Location::new("main.c", Some(12))]);
+        assert_eq!(
+            units[0].notes(),
+            &vec![Note::new(Origin::Developer, String::from("Greeting"))]
+        );
+
+        assert_eq!(
+            units[1].message(),
+            &Message::Simple {
+                id: String::from("Untranslated"),
+                text: None,
+            }
+        );
+        assert_eq!(units[1].state(), State::Empty);
+
+        match units[2].message().plural() {
+            Some(plural) => {
+                assert_eq!(plural.singular(), "One file");
+                assert_eq!(plural.plural(), "Several files");
+                assert_eq!(
+                    plural.values(),
+                    &vec![String::from("Um arquivo"), String::from("Arquivos")]
+                );
+            }
+            None => panic!("Plural message expected, got {:?}", units[2].message()),
+        }
+
+        assert_eq!(units[2].state(), State::NeedsWork);
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,180 @@
+use super::element::Element;
+use crate::{
+    error::{Error, ErrorKind},
+    location::Location,
+    note::Note,
+    plural::Plural,
+    unit::Unit,
+    Message, Origin, State,
+};
+
+/// Restype of the groups containing the plural variants of a gettext message.
+const PLURALS_RESTYPE: &str = "x-gettext-plurals";
+
+/// Context type of the context of a gettext message.
+const MSGCTXT_CONTEXT_TYPE: &str = "x-gettext-msgctxt";
+
+fn make_note(note: &Element) -> Note {
+    let origin = match note.attribute("from") {
+        Some(from) if from.contains("developer") => Origin::Developer,
+        _ => Origin::Translator,
+    };
+
+    Note::new(origin, note.text())
+}
+
+fn make_state(trans_unit: &Element, target: Option<&str>) -> State {
+    let is_blank = target.unwrap_or_default().is_empty();
+
+    match trans_unit.element("target").and_then(|t| t.attribute("state")) {
+        Some("new" | "needs-translation") => State::Empty,
+        Some("translated" | "signed-off" | "final") => State::Final,
+        Some(_) => State::NeedsWork,
+        None if is_blank => State::Empty,
+        None if trans_unit.attribute("approved") == Some("no") => State::NeedsWork,
+        None => State::Final,
+    }
+}
+
+/// Read the notes of the header of a `<file>` element.
+pub(super) fn read_header_notes(file: &Element) -> Vec<Note> {
+    file.element("header")
+        .map(|header| header.elements_named("note").map(make_note).collect())
+        .unwrap_or_default()
+}
+
+/// Read the units of a `<file>` element.
+pub(super) fn read_file(file: &Element, units: &mut Vec<Unit>) -> Result<(), Error> {
+    match file.element("body") {
+        Some(body) => read_units(body, units),
+        None => Ok(()),
+    }
+}
+
+fn read_units(parent: &Element, units: &mut Vec<Unit>) -> Result<(), Error> {
+    for element in parent.elements() {
+        match element.name() {
+            "trans-unit" => units.push(read_trans_unit(element)?),
+            "group" if element.attribute("restype") == Some(PLURALS_RESTYPE) => units.push(read_plural(element)?),
+            "group" => read_units(element, units)?,
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+fn read_source(trans_unit: &Element) -> Result<String, Error> {
+    match trans_unit.element("source") {
+        Some(source) => Ok(source.text()),
+        None => {
+            Err(Error::new(ErrorKind::Other, "Missing ‘source’ element in ‘trans-unit’").with_line(trans_unit.line()))
+        }
+    }
+}
+
+fn read_annotations(element: &Element, unit: &mut Unit) {
+    unit.notes.extend(element.elements_named("note").map(make_note));
+
+    for group in element.elements_named("context-group") {
+        let mut file = None;
+        let mut line = None;
+
+        for context in group.elements_named("context") {
+            match context.attribute("context-type") {
+                Some("sourcefile") => file = Some(context.text()),
+                Some("linenumber") => line = Some(context.text()),
+                Some(MSGCTXT_CONTEXT_TYPE) => unit.context = Some(context.text()),
+                _ => (),
+            }
+        }
+
+        if group.attribute("purpose") == Some("location") {
+            if let Some(file) = file {
+                unit.locations
+                    .push(Location::new(file, line.and_then(|l| l.trim().parse().ok())));
+            }
+        }
+    }
+}
+
+fn read_trans_unit(trans_unit: &Element) -> Result<Unit, Error> {
+    let mut unit = Unit::default();
+    let id = read_source(trans_unit)?;
+    let text = trans_unit.element("target").map(Element::text);
+
+    read_annotations(trans_unit, &mut unit);
+    unit.state = make_state(trans_unit, text.as_deref());
+    unit.message = Message::Simple { id, text };
+
+    Ok(unit)
+}
+
+fn read_plural(group: &Element) -> Result<Unit, Error> {
+    let trans_units: Vec<_> = group.elements_named("trans-unit").collect();
+    let first = match trans_units.first() {
+        Some(first) => *first,
+        None => {
+            return Err(
+                Error::new(ErrorKind::Other, "Missing ‘trans-unit’ element in plural group").with_line(group.line()),
+            );
+        }
+    };
+
+    let mut unit = Unit::default();
+    let singular = read_source(first)?;
+    let plural = match trans_units.get(1) {
+        Some(second) => read_source(second)?,
+        None => singular.clone(),
+    };
+
+    let values: Vec<_> = trans_units
+        .iter()
+        .map(|t| t.element("target").map(Element::text).unwrap_or_default())
+        .collect();
+
+    read_annotations(group, &mut unit);
+    read_annotations(first, &mut unit);
+    unit.state = if values.iter().all(String::is_empty) {
+        State::Empty
+    } else {
+        make_state(first, Some(&values[0]))
+    };
+
+    unit.message = Message::Plural(Plural::new(singular, plural, values, None));
+
+    Ok(unit)
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_func_make_state() {
+        let parse = |s: &str| Element::parse(s.as_bytes()).unwrap();
+
+        for (source, target, expected) in [
+            ("<u><target state=\"final\">T</target></u>", Some("T"), State::Final),
+            (
+                "<u><target state=\"signed-off\">T</target></u>",
+                Some("T"),
+                State::Final,
+            ),
+            (
+                "<u><target state=\"needs-l10n\">T</target></u>",
+                Some("T"),
+                State::NeedsWork,
+            ),
+            ("<u><target state=\"needs-translation\"/></u>", Some(""), State::Empty),
+            ("<u approved=\"no\"><target>T</target></u>", Some("T"), State::NeedsWork),
+            ("<u approved=\"yes\"><target>T</target></u>", Some("T"), State::Final),
+            ("<u><target/></u>", Some(""), State::Empty),
+            ("<u/>", None, State::Empty),
+        ] {
+            assert_eq!(make_state(&parse(source), target), expected, "State of {}", source);
+        }
+    }
+}
+// no-coverage:stop
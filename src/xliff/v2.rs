@@ -0,0 +1,291 @@
+use super::element::Element;
+use crate::{
+    error::{Error, ErrorKind},
+    location::Location,
+    note::Note,
+    plural::Plural,
+    unit::Unit,
+    Message, Origin, State,
+};
+
+/// Namespace of XLIFF 2.x documents.
+pub(super) const NAMESPACE: &str = "urn:oasis:names:tc:xliff:document:2.0";
+
+/// Type of the groups containing the plural variants of a gettext message.
+pub(super) const PLURALS_TYPE: &str = "x-gettext:plurals";
+
+/// Category of the note containing the context of a gettext message.
+pub(super) const MSGCTXT_CATEGORY: &str = "x-gettext-msgctxt";
+
+/// Category of the notes containing the locations.
+pub(super) const LOCATION_CATEGORY: &str = "location";
+
+/// Inline codes kept as markup in the messages.
+pub(super) const INLINE_CODES: [&str; 2] = ["ph", "pc"];
+
+fn make_note(note: &Element) -> Note {
+    let origin = match note.attribute("category") {
+        Some("developer") => Origin::Developer,
+        _ => Origin::Translator,
+    };
+
+    Note::new(origin, note.text())
+}
+
+/// Map the `state` attribute of a segment, the default state is `initial`.
+fn make_state(state: Option<&str>, target: Option<&str>) -> State {
+    match state {
+        Some("translated" | "reviewed" | "final") => State::Final,
+        _ if target.unwrap_or_default().is_empty() => State::Empty,
+        _ => State::NeedsWork,
+    }
+}
+
+fn notes(element: &Element) -> impl Iterator<Item = &Element> {
+    element
+        .elements_named("notes")
+        .flat_map(|notes| notes.elements_named("note"))
+}
+
+/// Read the notes of a `<file>` element.
+pub(super) fn read_file_notes(file: &Element) -> Vec<Note> {
+    notes(file).map(make_note).collect()
+}
+
+/// Read the units of a `<file>` element.
+pub(super) fn read_file(file: &Element, units: &mut Vec<Unit>) -> Result<(), Error> {
+    for element in file.elements() {
+        match element.name() {
+            "unit" => units.push(read_unit(element)?),
+            "group" if element.attribute("type") == Some(PLURALS_TYPE) => units.push(read_plural(element)?),
+            "group" => read_file(element, units)?,
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+fn read_notes(element: &Element, unit: &mut Unit) {
+    for note in notes(element) {
+        match note.attribute("category") {
+            Some(MSGCTXT_CATEGORY) => unit.context = Some(note.text()),
+            Some(LOCATION_CATEGORY) => unit.locations.push(Location::parse(&note.text())),
+            _ => unit.notes.push(make_note(note)),
+        }
+    }
+}
+
+/// Read the source, the target and the state of a `<unit>` element, segments are joined.
+///
+/// The target of an `<ignorable>` element without target is its source. With `markup`, the inline codes are kept and
+/// the text is escaped, else the text is read without the inline codes.
+fn read_content(unit: &Element, markup: bool) -> Result<(String, Option<String>, State), Error> {
+    let content = |element: &Element| {
+        if markup {
+            element.content(&INLINE_CODES)
+        } else {
+            element.text()
+        }
+    };
+
+    let mut source = String::new();
+    let mut target = String::new();
+    let mut has_target = false;
+    let mut state = None;
+    let mut has_segment = false;
+
+    for element in unit.elements() {
+        let is_segment = match element.name() {
+            "segment" => true,
+            "ignorable" => false,
+            _ => continue,
+        };
+
+        let part = match element.element("source") {
+            Some(s) => content(s),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("Missing ‘source’ element in ‘{}’", element.name()),
+                )
+                .with_line(element.line()));
+            }
+        };
+
+        match element.element("target") {
+            Some(t) => {
+                has_target |= is_segment;
+                target.push_str(&content(t));
+            }
+            None if is_segment => (),
+            None => target.push_str(&part),
+        }
+
+        if is_segment && !has_segment {
+            has_segment = true;
+            state = element.attribute("state");
+        }
+
+        source.push_str(&part);
+    }
+
+    if !has_segment {
+        return Err(Error::new(ErrorKind::Other, "Missing ‘segment’ element in ‘unit’").with_line(unit.line()));
+    }
+
+    let target = Some(target).filter(|_| has_target);
+    let state = make_state(state, target.as_deref());
+
+    Ok((source, target, state))
+}
+
+fn read_unit(element: &Element) -> Result<Unit, Error> {
+    let mut unit = Unit::default();
+    let markup = element.contains(&INLINE_CODES);
+    let (id, text, state) = read_content(element, markup)?;
+
+    read_notes(element, &mut unit);
+    unit.markup = markup;
+    unit.state = state;
+    unit.message = Message::Simple { id, text };
+
+    Ok(unit)
+}
+
+fn read_plural(group: &Element) -> Result<Unit, Error> {
+    let mut contents = vec![];
+    let markup = group.contains(&INLINE_CODES);
+
+    for element in group.elements_named("unit") {
+        contents.push(read_content(element, markup)?);
+    }
+
+    let mut unit = Unit::default();
+    let (singular, _, state) = match contents.first() {
+        Some(content) => content.clone(),
+        None => {
+            return Err(Error::new(ErrorKind::Other, "Missing ‘unit’ element in plural group").with_line(group.line()));
+        }
+    };
+
+    let plural = contents.get(1).map_or_else(|| singular.clone(), |c| c.0.clone());
+    let values: Vec<_> = contents.into_iter().map(|c| c.1.unwrap_or_default()).collect();
+
+    read_notes(group, &mut unit);
+    if let Some(first) = group.element("unit") {
+        read_notes(first, &mut unit);
+    }
+
+    unit.state = if values.iter().all(String::is_empty) {
+        State::Empty
+    } else {
+        state
+    };
+
+    unit.markup = markup;
+    unit.message = Message::Plural(Plural::new(singular, plural, values, None));
+
+    Ok(unit)
+}
+
+// no-coverage:start
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Element {
+        Element::parse(source.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_func_make_state() {
+        for (state, target, expected) in [
+            (Some("final"), Some("T"), State::Final),
+            (Some("reviewed"), Some("T"), State::Final),
+            (Some("translated"), Some("T"), State::Final),
+            (Some("initial"), Some("T"), State::NeedsWork),
+            (None, Some("T"), State::NeedsWork),
+            (Some("initial"), Some(""), State::Empty),
+            (None, None, State::Empty),
+        ] {
+            assert_eq!(
+                make_state(state, target),
+                expected,
+                "State {:?} with target {:?}",
+                state,
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn test_func_read_content() {
+        let unit = parse(
+            r#"<unit id="1">
+                <segment state="reviewed"><source>Hello &amp;</source><target>Salut &amp;</target></segment>
+                <ignorable><source> </source></ignorable>
+                <segment state="initial">
+                    <source>the <pc id="1">world</pc></source><target>le <pc id="1">monde</pc></target>
+                </segment>
+            </unit>"#,
+        );
+
+        assert_eq!(
+            read_content(&unit, true).unwrap(),
+            (
+                String::from(r#"Hello &amp; the <pc id="1">world</pc>"#),
+                Some(String::from(r#"Salut &amp; le <pc id="1">monde</pc>"#)),
+                State::Final,
+            )
+        );
+        assert_eq!(
+            read_content(&unit, false).unwrap(),
+            (
+                String::from("Hello & the world"),
+                Some(String::from("Salut & le monde")),
+                State::Final,
+            )
+        );
+
+        let unit = parse(
+            r#"<unit id="1"><segment><source>Hello</source></segment><ignorable><source> </source></ignorable></unit>"#,
+        );
+
+        assert_eq!(
+            read_content(&unit, false).unwrap(),
+            (String::from("Hello "), None, State::Empty)
+        );
+    }
+
+    #[test]
+    fn test_func_read_content_with_error() {
+        match read_content(&parse("<unit id=\"1\">\n<notes/></unit>"), false) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 1: Missing ‘segment’ element in ‘unit’"
+            ),
+            r => panic!("Unexpected result without segment: {:?}", r),
+        }
+
+        match read_content(&parse("<unit id=\"1\">\n<segment><target/></segment></unit>"), false) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 2: Missing ‘source’ element in ‘segment’"
+            ),
+            r => panic!("Unexpected result without source: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_func_read_plural_with_error() {
+        match read_plural(&parse("<group id=\"1\" type=\"x-gettext:plurals\"/>")) {
+            Err(err) => assert_eq!(
+                format!("{:?}", err),
+                "Unexpected error at line 1: Missing ‘unit’ element in plural group"
+            ),
+            r => panic!("Unexpected result for an empty plural group: {:?}", r),
+        }
+    }
+}
+// no-coverage:stop
@@ -0,0 +1,407 @@
+use super::v2::{LOCATION_CATEGORY, MSGCTXT_CATEGORY, NAMESPACE, PLURALS_TYPE};
+use crate::{error::Error, note::Note, unit::Unit, CatalogueReader, Message, Origin, State};
+use quick_xml::escape::{escape, partial_escape};
+use std::{
+    borrow::{Borrow, Cow},
+    io::Write,
+};
+
+/// Object for writing XLIFF 2.0 documents
+///
+/// Each call of a writing method writes a whole document with one `<file>` element. Obsolete units are not written.
+///
+/// The messages of plain text are escaped entirely, so a message which looks like inline codes (`<ph>` and `<pc>`
+/// tags) is written as text. The messages of the units whose messages are markup (see [`Unit::is_markup`]), like the
+/// units read from XLIFF 2.x with inline codes, are written as is, so their inline codes are kept.
+pub struct XliffWriter<W: Write> {
+    writer: W,
+    source_language: String,
+    target_language: Option<String>,
+    count: usize,
+}
+
+impl<W: Write> XliffWriter<W> {
+    pub fn new(writer: W) -> XliffWriter<W> {
+        XliffWriter {
+            writer,
+            source_language: String::from("en"),
+            target_language: None,
+            count: 0,
+        }
+    }
+
+    /// Set the language of the original messages, `en` by default.
+    pub fn set_source_language(&mut self, lang: &str) {
+        self.source_language = lang.to_string();
+    }
+
+    /// Set the language of the translations.
+    pub fn set_target_language(&mut self, lang: &str) {
+        self.target_language = Some(lang.to_string());
+    }
+
+    /// Write a document with the units given by an iterator, and the notes of the file.
+    pub fn write_units<I>(&mut self, notes: &[Note], units: I) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Unit>, {
+        self.write_document(notes, units.into_iter().map(Ok))
+    }
+
+    /// Write a whole catalogue, the header notes are the notes of the file.
+    ///
+    /// The target language is the one of the catalogue, unless it has been set before.
+    pub fn write_catalogue<C: CatalogueReader>(&mut self, catalogue: C) -> Result<(), Error> {
+        let lang = catalogue.target_language().to_string();
+
+        if self.target_language.is_none() && !lang.is_empty() {
+            self.target_language = Some(lang);
+        }
+
+        let notes = catalogue.header_notes().clone();
+
+        self.write_document(&notes, catalogue)
+    }
+
+    /// Get back the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_document<I, U>(&mut self, notes: &[Note], units: I) -> Result<(), Error>
+    where
+        I: Iterator<Item = Result<U, Error>>,
+        U: Borrow<Unit>, {
+        let mut root = format!(
+            "<xliff xmlns=\"{}\" version=\"2.0\" srcLang=\"{}\"",
+            NAMESPACE,
+            escape(&self.source_language)
+        );
+
+        if let Some(lang) = &self.target_language {
+            root.push_str(&format!(" trgLang=\"{}\"", escape(lang)));
+        }
+
+        root.push('>');
+        self.count = 0;
+        self.write_line(0, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        self.write_line(0, &root)?;
+        self.write_line(1, "<file id=\"f1\">")?;
+        self.write_notes(2, notes.iter().map(|n| note_element(n.origin(), n.value())).collect())?;
+
+        for unit in units {
+            let unit = unit?;
+            let unit = unit.borrow();
+
+            if !unit.obsolete {
+                self.write_unit(unit)?;
+            }
+        }
+
+        self.write_line(1, "</file>")?;
+        self.write_line(0, "</xliff>")?;
+        self.writer.flush().map_err(|err| Error::io(0, err))
+    }
+
+    fn write_unit(&mut self, unit: &Unit) -> Result<(), Error> {
+        let mut notes = vec![];
+
+        self.count += 1;
+        if let Some(context) = &unit.context {
+            notes.push(format!(
+                "<note category=\"{}\">{}</note>",
+                MSGCTXT_CATEGORY,
+                partial_escape(context)
+            ));
+        }
+
+        // No isolation marks are needed around the file names, as the note contains a single location
+        for location in unit.locations.iter() {
+            let text = match location.line() {
+                Some(line) => format!("{}:{}", location.file(), line),
+                None => location.file().to_string(),
+            };
+
+            notes.push(format!(
+                "<note category=\"{}\">{}</note>",
+                LOCATION_CATEGORY,
+                partial_escape(&text)
+            ));
+        }
+
+        notes.extend(unit.notes.iter().map(|n| note_element(n.origin(), n.value())));
+
+        let id = format!("u{}", self.count);
+
+        match &unit.message {
+            Message::Simple { id: source, text } => {
+                self.write_line(2, &format!("<unit id=\"{}\">", id))?;
+                self.write_notes(3, notes)?;
+                self.write_segment(3, unit, source, text.as_deref().unwrap_or_default())?;
+                self.write_line(2, "</unit>")
+            }
+            Message::Plural(plural) => {
+                self.write_line(2, &format!("<group id=\"{}\" type=\"{}\">", id, PLURALS_TYPE))?;
+                self.write_notes(3, notes)?;
+
+                let count = plural.values().len().max(2);
+
+                for i in 0..count {
+                    let source = if i == 0 { plural.singular() } else { plural.plural() };
+                    let target = plural.values().get(i).map(String::as_str).unwrap_or_default();
+
+                    self.write_line(3, &format!("<unit id=\"{}-{}\">", id, i))?;
+                    self.write_segment(4, unit, source, target)?;
+                    self.write_line(3, "</unit>")?;
+                }
+
+                self.write_line(2, "</group>")
+            }
+        }
+    }
+
+    fn write_notes(&mut self, depth: usize, notes: Vec<String>) -> Result<(), Error> {
+        if notes.is_empty() {
+            return Ok(());
+        }
+
+        self.write_line(depth, "<notes>")?;
+        for note in notes {
+            self.write_line(depth + 1, &note)?;
+        }
+
+        self.write_line(depth, "</notes>")
+    }
+
+    fn write_segment(&mut self, depth: usize, unit: &Unit, source: &str, target: &str) -> Result<(), Error> {
+        let state = if unit.state == State::Final { "translated" } else { "initial" };
+        let content = |text| if unit.markup { Cow::Borrowed(text) } else { partial_escape(text) };
+
+        self.write_line(depth, &format!("<segment state=\"{}\">", state))?;
+        self.write_line(depth + 1, &format!("<source>{}</source>", content(source)))?;
+        if !target.is_empty() {
+            self.write_line(depth + 1, &format!("<target>{}</target>", content(target)))?;
+        }
+
+        self.write_line(depth, "</segment>")
+    }
+
+    fn write_line(&mut self, depth: usize, line: &str) -> Result<(), Error> {
+        writeln!(self.writer, "{:width$}{}", "", line, width = depth * 2).map_err(|err| Error::io(0, err))
+    }
+}
+
+fn note_element(origin: &Origin, value: &str) -> String {
+    let category = match origin {
+        Origin::Developer => "developer",
+        Origin::Translator => "translator",
+    };
+
+    format!("<note category=\"{}\">{}</note>", category, partial_escape(value))
+}
+
+// no-coverage:start
+#[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
+mod tests {
+    use super::*;
+    use crate::{location::Location, plural::Plural, XliffReader};
+
+    fn write<F: FnOnce(&mut XliffWriter<Vec<u8>>) -> Result<(), Error>>(f: F) -> String {
+        let mut writer = XliffWriter::new(vec![]);
+
+        f(&mut writer).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    fn make_units() -> Vec<Unit> {
+        let mut simple = Unit::default();
+        let mut plural = Unit::default();
+        let mut obsolete = Unit::for_tests_incomplete();
+
+        simple.context = Some(String::from("Menu"));
+        simple.locations = vec![Location::new("main.c", Some(12))];
+        simple.notes = vec![Note::new(Origin::Developer, String::from("A <note>"))];
+        simple.state = State::Final;
+        simple.message = Message::Simple {
+            id: String::from("Open <ph id=\"1\"/> & close"),
+            text: Some(String::from("Ouvrir <ph id=\"1\"/> & fermer")),
+        };
+
+        plural.state = State::NeedsWork;
+        plural.message = Message::Plural(Plural::new(
+            String::from("One file"),
+            String::from("Several files"),
+            vec![String::from("Un fichier"), String::from("Des fichiers")],
+            None,
+        ));
+
+        obsolete.obsolete = true;
+
+        vec![simple, plural, obsolete]
+    }
+
+    #[test]
+    fn test_func_write_escaped() {
+        let texts = [
+            ("a < b && c > d", "a &lt; b &amp;&amp; c &gt; d"),
+            ("<pc id=\"1\">unbalanced", "&lt;pc id=\"1\"&gt;unbalanced"),
+            ("</pc> & <ph id=\"2\" disp=\"&lt;\"/>", "&lt;/pc&gt; &amp; &lt;ph id=\"2\" disp=\"&amp;lt;\"/&gt;"),
+        ];
+        let units: Vec<Unit> = texts
+            .iter()
+            .map(|(text, _)| {
+                let mut unit = Unit::default();
+
+                unit.message = Message::Simple {
+                    id: text.to_string(),
+                    text: Some(text.to_string()),
+                };
+                unit.state = State::Final;
+                unit
+            })
+            .collect();
+        let output = write(|w| w.write_units(&[], &units));
+
+        for (_, escaped) in texts {
+            assert!(
+                output.contains(&format!("<source>{}</source>", escaped)),
+                "Unexpected output:\n{}",
+                output
+            );
+        }
+
+        let reader = XliffReader::new(output.as_bytes()).unwrap();
+        let messages: Vec<Message> = reader.map(|u| u.unwrap().message().clone()).collect();
+
+        assert_eq!(messages, units.iter().map(|u| u.message().clone()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_func_write_inline_codes() {
+        let source = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en" trgLang="fr">
+  <file id="f1">
+    <unit id="u1">
+      <segment state="translated">
+        <source>Hello <ph id="1" disp="&lt;br&gt;"/> &lt;ph id="2"/&gt; &amp; <pc id="3">bye</pc></source>
+        <target>Salut <ph id="1" disp="&lt;br&gt;"/> &lt;ph id="2"/&gt; &amp; <pc id="3">au revoir</pc></target>
+      </segment>
+    </unit>
+    <unit id="u2">
+      <segment state="translated">
+        <source>Plain &lt;ph id="1"/&gt; &amp; text</source>
+        <target>Texte &lt;ph id="1"/&gt; &amp; brut</target>
+      </segment>
+    </unit>
+  </file>
+</xliff>
+"#;
+        let units: Vec<Unit> = XliffReader::new(source.as_bytes())
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        assert!(units[0].is_markup(), "The unit with inline codes should be markup");
+        assert!(!units[1].is_markup(), "The unit without inline codes should be plain text");
+        assert_eq!(units[1].message().get_id(), r#"Plain <ph id="1"/> & text"#);
+
+        let output = write(|w| {
+            w.set_target_language("fr");
+            w.write_units(&[], &units)
+        });
+
+        assert_eq!(output, source);
+    }
+
+    #[test]
+    fn test_func_write_units() {
+        let output = write(|w| {
+            w.set_source_language("en");
+            w.set_target_language("fr");
+            w.write_units(
+                &[Note::new(Origin::Translator, String::from("File note"))],
+                make_units(),
+            )
+        });
+
+        assert_eq!(
+            output,
+            "\
+                <?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                <xliff xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" version=\"2.0\" srcLang=\"en\" trgLang=\"fr\">\n\
+                \x20 <file id=\"f1\">\n\
+                \x20   <notes>\n\
+                \x20     <note category=\"translator\">File note</note>\n\
+                \x20   </notes>\n\
+                \x20   <unit id=\"u1\">\n\
+                \x20     <notes>\n\
+                \x20       <note category=\"x-gettext-msgctxt\">Menu</note>\n\
+                \x20       <note category=\"location\">main.c:12</note>\n\
+                \x20       <note category=\"developer\">A &lt;note&gt;</note>\n\
+                \x20     </notes>\n\
+                \x20     <segment state=\"translated\">\n\
+                \x20       <source>Open &lt;ph id=\"1\"/&gt; &amp; close</source>\n\
+                \x20       <target>Ouvrir &lt;ph id=\"1\"/&gt; &amp; fermer</target>\n\
+                \x20     </segment>\n\
+                \x20   </unit>\n\
+                \x20   <group id=\"u2\" type=\"x-gettext:plurals\">\n\
+                \x20     <unit id=\"u2-0\">\n\
+                \x20       <segment state=\"initial\">\n\
+                \x20         <source>One file</source>\n\
+                \x20         <target>Un fichier</target>\n\
+                \x20       </segment>\n\
+                \x20     </unit>\n\
+                \x20     <unit id=\"u2-1\">\n\
+                \x20       <segment state=\"initial\">\n\
+                \x20         <source>Several files</source>\n\
+                \x20         <target>Des fichiers</target>\n\
+                \x20       </segment>\n\
+                \x20     </unit>\n\
+                \x20   </group>\n\
+                \x20 </file>\n\
+                </xliff>\n\
+            "
+        );
+    }
+
+    #[test]
+    fn test_func_write_catalogue() {
+        let units = make_units();
+        let output = write(|w| w.write_units(&[], &units));
+        let mut reader = XliffReader::new(output.as_bytes()).unwrap();
+
+        assert_eq!(reader.target_language().as_ref(), "");
+        for unit in units.iter().take(2) {
+            match reader.next() {
+                Some(Ok(u)) => {
+                    assert_eq!(u.context(), unit.context());
+                    assert_eq!(u.message(), unit.message());
+                    assert_eq!(u.locations(), unit.locations());
+                    assert_eq!(u.notes(), unit.notes());
+                    assert_eq!(u.state(), unit.state());
+                }
+                r => panic!("Unexpected result for {:?}: {:?}", unit.message(), r),
+            }
+        }
+
+        assert!(reader.next().is_none(), "There should be no more unit");
+
+        let output = write(|w| w.write_catalogue(XliffReader::new(output.as_bytes())?));
+        let reader = XliffReader::new(output.as_bytes()).unwrap();
+
+        assert_eq!(reader.count(), 2);
+
+        let source =
+            "<xliff version=\"1.2\"><file target-language=\"de\"><header><note>N</note></header></file></xliff>";
+        let output = write(|w| w.write_catalogue(XliffReader::new(source.as_bytes())?));
+        let reader = XliffReader::new(output.as_bytes()).unwrap();
+
+        assert_eq!(reader.target_language().as_ref(), "de");
+        assert_eq!(
+            reader.header_notes(),
+            &vec![Note::new(Origin::Translator, String::from("N"))]
+        );
+    }
+}
+// no-coverage:stop
@@ -1,8 +1,19 @@
 // no-coverage:start
+use encoding_rs::{ISO_8859_15, ISO_8859_2};
 use locale_config::LanguageRange;
-use poreader::{error::Error, note::Note, CatalogueReader, Message, Origin, PoParser, State};
-
-static SAMPLE_PO: &'static str = r###"
+use poreader::{
+    error::{Error, ErrorKind},
+    flag::{Flag, FormatLanguage},
+    format::check_catalog,
+    location::Location,
+    note::Note,
+    plural::PluralCategory,
+    unit::Unit,
+    Catalog, CatalogueReader, Merger, Message, MoCompiler, MoReader, Origin, PoParser, PoWriter, State, TextDomains,
+    XliffReader, XliffWriter,
+};
+
+static SAMPLE_PO: &str = r###"
 msgid ""
 msgstr ""
 "Project-Id-Version: poreader test\n"
@@ -44,7 +55,7 @@ msgstr[2] "Des messages avec plusieurs traductions"
 
 # Another comment
 #~ msgid "Obsolete message"
-#~ msgstr "Message obsolète"
+#~ msgstr "Message périmé"
 
 "###;
 
@@ -54,11 +65,19 @@ macro_rules! a_str {
     };
 }
 
+/// Get the sample in its charset, ISO-8859-2.
+fn sample_po() -> Vec<u8> {
+    let (encoded, _, unmappable) = ISO_8859_2.encode(SAMPLE_PO);
+
+    assert!(!unmappable, "The sample should be written in ISO-8859-2");
+    encoded.into_owned()
+}
+
 #[test]
 fn integration_test() -> Result<(), Error> {
-    let input = SAMPLE_PO.as_bytes();
+    let input = sample_po();
     let parser = PoParser::new();
-    let mut reader = parser.parse(input)?;
+    let mut reader = parser.parse(&input[..])?;
     let lang = LanguageRange::new("fr").unwrap();
 
     assert_eq!(reader.target_language(), &lang);
@@ -134,7 +153,10 @@ fn integration_test() -> Result<(), Error> {
             ]
         );
 
-        assert_eq!(u.locations(), &vec![a_str!("Location:42"), a_str!("Another:69"),]);
+        assert_eq!(
+            u.locations(),
+            &vec![Location::new("Location", Some(42)), Location::new("Another", Some(69))]
+        );
 
         assert!(!u.is_translated(), "It should not be translated");
         assert!(!u.is_obsolete(), "It should not be obsolete");
@@ -179,7 +201,7 @@ fn integration_test() -> Result<(), Error> {
         let empty_msg = Message::default();
         let msg = Message::Simple {
             id: a_str!("Obsolete message"),
-            text: Some(a_str!("Message obsolète")),
+            text: Some(a_str!("Message périmé")),
         };
 
         assert!(u.is_obsolete(), "This entry should be obsolete");
@@ -201,4 +223,702 @@ fn integration_test() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn write_test() -> Result<(), Error> {
+    let input = sample_po();
+    let parser = PoParser::new();
+    let mut writer = PoWriter::new(vec![]);
+
+    writer.write_catalogue(parser.parse(&input[..])?)?;
+
+    let output = writer.into_inner();
+    let mut original = parser.parse(&input[..])?;
+    let mut written = parser.parse(&output[..])?;
+
+    assert_eq!(written.header_properties(), original.header_properties());
+    assert_eq!(written.header_notes(), original.header_notes());
+
+    loop {
+        match (original.next(), written.next()) {
+            (None, None) => break,
+            (Some(o), Some(w)) => {
+                let (o, w) = (o?, w?);
+
+                assert_eq!(w.context(), o.context());
+                assert_eq!(w.message(), o.message());
+                assert_eq!(w.message().get_text(), o.message().get_text());
+                assert_eq!(w.prev_context(), o.prev_context());
+                assert_eq!(w.prev_message(), o.prev_message());
+                assert_eq!(w.flags(), o.flags());
+                for origin in [Origin::Developer, Origin::Translator] {
+                    assert_eq!(
+                        w.notes().iter().filter(|n| *n.origin() == origin).collect::<Vec<_>>(),
+                        o.notes().iter().filter(|n| *n.origin() == origin).collect::<Vec<_>>(),
+                    );
+                }
+                assert_eq!(w.locations(), o.locations());
+                assert_eq!(w.comments(), o.comments());
+                assert_eq!(w.state(), o.state());
+                assert_eq!(w.is_obsolete(), o.is_obsolete());
+            }
+            (o, w) => panic!("Streams differ: {:?} != {:?}", o, w),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mo_test() -> Result<(), Error> {
+    let input = sample_po();
+    let parser = PoParser::new();
+    let mut output = vec![];
+
+    MoCompiler::new().compile(parser.parse(&input[..])?, &mut output)?;
+
+    let original = parser.parse(&input[..])?;
+    let mut reader = MoReader::new(&output[..])?;
+
+    assert_eq!(reader.header_properties(), original.header_properties());
+    assert_eq!(reader.target_language(), original.target_language());
+
+    let mut expected: Vec<_> = original
+        .filter(|u| u.as_ref().map_or(true, |u| u.is_translated() && !u.is_obsolete()))
+        .collect::<Result<_, _>>()?;
+
+    // Units of MO files are sorted by message
+    expected.sort_by(|a, b| a.message().get_id().cmp(b.message().get_id()));
+
+    for unit in expected {
+        match reader.next() {
+            Some(Ok(u)) => {
+                assert_eq!(u.context(), unit.context());
+                assert_eq!(u.message(), unit.message());
+                assert_eq!(u.state(), State::Final);
+            }
+            r => panic!("Unexpected result for {:?}: {:?}", unit.message(), r),
+        }
+    }
+
+    assert!(reader.next().is_none(), "There should be no more unit");
+
+    Ok(())
+}
+
+#[test]
+fn xliff_test() -> Result<(), Error> {
+    let source = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+  <file original="sample.po" source-language="en" target-language="fr" datatype="po">
+    <body>
+      <trans-unit id="1" approved="yes">
+        <source>Simple message</source>
+        <target>Un simple message</target>
+      </trans-unit>
+      <trans-unit id="2" approved="no">
+        <source>Changed message</source>
+        <target>Message
+changé</target>
+        <note from="developer">Extracted comment</note>
+        <note from="po-translator">Translator comment</note>
+        <context-group name="po-reference" purpose="location">
+          <context context-type="sourcefile">Location</context>
+          <context context-type="linenumber">42</context>
+        </context-group>
+        <context-group name="po-reference" purpose="location">
+          <context context-type="sourcefile">Another</context>
+          <context context-type="linenumber">69</context>
+        </context-group>
+        <context-group name="po-entry" purpose="information">
+          <context context-type="x-gettext-msgctxt">ConTeXt</context>
+        </context-group>
+      </trans-unit>
+      <trans-unit id="3">
+        <source>Untranslated message</source>
+        <target></target>
+      </trans-unit>
+      <group id="4" restype="x-gettext-plurals">
+        <trans-unit id="4[0]">
+          <source>A message with several translations</source>
+          <target>Un message avec plusieurs traductions</target>
+        </trans-unit>
+        <trans-unit id="4[1]">
+          <source>Some messages with several translations</source>
+          <target>Quelques messages avec plusieurs traductions</target>
+        </trans-unit>
+        <trans-unit id="4[2]">
+          <source>Some messages with several translations</source>
+          <target>Des messages avec plusieurs traductions</target>
+        </trans-unit>
+      </group>
+    </body>
+  </file>
+</xliff>
+"#;
+
+    let input = sample_po();
+    let parser = PoParser::new();
+    let mut original = parser.parse(&input[..])?;
+    let mut reader = XliffReader::new(source.as_bytes())?;
+
+    assert_eq!(reader.target_language(), original.target_language());
+
+    for _ in 0..4 {
+        match (original.next(), reader.next()) {
+            (Some(o), Some(x)) => {
+                let (o, x) = (o?, x?);
+
+                assert_eq!(x.context(), o.context());
+                assert_eq!(x.message().get_id(), o.message().get_id());
+                assert_eq!(x.message().get_plural_id(), o.message().get_plural_id());
+                assert_eq!(x.message().get_text(), o.message().get_text());
+                assert_eq!(x.notes(), o.notes());
+                assert_eq!(x.locations(), o.locations());
+                assert_eq!(x.state(), o.state());
+            }
+            (o, x) => panic!("Streams differ: {:?} != {:?}", o, x),
+        }
+    }
+
+    assert!(reader.next().is_none(), "There should be no more unit");
+
+    Ok(())
+}
+
+#[test]
+fn xliff_write_test() -> Result<(), Error> {
+    let input = sample_po();
+    let parser = PoParser::new();
+    let mut writer = XliffWriter::new(vec![]);
+
+    writer.write_catalogue(parser.parse(&input[..])?)?;
+
+    let output = writer.into_inner();
+    let mut original = parser.parse(&input[..])?;
+    let mut reader = XliffReader::new(&output[..])?;
+
+    assert_eq!(reader.target_language(), original.target_language());
+
+    for _ in 0..4 {
+        match (original.next(), reader.next()) {
+            (Some(o), Some(x)) => {
+                let (o, x) = (o?, x?);
+
+                assert_eq!(x.context(), o.context());
+                assert_eq!(x.message(), o.message());
+                assert_eq!(x.notes(), o.notes());
+                assert_eq!(x.locations(), o.locations());
+                assert_eq!(x.state(), o.state());
+            }
+            (o, x) => panic!("Streams differ: {:?} != {:?}", o, x),
+        }
+    }
+
+    assert!(reader.next().is_none(), "There should be no more unit");
+
+    Ok(())
+}
+
+#[test]
+fn charset_test() -> Result<(), Error> {
+    let source = SAMPLE_PO.replace("charset=ISO-8859-2", "charset=ISO-8859-15");
+    let (encoded, ..) = ISO_8859_15.encode(&source);
+    let input = sample_po();
+    let mut parser = PoParser::new();
+    let mut original = parser.parse(&input[..])?;
+    let mut reader = parser.parse(&encoded[..])?;
+
+    for _ in 0..5 {
+        match (original.next(), reader.next()) {
+            (Some(o), Some(r)) => {
+                let (o, r) = (o?, r?);
+
+                assert_eq!(r.message(), o.message());
+                assert_eq!(r.notes(), o.notes());
+                assert_eq!(r.comments(), o.comments());
+            }
+            (o, r) => panic!("Streams differ: {:?} != {:?}", o, r),
+        }
+    }
+
+    assert!(reader.next().is_none(), "There should be no more unit");
+
+    let mut writer = PoWriter::new(vec![]);
+
+    parser.set_lossless(true);
+    writer.write_catalogue(parser.parse(&encoded[..])?)?;
+
+    assert_eq!(writer.into_inner(), encoded.as_ref());
+
+    Ok(())
+}
+
+#[test]
+fn span_test() -> Result<(), Error> {
+    let encoded = sample_po();
+    let parser = PoParser::new();
+    let units = parser.parse(&encoded[..])?.collect::<Result<Vec<_>, _>>()?;
+    let text = |unit: &Unit, field: &str| {
+        let span = unit.field_span(field).expect("The field should have a span");
+
+        ISO_8859_2
+            .decode_without_bom_handling(&encoded[span.start().offset()..span.end().offset()])
+            .0
+            .into_owned()
+    };
+
+    assert_eq!(text(&units[1], "msgstr"), "msgstr \"Message\\n\"\n\"changé\"");
+    assert_eq!(text(&units[1], "|msgid"), "#| msgid \"Previous message\"");
+    assert_eq!(
+        text(&units[3], "msgstr[2]"),
+        "msgstr[2] \"Des messages avec plusieurs traductions\""
+    );
+    assert_eq!(text(&units[4], "msgstr"), "#~ msgstr \"Message périmé\"");
+    assert_eq!(units[1].comment_spans().len(), 4);
+
+    let span = units[4].field_span("msgstr").unwrap();
+
+    assert_eq!((span.start().line(), span.start().column()), (43, 1));
+    assert_eq!((span.end().line(), span.end().column()), (43, 27));
+
+    Ok(())
+}
+
+#[test]
+fn error_kind_test() {
+    let parser = PoParser::new();
+    let source = SAMPLE_PO.replace("msgstr \"Un simple message\"\n", "");
+    let (input, ..) = ISO_8859_2.encode(&source);
+
+    match parser
+        .parse(&input[..])
+        .map(|reader| reader.collect::<Result<Vec<_>, _>>())
+    {
+        Ok(Err(err)) => {
+            let err = err.with_path("fr.po");
+
+            assert_eq!(err.kind(), ErrorKind::MissingMsgstr);
+            assert_eq!(err.expected(), Some("msgstr"));
+            assert_eq!(err.line(), 20);
+            assert_eq!(
+                err.to_string(),
+                "fr.po: Parse error at line 20, column 1 expected ‘msgstr’, got ‘#.’"
+            );
+        }
+        r => panic!("Unexpected result: {:?}", r.map(|r| r.map(|u| u.len()))),
+    }
+}
+
+#[test]
+fn lossless_test() -> Result<(), Error> {
+    let mut parser = PoParser::new();
+    let mut writer = PoWriter::new(vec![]);
+
+    parser.set_lossless(true);
+    writer.write_catalogue(parser.parse(&sample_po()[..])?)?;
+
+    assert_eq!(writer.into_inner(), sample_po());
+
+    Ok(())
+}
+
+#[test]
+fn edit_and_write_test() -> Result<(), Error> {
+    let source = "msgid \"\"\n\
+                  msgstr \"\"\n\
+                  \"Content-Type: text/plain; charset=UTF-8\\n\"\n\
+                  \n\
+                  msgid \"Open the file\"\n\
+                  msgstr \"Ouvre le fichier\"\n";
+    let mut parser = PoParser::new();
+
+    parser.set_lossless(true);
+
+    let mut unit = parser.parse(source.as_bytes())?.next().unwrap()?;
+    let mut flags = unit.flags().clone();
+
+    flags.insert(Flag::Format(FormatLanguage::C));
+    unit.set_flags(flags);
+    unit.set_message(Message::Simple {
+        id: String::from("Open the file"),
+        text: Some(String::from("Ouvrir le fichier")),
+    });
+    unit.set_state(State::NeedsWork);
+
+    let mut added = Unit::new(
+        Some(String::from("menu")),
+        Message::Simple {
+            id: String::from("Close"),
+            text: Some(String::from("Fermer")),
+        },
+    );
+
+    added.set_locations(vec![Location::new("src/menu.rs", Some(12))]);
+
+    let mut writer = PoWriter::new(vec![]);
+
+    writer.write_units(vec![unit, added])?;
+    assert_eq!(
+        String::from_utf8(writer.into_inner()).unwrap(),
+        "#, fuzzy, c-format\n\
+         msgid \"Open the file\"\n\
+         msgstr \"Ouvrir le fichier\"\n\
+         \n\
+         #: src/menu.rs:12\n\
+         msgctxt \"menu\"\n\
+         msgid \"Close\"\n\
+         msgstr \"Fermer\"\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn catalog_test() -> Result<(), Error> {
+    let mut parser = PoParser::new();
+    let mut catalog = Catalog::from_reader(parser.parse(&sample_po()[..])?)?;
+
+    assert_eq!(catalog.len(), 5);
+    assert_eq!(catalog.target_language().as_ref(), "fr");
+    assert!(catalog.get(None, "Changed message").is_none());
+    assert_eq!(
+        catalog
+            .get(Some("ConTeXt"), "Changed message")
+            .map(|u| u.message().get_text()),
+        Some("Message\nchangé")
+    );
+    assert_eq!(
+        catalog
+            .get_by_plural(None, "Some messages with several translations")
+            .map(|u| u.message().get_id()),
+        Some("A message with several translations")
+    );
+
+    assert_eq!(catalog.gettext("Simple message"), "Un simple message");
+    assert_eq!(catalog.gettext("Untranslated message"), "Untranslated message");
+    assert_eq!(catalog.pgettext("ConTeXt", "Changed message"), "Changed message");
+    assert_eq!(
+        catalog.ngettext(
+            "A message with several translations",
+            "Some messages with several translations",
+            3
+        ),
+        "Quelques messages avec plusieurs traductions"
+    );
+    assert_eq!(
+        catalog.ngettext(
+            "A message with several translations",
+            "Some messages with several translations",
+            5
+        ),
+        "Des messages avec plusieurs traductions"
+    );
+
+    catalog.set_allow_fuzzy(true);
+    assert_eq!(catalog.pgettext("ConTeXt", "Changed message"), "Message\nchangé");
+
+    let unit = catalog.remove(None, "Untranslated message").unwrap();
+
+    assert!(!catalog.contains(None, "Untranslated message"));
+    assert!(catalog.insert(unit).is_none());
+    assert_eq!(
+        catalog.iter().last().map(|u| u.message().get_id()),
+        Some("Untranslated message")
+    );
+
+    let mut writer = PoWriter::new(vec![]);
+
+    parser.set_lossless(true);
+    writer.write_catalogue(Catalog::from_reader(parser.parse(&sample_po()[..])?)?.into_iter())?;
+    assert_eq!(writer.into_inner(), sample_po());
+
+    Ok(())
+}
+
+#[test]
+fn domain_test() -> Result<(), Error> {
+    let locale_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/locale");
+    let mut domains = TextDomains::new();
+
+    domains.set_language("pt_BR")?;
+    domains.bind_text_domain("app", &locale_dir)?;
+    domains.bind_text_domain("lib", &locale_dir)?;
+    domains.set_text_domain("app");
+
+    assert_eq!(domains.gettext("Hello"), "Oi");
+    assert_eq!(domains.gettext("Goodbye"), "Adeus");
+    assert_eq!(domains.ngettext("One file", "%d files", 3), "%d arquivos");
+    assert_eq!(domains.dpgettext("lib", "menu", "Open"), "Open");
+
+    domains.set_language("fr_FR")?;
+    assert_eq!(domains.gettext("Hello"), "Hello");
+    assert_eq!(domains.dgettext("lib", "Hello"), "Bonjour");
+    assert_eq!(domains.dpgettext("lib", "menu", "Open"), "Ouvrir");
+
+    Ok(())
+}
+
+#[test]
+fn merge_test() -> Result<(), Error> {
+    let parser = PoParser::new();
+    let definitions = Catalog::from_reader(parser.parse(&sample_po()[..])?)?;
+    let template = Catalog::from_reader(
+        parser.parse(
+            r#"
+msgid "Simple message"
+msgstr ""
+
+#: src/lib.rs:12
+msgid "A simple message"
+msgstr ""
+"#
+            .as_bytes(),
+        )?,
+    )?;
+
+    let catalog = Merger::new().merge(&definitions, &template);
+    let units: Vec<_> = catalog
+        .iter()
+        .map(|u| (u.context(), u.message().get_id(), u.is_obsolete(), u.state()))
+        .collect();
+
+    assert_eq!(
+        units,
+        vec![
+            (None, "Simple message", false, State::Final),
+            (None, "A simple message", false, State::NeedsWork),
+            (Some("ConTeXt"), "Changed message", true, State::NeedsWork),
+            (None, "A message with several translations", true, State::Final),
+            (None, "Obsolete message", true, State::Final),
+        ]
+    );
+
+    let unit = catalog.get(None, "A simple message").unwrap();
+
+    assert_eq!(unit.message().get_text(), "Un simple message");
+    assert_eq!(unit.prev_message().get_id(), "Simple message");
+    assert_eq!(unit.locations(), &vec![Location::new("src/lib.rs", Some(12))]);
+    assert_eq!(catalog.target_language().as_ref(), "fr");
+
+    Ok(())
+}
+
+#[test]
+fn location_test() -> Result<(), Error> {
+    let source = "#: src/main.rs:12 \u{2068}my file.c\u{2069}:7 \u{2068}README with spaces\u{2069}\n\
+                  msgid \"Hello\"\n\
+                  msgstr \"Bonjour\"\n";
+    let parser = PoParser::new();
+    let units: Vec<Unit> = parser.parse(source.as_bytes())?.collect::<Result<_, _>>()?;
+    let expected = vec![
+        Location::new("src/main.rs", Some(12)),
+        Location::new("my file.c", Some(7)),
+        Location::new("README with spaces", None),
+    ];
+
+    assert_eq!(units[0].locations(), &expected);
+
+    let mut writer = PoWriter::new(vec![]);
+
+    writer.write_unit(&units[0])?;
+    assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), source);
+
+    let mut writer = XliffWriter::new(vec![]);
+
+    writer.write_catalogue(parser.parse(source.as_bytes())?)?;
+
+    let units: Vec<Unit> = XliffReader::new(&writer.into_inner()[..])?.collect::<Result<_, _>>()?;
+
+    assert_eq!(units[0].locations(), &expected);
+
+    Ok(())
+}
+
+#[test]
+fn flag_test() -> Result<(), Error> {
+    let source = "#, fuzzy, c-format, no-python-format, range: 1..5, my-flag\n\
+                  msgid \"%d file\"\n\
+                  msgid_plural \"%d files\"\n\
+                  msgstr[0] \"%d fichier\"\n\
+                  msgstr[1] \"%d fichiers\"\n";
+    let parser = PoParser::new();
+    let units: Vec<Unit> = parser.parse(source.as_bytes())?.collect::<Result<_, _>>()?;
+    let unit = &units[0];
+
+    assert_eq!(unit.state(), State::NeedsWork);
+    assert!(unit.flags().contains(&Flag::Fuzzy));
+    assert!(unit.flags().contains(&Flag::NoFormat(FormatLanguage::Python)));
+    assert!(unit.flags().contains(&Flag::Other(String::from("my-flag"))));
+    assert_eq!(unit.format_languages(), vec![FormatLanguage::C]);
+    assert_eq!(unit.range(), Some(1..=5));
+
+    let mut writer = PoWriter::new(vec![]);
+
+    writer.write_unit(unit)?;
+    assert_eq!(
+        String::from_utf8(writer.into_inner()).unwrap(),
+        source.replace(
+            "fuzzy, c-format, no-python-format, range: 1..5, my-flag",
+            "fuzzy, c-format, my-flag, no-python-format, range: 1..5"
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn format_test() -> Result<(), Error> {
+    let source = "#: src/main.c:12\n\
+                  #, c-format\n\
+                  msgid \"%s has %d files\"\n\
+                  msgstr \"%d fichiers pour %s\"\n\
+                  \n\
+                  #, c-format\n\
+                  msgid \"%1$s has %2$d files\"\n\
+                  msgstr \"%2$d fichiers pour %1$s\"\n\
+                  \n\
+                  #, c-format\n\
+                  msgid \"Width %*d\"\n\
+                  msgstr \"Largeur %d\"\n";
+    let parser = PoParser::new();
+    let catalog = Catalog::from_reader(parser.parse(source.as_bytes())?)?;
+    let issues = check_catalog(&catalog);
+    let lines: Vec<(String, Option<usize>)> = issues
+        .iter()
+        .map(|i| (i.field().to_string(), i.span().map(|s| s.start().line())))
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            (String::from("msgstr"), Some(4)),
+            (String::from("msgstr"), Some(4)),
+            (String::from("msgstr"), Some(12)),
+        ]
+    );
+    assert!(issues[0].to_string().starts_with("src/main.c:12: "));
+    assert_eq!(
+        issues[2].message(),
+        "number of format specifications in ‘msgid’ and ‘msgstr’ does not match (2 and 1)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn format_placeholder_test() -> Result<(), Error> {
+    let source = "msgid \"\"\n\
+                  msgstr \"\"\n\
+                  \"Plural-Forms: nplurals=2; plural=n>1;\\n\"\n\
+                  \n\
+                  #, rust-format\n\
+                  msgid \"{count} file in {dir}\"\n\
+                  msgid_plural \"{count} files in {dir}\"\n\
+                  msgstr[0] \"Un fichier dans {dir}\"\n\
+                  msgstr[1] \"{count} fichiers dans {folder}\"\n";
+    let parser = PoParser::new();
+    let catalog = Catalog::from_reader(parser.parse(source.as_bytes())?)?;
+    let messages: Vec<String> = check_catalog(&catalog)
+        .iter()
+        .map(|i| i.message().to_string())
+        .collect();
+
+    assert_eq!(
+        messages,
+        vec![
+            "a format specification for argument ‘dir’ doesn't exist in ‘msgstr[1]’",
+            "a format specification for argument ‘folder’, as in ‘msgstr[1]’, doesn't exist in ‘msgid_plural’",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn plural_check_test() -> Result<(), Error> {
+    let source = "msgid \"\"\n\
+                  msgstr \"\"\n\
+                  \"Plural-Forms: nplurals=3; plural=n==1 ? 0 : n<5 ? 1 : 3;\\n\"\n\
+                  \n\
+                  msgid \"One file\"\n\
+                  msgid_plural \"%d files\"\n\
+                  msgstr[0] \"Jeden plik\"\n\
+                  msgstr[1] \"%d pliki\"\n";
+    let parser = PoParser::new();
+    let catalog = Catalog::from_reader(parser.parse(source.as_bytes())?)?;
+    let errors = catalog.check_plurals();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind(), ErrorKind::InvalidPluralFormula { offset: Some(19) });
+    assert_eq!(
+        errors[0].message(),
+        "the plural formula gives the form 3 for n = 5, but ‘nplurals’ is 3"
+    );
+    assert_eq!(errors[1].kind(), ErrorKind::PluralCountMismatch);
+    assert_eq!(errors[1].line(), 6);
+
+    Ok(())
+}
+
+#[test]
+fn plural_rule_fallback_test() -> Result<(), Error> {
+    let source = "msgid \"\"\n\
+                  msgstr \"\"\n\
+                  \"Language: pl\\n\"\n\
+                  \n\
+                  msgid \"One file\"\n\
+                  msgid_plural \"%d files\"\n\
+                  msgstr[0] \"%d plik\"\n\
+                  msgstr[1] \"%d pliki\"\n\
+                  msgstr[2] \"%d plików\"\n";
+    let parser = PoParser::new();
+    let catalog = Catalog::from_reader(parser.parse(source.as_bytes())?)?;
+
+    assert!(catalog.check_plurals().is_empty());
+    assert_eq!(catalog.ngettext("One file", "%d files", 1), "%d plik");
+    assert_eq!(catalog.ngettext("One file", "%d files", 3), "%d pliki");
+    assert_eq!(catalog.ngettext("One file", "%d files", 5), "%d plików");
+
+    let mut catalog = Catalog::new();
+
+    catalog.set_target_language(LanguageRange::new("fr-CA").unwrap());
+    assert_eq!(catalog.header_properties()["Language"], "fr_CA");
+    assert_eq!(
+        catalog.header_properties()["Plural-Forms"],
+        "nplurals=2; plural=(n > 1);"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn plural_category_test() -> Result<(), Error> {
+    let source = "msgid \"\"\n\
+                  msgstr \"\"\n\
+                  \"Language: ru\\n\"\n\
+                  \n\
+                  msgid \"One file\"\n\
+                  msgid_plural \"%d files\"\n\
+                  msgstr[0] \"%d файл\"\n\
+                  msgstr[1] \"%d файла\"\n\
+                  msgstr[2] \"%d файлов\"\n";
+    let parser = PoParser::new();
+    let catalog = Catalog::from_reader(parser.parse(source.as_bytes())?)?;
+    let language = catalog.target_language();
+    let plural = catalog.get(None, "One file").unwrap().message().plural().unwrap();
+
+    assert_eq!(
+        plural.get_forms().unwrap().categories(language),
+        Some(vec![PluralCategory::One, PluralCategory::Few, PluralCategory::Many])
+    );
+    assert_eq!(plural.get_by_category(PluralCategory::Few, language), Some("%d файла"));
+    assert_eq!(
+        plural.get_by_category(PluralCategory::Many, language),
+        Some("%d файлов")
+    );
+    assert_eq!(plural.get_by_category(PluralCategory::Other, language), None);
+
+    Ok(())
+}
+
 // no-coverage:stop